@@ -303,6 +303,7 @@ mod test {
                     ),
                     cln: Some(cln.clone()),
                     lnd: None,
+                    eclair: None,
                     chain: None,
                 })
             });
@@ -370,6 +371,7 @@ mod test {
                     ),
                     cln: Some(cln.clone()),
                     lnd: None,
+                    eclair: None,
                     chain: None,
                 })
             });
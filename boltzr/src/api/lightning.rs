@@ -1,16 +1,25 @@
 use crate::api::ServerState;
 use crate::api::errors::{ApiError, AxumError};
 use crate::api::ws::status::SwapInfos;
+use crate::service::{Channel, GraphCursor, LookupError};
 use crate::swap::manager::SwapManager;
 use alloy::hex;
 use anyhow::Result;
 use axum::extract::Path;
 use axum::extract::Query;
 use axum::http::StatusCode;
+use axum::http::header::CACHE_CONTROL;
 use axum::response::IntoResponse;
 use axum::{Extension, Json};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+const TOP_NODES_DEFAULT_LIMIT: usize = 10;
+const TOP_NODES_MAX_LIMIT: usize = 100;
+
+const GRAPH_DEFAULT_LIMIT: usize = 1_000;
+const GRAPH_MAX_LIMIT: usize = 5_000;
 
 #[derive(Deserialize)]
 pub struct LightningInfoParams {
@@ -24,9 +33,85 @@ pub struct ChannelInfoParams {
     id: String,
 }
 
+#[derive(Deserialize)]
+pub struct ChannelQuery {
+    active: Option<bool>,
+    // Skips channels whose policy was not updated for longer than that
+    #[serde(rename = "maxAgeSecs")]
+    max_age_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct FeeQuery {
+    #[serde(rename = "amountMsat")]
+    amount_msat: u64,
+}
+
+#[derive(Deserialize)]
+pub struct TopNodesQuery {
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct GraphQuery {
+    #[serde(rename = "afterNode")]
+    after_node: Option<String>,
+    #[serde(rename = "afterChannel")]
+    after_channel: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct SearchIdParams {
+    currency: String,
+    prefix: String,
+}
+
 #[derive(Deserialize)]
 pub struct SearchQuery {
     alias: String,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+// What the source of a channel charges for forwarding an amount through it
+#[derive(Debug, PartialEq, Serialize)]
+struct ChannelFee {
+    #[serde(rename = "shortChannelId")]
+    short_channel_id: String,
+    source: String,
+    #[serde(rename = "baseFeeSat")]
+    base_fee_sat: u32,
+    #[serde(rename = "feeMsat")]
+    fee_msat: u64,
+}
+
+impl ChannelFee {
+    fn from_channels(channels: Vec<Channel>, amount_msat: u64) -> Vec<Self> {
+        channels
+            .into_iter()
+            .map(|channel| Self {
+                base_fee_sat: channel.info.base_fee_sat(),
+                fee_msat: channel.info.total_fee_msat(amount_msat),
+                short_channel_id: channel.short_channel_id,
+                source: channel.source.id,
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+struct LoadedGossip {
+    symbol: String,
+    #[serde(rename = "nodeCount", skip_serializing_if = "Option::is_none")]
+    node_count: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct GossipReadiness {
+    fresh: bool,
+    #[serde(rename = "ageSecs", skip_serializing_if = "Option::is_none")]
+    age_secs: Option<u64>,
 }
 
 pub async fn node_info<S, M>(
@@ -42,11 +127,42 @@ where
         Err(response) => return Ok(*response),
     };
 
+    let lightning_info = &state.service.lightning_info;
+    let res = match lightning_info.get_node_info(&currency, &node).await {
+        Ok(res) => res,
+        Err(err) => return Ok(handle_info_fetch_error(err)),
+    };
+
+    // Clients can reuse the info until it expires in the cache
+    Ok(match lightning_info.node_ttl(&currency, &node).await {
+        Ok(Some(ttl)) => (
+            StatusCode::OK,
+            [(CACHE_CONTROL, format!("max-age={}", ttl.as_secs()))],
+            Json(res),
+        )
+            .into_response(),
+        _ => (StatusCode::OK, Json(res)).into_response(),
+    })
+}
+
+pub async fn node_capacity<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Path(LightningInfoParams { node, currency }): Path<LightningInfoParams>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    let node = match decode_node(&node) {
+        Ok(node) => node,
+        Err(response) => return Ok(*response),
+    };
+
     Ok(
         match state
             .service
             .lightning_info
-            .get_node_info(&currency, &node)
+            .get_node_capacity(&currency, &node)
             .await
         {
             Ok(res) => (StatusCode::OK, Json(res)).into_response(),
@@ -55,9 +171,99 @@ where
     )
 }
 
+pub async fn node_policies<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Path(LightningInfoParams { node, currency }): Path<LightningInfoParams>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    let node = match decode_node(&node) {
+        Ok(node) => node,
+        Err(response) => return Ok(*response),
+    };
+
+    Ok(
+        match state
+            .service
+            .lightning_info
+            .get_node_policy_summary(&currency, &node)
+            .await
+        {
+            Ok(Some(res)) => (StatusCode::OK, Json(res)).into_response(),
+            Ok(None) => handle_info_fetch_error(anyhow::anyhow!("no channels for node")),
+            Err(err) => handle_info_fetch_error(err),
+        },
+    )
+}
+
 pub async fn channels<S, M>(
     Extension(state): Extension<Arc<ServerState<S, M>>>,
     Path(LightningInfoParams { node, currency }): Path<LightningInfoParams>,
+    Query(query): Query<ChannelQuery>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    let node = match decode_node(&node) {
+        Ok(node) => node,
+        Err(response) => return Ok(*response),
+    };
+
+    let lightning_info = &state.service.lightning_info;
+    let res = if query.active.unwrap_or_default() {
+        lightning_info.get_active_channels(&currency, &node).await
+    } else {
+        lightning_info.get_channels(&currency, &node).await
+    };
+
+    Ok(match res {
+        Ok(res) => (
+            StatusCode::OK,
+            Json(without_stale(res, query.max_age_secs, SystemTime::now())),
+        )
+            .into_response(),
+        Err(err) => handle_info_fetch_error(err),
+    })
+}
+
+// Only active channels can forward
+pub async fn channel_fees<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Path(LightningInfoParams { node, currency }): Path<LightningInfoParams>,
+    Query(query): Query<FeeQuery>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    let node = match decode_node(&node) {
+        Ok(node) => node,
+        Err(response) => return Ok(*response),
+    };
+
+    Ok(
+        match state
+            .service
+            .lightning_info
+            .get_active_channels(&currency, &node)
+            .await
+        {
+            Ok(res) => (
+                StatusCode::OK,
+                Json(ChannelFee::from_channels(res, query.amount_msat)),
+            )
+                .into_response(),
+            Err(err) => handle_info_fetch_error(err),
+        },
+    )
+}
+
+pub async fn channels_from<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Path(LightningInfoParams { node, currency }): Path<LightningInfoParams>,
 ) -> Result<impl IntoResponse, AxumError>
 where
     S: SwapInfos + Send + Sync + Clone + 'static,
@@ -72,7 +278,7 @@ where
         match state
             .service
             .lightning_info
-            .get_channels(&currency, &node)
+            .get_channels_from(&currency, &node)
             .await
         {
             Ok(res) => (StatusCode::OK, Json(res)).into_response(),
@@ -84,6 +290,30 @@ where
 pub async fn channel<S, M>(
     Extension(state): Extension<Arc<ServerState<S, M>>>,
     Path(ChannelInfoParams { currency, id }): Path<ChannelInfoParams>,
+    Query(query): Query<ChannelQuery>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    let lightning_info = &state.service.lightning_info;
+
+    Ok(if query.active.unwrap_or_default() {
+        match lightning_info.get_channel_by_scid(&currency, id).await {
+            Ok(res) => (StatusCode::OK, Json(res)).into_response(),
+            Err(err) => handle_info_fetch_error(err),
+        }
+    } else {
+        match lightning_info.get_channel(&currency, id).await {
+            Ok(res) => (StatusCode::OK, Json(res)).into_response(),
+            Err(err) => handle_info_fetch_error(err),
+        }
+    })
+}
+
+pub async fn channel_pair<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Path(ChannelInfoParams { currency, id }): Path<ChannelInfoParams>,
 ) -> Result<impl IntoResponse, AxumError>
 where
     S: SwapInfos + Send + Sync + Clone + 'static,
@@ -93,7 +323,7 @@ where
         match state
             .service
             .lightning_info
-            .get_channel(&currency, id)
+            .get_channel_pair(&currency, id)
             .await
         {
             Ok(res) => (StatusCode::OK, Json(res)).into_response(),
@@ -107,6 +337,39 @@ pub async fn search<S, M>(
     Path(currency): Path<String>,
     Query(query): Query<SearchQuery>,
 ) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    let lightning_info = &state.service.lightning_info;
+    let res = match query.limit {
+        Some(limit) => {
+            lightning_info
+                .find_node_by_alias_paged(
+                    &currency,
+                    &query.alias,
+                    limit,
+                    query.offset.unwrap_or_default(),
+                )
+                .await
+        }
+        None => {
+            lightning_info
+                .find_node_by_alias(&currency, &query.alias)
+                .await
+        }
+    };
+
+    Ok(match res {
+        Ok(res) => (StatusCode::OK, Json(res)).into_response(),
+        Err(err) => handle_info_fetch_error(err),
+    })
+}
+
+pub async fn search_id<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Path(SearchIdParams { currency, prefix }): Path<SearchIdParams>,
+) -> Result<impl IntoResponse, AxumError>
 where
     S: SwapInfos + Send + Sync + Clone + 'static,
     M: SwapManager + Send + Sync + 'static,
@@ -115,7 +378,7 @@ where
         match state
             .service
             .lightning_info
-            .find_node_by_alias(&currency, &query.alias)
+            .find_node_by_id_prefix(&currency, &prefix)
             .await
         {
             Ok(res) => (StatusCode::OK, Json(res)).into_response(),
@@ -124,6 +387,192 @@ where
     )
 }
 
+pub async fn search_all<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Query(query): Query<SearchQuery>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    Ok(
+        match state
+            .service
+            .lightning_info
+            .find_node_by_alias_all(&query.alias)
+            .await
+        {
+            Ok(res) => {
+                let res = res
+                    .into_iter()
+                    .skip(query.offset.unwrap_or_default())
+                    .take(query.limit.unwrap_or(usize::MAX))
+                    .collect::<Vec<_>>();
+                (StatusCode::OK, Json(res)).into_response()
+            }
+            Err(err) => handle_info_fetch_error(err),
+        },
+    )
+}
+
+pub async fn gossip_symbols<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    let lightning_info = &state.service.lightning_info;
+
+    let mut res = Vec::new();
+    for symbol in lightning_info.loaded_symbols().await {
+        res.push(LoadedGossip {
+            node_count: lightning_info.node_count(&symbol).await,
+            symbol,
+        });
+    }
+
+    Ok((StatusCode::OK, Json(res)))
+}
+
+pub async fn gossip<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Path(currency): Path<String>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    Ok(
+        match state.service.lightning_info.gossip_stats(&currency).await {
+            Some(res) => (StatusCode::OK, Json(res)).into_response(),
+            None => handle_info_fetch_error(anyhow::anyhow!("no gossip for {}", currency)),
+        },
+    )
+}
+
+pub async fn gossip_diagnostics<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Path(currency): Path<String>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    Ok(
+        match state
+            .service
+            .lightning_info
+            .gossip_diagnostics(&currency)
+            .await
+        {
+            Some(res) => (StatusCode::OK, Json(res)).into_response(),
+            None => handle_info_fetch_error(anyhow::anyhow!("no gossip for {}", currency)),
+        },
+    )
+}
+
+pub async fn gossip_ready<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Path(currency): Path<String>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    let lightning_info = &state.service.lightning_info;
+
+    let readiness = GossipReadiness {
+        fresh: lightning_info.is_gossip_fresh(&currency).await,
+        age_secs: lightning_info
+            .gossip_age(&currency)
+            .await
+            .map(|age| age.as_secs()),
+    };
+
+    Ok((
+        if readiness.fresh {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        },
+        Json(readiness),
+    ))
+}
+
+pub async fn top_nodes<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Path(currency): Path<String>,
+    Query(query): Query<TopNodesQuery>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    let limit = query
+        .limit
+        .unwrap_or(TOP_NODES_DEFAULT_LIMIT)
+        .min(TOP_NODES_MAX_LIMIT);
+
+    Ok(
+        match state
+            .service
+            .lightning_info
+            .top_nodes_by_capacity(&currency, limit)
+            .await
+        {
+            Ok(res) => (StatusCode::OK, Json(res)).into_response(),
+            Err(err) => handle_info_fetch_error(err),
+        },
+    )
+}
+
+// The graph of mainnet is too big for a single response, so it is served in pages
+pub async fn graph<S, M>(
+    Extension(state): Extension<Arc<ServerState<S, M>>>,
+    Path(currency): Path<String>,
+    Query(query): Query<GraphQuery>,
+) -> Result<impl IntoResponse, AxumError>
+where
+    S: SwapInfos + Send + Sync + Clone + 'static,
+    M: SwapManager + Send + Sync + 'static,
+{
+    let limit = query
+        .limit
+        .unwrap_or(GRAPH_DEFAULT_LIMIT)
+        .clamp(1, GRAPH_MAX_LIMIT);
+    let cursor = GraphCursor {
+        after_node: query.after_node,
+        after_channel: query.after_channel,
+    };
+
+    Ok(
+        match state
+            .service
+            .lightning_info
+            .export_graph(&currency, &cursor, limit)
+            .await
+        {
+            Ok(res) => (StatusCode::OK, Json(res)).into_response(),
+            Err(err) => handle_info_fetch_error(err),
+        },
+    )
+}
+
+fn without_stale(
+    channels: Vec<Channel>,
+    max_age_secs: Option<u64>,
+    now: SystemTime,
+) -> Vec<Channel> {
+    match max_age_secs {
+        Some(max_age_secs) => channels
+            .into_iter()
+            .filter(|channel| !channel.is_stale(Duration::from_secs(max_age_secs), now))
+            .collect(),
+        None => channels,
+    }
+}
+
 fn decode_node(node: &str) -> Result<Vec<u8>, Box<axum::http::Response<axum::body::Body>>> {
     fn invalid_node_response<E: std::fmt::Display>(
         err: E,
@@ -151,7 +600,12 @@ fn decode_node(node: &str) -> Result<Vec<u8>, Box<axum::http::Response<axum::bod
 
 fn handle_info_fetch_error(err: anyhow::Error) -> axum::http::Response<axum::body::Body> {
     (
-        StatusCode::NOT_FOUND,
+        match err.downcast_ref::<LookupError>() {
+            Some(LookupError::UnknownSymbol(_)) => StatusCode::BAD_REQUEST,
+            Some(LookupError::GossipNotReady(_)) => StatusCode::SERVICE_UNAVAILABLE,
+            Some(LookupError::RefreshInProgress(_)) => StatusCode::CONFLICT,
+            None => StatusCode::NOT_FOUND,
+        },
         Json(ApiError {
             error: err.to_string(),
         }),
@@ -164,6 +618,59 @@ mod test {
     use super::*;
     use http_body_util::BodyExt;
     use rstest::*;
+    use std::time::UNIX_EPOCH;
+
+    fn channel(short_channel_id: &str, last_update: Option<u64>) -> Channel {
+        serde_json::from_value(serde_json::json!({
+            "source": { "id": "02" },
+            "shortChannelId": short_channel_id,
+            "active": true,
+            "info": {
+                "active": true,
+                "baseFeeMillisatoshi": 1_500,
+                "feePpm": 100,
+                "delay": 80,
+                "lastUpdate": last_update,
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_channel_fee_from_channels() {
+        let channel = channel("1x2x3", None);
+
+        assert_eq!(
+            ChannelFee::from_channels(vec![channel], 1_234_567),
+            vec![ChannelFee {
+                short_channel_id: "1x2x3".to_string(),
+                source: "02".to_string(),
+                base_fee_sat: 1,
+                fee_msat: 1_623,
+            }]
+        );
+    }
+
+    #[rstest]
+    #[case(None, vec!["1x1x1", "2x1x1", "3x1x1"])]
+    #[case(Some(600), vec!["1x1x1"])]
+    #[case(Some(0), vec![])]
+    fn test_without_stale(#[case] max_age_secs: Option<u64>, #[case] expected: Vec<&str>) {
+        let channels = vec![
+            channel("1x1x1", Some(1_000)),
+            channel("2x1x1", Some(100)),
+            channel("3x1x1", None),
+        ];
+        let now = UNIX_EPOCH + Duration::from_secs(1_200);
+
+        assert_eq!(
+            without_stale(channels, max_age_secs, now)
+                .into_iter()
+                .map(|channel| channel.short_channel_id)
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
 
     #[rstest]
     #[case("03a7ee82c3c7fc4c796d26e513676d445d49b9c62004a47f2e813695a439a8fd01")]
@@ -172,6 +679,37 @@ mod test {
         assert_eq!(decode_node(node).unwrap(), hex::decode(node).unwrap());
     }
 
+    #[rstest]
+    #[case(
+        LookupError::UnknownSymbol("BTC".into()).into(),
+        StatusCode::BAD_REQUEST,
+        "no gossip source for BTC"
+    )]
+    #[case(
+        LookupError::GossipNotReady("BTC".into()).into(),
+        StatusCode::SERVICE_UNAVAILABLE,
+        "gossip of BTC is not ready"
+    )]
+    #[case(
+        LookupError::RefreshInProgress("BTC".into()).into(),
+        StatusCode::CONFLICT,
+        "refresh of BTC lightning gossip is already in progress"
+    )]
+    #[case(anyhow::anyhow!("node not found"), StatusCode::NOT_FOUND, "node not found")]
+    #[tokio::test]
+    async fn test_handle_info_fetch_error(
+        #[case] err: anyhow::Error,
+        #[case] status: StatusCode,
+        #[case] expected: &str,
+    ) {
+        let res = handle_info_fetch_error(err);
+        assert_eq!(res.status(), status);
+
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let error: ApiError = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.error, expected);
+    }
+
     #[rstest]
     #[case(
         "03a7ee82c3c7fc4c796d26e513676d445d49b9c62004a47f2e813695a439a8fd",
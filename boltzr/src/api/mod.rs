@@ -133,18 +133,67 @@ where
                 "/v2/lightning/{currency}/node/{node}",
                 get(lightning::node_info::<S, M>),
             )
+            .route(
+                "/v2/lightning/{currency}/node/{node}/capacity",
+                get(lightning::node_capacity::<S, M>),
+            )
+            .route(
+                "/v2/lightning/{currency}/node/{node}/policies",
+                get(lightning::node_policies::<S, M>),
+            )
             .route(
                 "/v2/lightning/{currency}/channel/{id}",
                 get(lightning::channel::<S, M>),
             )
+            .route(
+                "/v2/lightning/{currency}/channel/{id}/pair",
+                get(lightning::channel_pair::<S, M>),
+            )
             .route(
                 "/v2/lightning/{currency}/channels/{node}",
                 get(lightning::channels::<S, M>),
             )
+            .route(
+                "/v2/lightning/{currency}/channels/{node}/fees",
+                get(lightning::channel_fees::<S, M>),
+            )
+            .route(
+                "/v2/lightning/{currency}/channels/{node}/outbound",
+                get(lightning::channels_from::<S, M>),
+            )
+            .route("/v2/lightning/search", get(lightning::search_all::<S, M>))
+            .route(
+                "/v2/lightning/gossip",
+                get(lightning::gossip_symbols::<S, M>),
+            )
             .route(
                 "/v2/lightning/{currency}/search",
                 get(lightning::search::<S, M>),
             )
+            .route(
+                "/v2/lightning/{currency}/search/id/{prefix}",
+                get(lightning::search_id::<S, M>),
+            )
+            .route(
+                "/v2/lightning/{currency}/gossip",
+                get(lightning::gossip::<S, M>),
+            )
+            .route(
+                "/v2/lightning/{currency}/gossip/diagnostics",
+                get(lightning::gossip_diagnostics::<S, M>),
+            )
+            .route(
+                "/v2/lightning/{currency}/gossip/ready",
+                get(lightning::gossip_ready::<S, M>),
+            )
+            .route(
+                "/v2/lightning/{currency}/graph",
+                get(lightning::graph::<S, M>),
+            )
+            .route(
+                "/v2/lightning/{currency}/nodes/top",
+                get(lightning::top_nodes::<S, M>),
+            )
             .route(
                 "/v2/lightning/{currency}/bolt12",
                 post(bolt12::create::<S, M>),
@@ -1,12 +1,52 @@
+use crate::cache::CacheBackend;
+use async_trait::async_trait;
 use dashmap::DashMap;
-use serde::de::DeserializeOwned;
+use serde::de::{DeserializeOwned, IgnoredAny};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Clone, Debug)]
 pub struct MemCache {
     pub map: Arc<DashMap<String, String>>,
+    capacity: Option<usize>,
+    recency: Arc<Mutex<Recency>>,
+}
+
+// Tracks the order in which entries were last used for bounded caches
+#[derive(Debug, Default)]
+struct Recency {
+    tick: u64,
+    ticks: HashMap<String, u64>,
+    order: BTreeMap<u64, String>,
+}
+
+impl Recency {
+    fn insert(&mut self, key: &str) {
+        self.remove(key);
+        self.tick += 1;
+        self.ticks.insert(key.to_string(), self.tick);
+        self.order.insert(self.tick, key.to_string());
+    }
+
+    fn touch(&mut self, key: &str) {
+        if self.ticks.contains_key(key) {
+            self.insert(key);
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(tick) = self.ticks.remove(key) {
+            self.order.remove(&tick);
+        }
+    }
+
+    fn pop_oldest(&mut self) -> Option<String> {
+        let (_, key) = self.order.pop_first()?;
+        self.ticks.remove(&key);
+        Some(key)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -19,27 +59,66 @@ impl MemCache {
     pub fn new() -> Self {
         Self {
             map: Arc::new(DashMap::new()),
+            capacity: None,
+            recency: Arc::new(Mutex::new(Recency::default())),
+        }
+    }
+
+    // Evicts the least recently used entries once more than max_entries are stored
+    pub fn with_capacity(max_entries: usize) -> anyhow::Result<Self> {
+        if max_entries == 0 {
+            return Err(anyhow::anyhow!("cache capacity has to be at least 1"));
+        }
+
+        Ok(Self {
+            capacity: Some(max_entries),
+            ..Self::new()
+        })
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.capacity.is_some()
+    }
+
+    fn recency(&self) -> MutexGuard<'_, Recency> {
+        match self.recency.lock() {
+            Ok(recency) => recency,
+            Err(poisoned) => poisoned.into_inner(),
         }
     }
 }
 
 impl MemCache {
     pub fn get<V: DeserializeOwned>(&self, key: &str, field: &str) -> anyhow::Result<Option<V>> {
+        Ok(self.get_with_ttl(key, field)?.map(|(value, _)| value))
+    }
+
+    // Expiry is stored with a precision of seconds
+    pub fn get_with_ttl<V: DeserializeOwned>(
+        &self,
+        key: &str,
+        field: &str,
+    ) -> anyhow::Result<Option<(V, Option<Duration>)>> {
         let key = Self::get_key(key, field);
 
-        let entry = self.map.get(&key);
+        // The guard of the map has to be released before the recency is locked
+        let entry = self.map.get(&key).map(|res| res.value().clone());
         match entry {
             Some(res) => {
-                let cache_value: CacheValue<V> = serde_json::from_str(res.value())?;
+                let cache_value: CacheValue<V> = serde_json::from_str(&res)?;
                 match cache_value.expires_at {
-                    None => Ok(Some(cache_value.value)),
+                    None => {
+                        self.touch(&key);
+                        Ok(Some((cache_value.value, None)))
+                    }
                     Some(expires_at) => {
-                        let current_time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-                        if current_time < expires_at {
-                            Ok(Some(cache_value.value))
+                        let current_time = SystemTime::now().duration_since(UNIX_EPOCH)?;
+                        if current_time.as_secs() < expires_at {
+                            self.touch(&key);
+                            let ttl = Duration::from_secs(expires_at).saturating_sub(current_time);
+                            Ok(Some((cache_value.value, Some(ttl))))
                         } else {
-                            drop(res);
-                            self.map.remove(&key);
+                            self.remove(&key);
                             Ok(None)
                         }
                     }
@@ -49,6 +128,30 @@ impl MemCache {
         }
     }
 
+    // Only the expiry is parsed; checking for an entry does not count as using it
+    pub fn exists(&self, key: &str, field: &str) -> anyhow::Result<bool> {
+        let key = Self::get_key(key, field);
+
+        let entry = self.map.get(&key).map(|res| res.value().clone());
+        let expires_at = match entry {
+            Some(res) => serde_json::from_str::<CacheValue<IgnoredAny>>(&res)?.expires_at,
+            None => return Ok(false),
+        };
+
+        match expires_at {
+            Some(expires_at) => {
+                let current_time = SystemTime::now().duration_since(UNIX_EPOCH)?;
+                if current_time.as_secs() < expires_at {
+                    Ok(true)
+                } else {
+                    self.remove(&key);
+                    Ok(false)
+                }
+            }
+            None => Ok(true),
+        }
+    }
+
     pub fn set<V: Serialize + Sync>(
         &self,
         key: &str,
@@ -70,15 +173,91 @@ impl MemCache {
         };
 
         let cache_value = CacheValue { value, expires_at };
-        self.map.insert(key, serde_json::to_string(&cache_value)?);
+        let value = serde_json::to_string(&cache_value)?;
+
+        let capacity = match self.capacity {
+            Some(capacity) => capacity,
+            None => {
+                self.map.insert(key, value);
+                return Ok(());
+            }
+        };
+
+        let mut recency = self.recency();
+        recency.insert(&key);
+        self.map.insert(key, value);
+
+        while self.map.len() > capacity {
+            match recency.pop_oldest() {
+                Some(oldest) => {
+                    self.map.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+
         Ok(())
     }
 
+    pub fn del(&self, key: &str, field: &str) {
+        self.remove(&Self::get_key(key, field));
+    }
+
+    fn touch(&self, key: &str) {
+        if self.capacity.is_some() {
+            self.recency().touch(key);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        if self.capacity.is_some() {
+            let mut recency = self.recency();
+            recency.remove(key);
+            self.map.remove(key);
+        } else {
+            self.map.remove(key);
+        }
+    }
+
     fn get_key(key: &str, field: &str) -> String {
         format!("{key}:{field}")
     }
 }
 
+#[async_trait]
+impl CacheBackend for MemCache {
+    async fn get(&self, key: &str, field: &str) -> anyhow::Result<Option<serde_json::Value>> {
+        MemCache::get(self, key, field)
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        field: &str,
+        value: serde_json::Value,
+        ttl: Option<u64>,
+    ) -> anyhow::Result<()> {
+        MemCache::set(self, key, field, &value, ttl)
+    }
+
+    async fn del(&self, key: &str, field: &str) -> anyhow::Result<()> {
+        MemCache::del(self, key, field);
+        Ok(())
+    }
+
+    async fn get_with_ttl(
+        &self,
+        key: &str,
+        field: &str,
+    ) -> anyhow::Result<Option<(serde_json::Value, Option<Duration>)>> {
+        MemCache::get_with_ttl(self, key, field)
+    }
+
+    async fn exists(&self, key: &str, field: &str) -> anyhow::Result<bool> {
+        MemCache::exists(self, key, field)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,4 +407,141 @@ mod tests {
         let retrieved: Option<String> = cache.get(key, field).unwrap();
         assert_eq!(retrieved, Some(value_set));
     }
+
+    #[test]
+    fn test_get_with_ttl() {
+        let cache = MemCache::new();
+        cache.set("ttl", "field", &"value", Some(10)).unwrap();
+
+        let (value, ttl) = cache
+            .get_with_ttl::<String>("ttl", "field")
+            .unwrap()
+            .unwrap();
+        assert_eq!(value, "value");
+        let ttl = ttl.unwrap();
+        assert!(ttl > Duration::from_secs(8) && ttl <= Duration::from_secs(10));
+
+        cache.set("ttl", "no_ttl", &"value", None).unwrap();
+        assert_eq!(
+            cache.get_with_ttl::<String>("ttl", "no_ttl").unwrap(),
+            Some(("value".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn test_get_with_ttl_after_expiry() {
+        let cache = MemCache::new();
+        cache.set("ttl", "field", &"value", Some(1)).unwrap();
+
+        sleep(Duration::from_millis(1100));
+        assert_eq!(cache.get_with_ttl::<String>("ttl", "field").unwrap(), None);
+        assert!(cache.map.is_empty());
+    }
+
+    #[test]
+    fn test_with_capacity_invalid() {
+        assert_eq!(
+            MemCache::with_capacity(0).err().unwrap().to_string(),
+            "cache capacity has to be at least 1"
+        );
+    }
+
+    #[test]
+    fn test_with_capacity_evicts_least_recently_used() {
+        let cache = MemCache::with_capacity(3).unwrap();
+        for field in ["a", "b", "c"] {
+            cache.set("lru", field, &field.to_string(), None).unwrap();
+        }
+
+        // Reading "a" makes "b" the least recently used entry
+        let retrieved: Option<String> = cache.get("lru", "a").unwrap();
+        assert_eq!(retrieved, Some("a".to_string()));
+
+        cache.set("lru", "d", &"d".to_string(), None).unwrap();
+        assert_eq!(cache.map.len(), 3);
+        assert_eq!(cache.get::<String>("lru", "b").unwrap(), None);
+
+        cache.set("lru", "e", &"e".to_string(), None).unwrap();
+        assert_eq!(cache.get::<String>("lru", "c").unwrap(), None);
+
+        for field in ["a", "d", "e"] {
+            assert_eq!(
+                cache.get::<String>("lru", field).unwrap(),
+                Some(field.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_with_capacity_overwrite_refreshes_entry() {
+        let cache = MemCache::with_capacity(2).unwrap();
+        cache.set("lru", "a", &1, None).unwrap();
+        cache.set("lru", "b", &2, None).unwrap();
+        cache.set("lru", "a", &3, None).unwrap();
+
+        cache.set("lru", "c", &4, None).unwrap();
+        assert_eq!(cache.map.len(), 2);
+        assert_eq!(cache.get::<u32>("lru", "a").unwrap(), Some(3));
+        assert_eq!(cache.get::<u32>("lru", "b").unwrap(), None);
+    }
+
+    #[test]
+    fn test_with_capacity_del() {
+        let cache = MemCache::with_capacity(2).unwrap();
+        cache.set("lru", "a", &1, None).unwrap();
+        cache.set("lru", "b", &2, None).unwrap();
+        cache.del("lru", "a");
+
+        cache.set("lru", "c", &3, None).unwrap();
+        assert_eq!(cache.get::<u32>("lru", "b").unwrap(), Some(2));
+        assert_eq!(cache.get::<u32>("lru", "c").unwrap(), Some(3));
+        assert_eq!(cache.recency().ticks.len(), 2);
+    }
+
+    #[test]
+    fn test_with_capacity_expired_removed_on_access() {
+        let cache = MemCache::with_capacity(2).unwrap();
+        cache.set("lru", "a", &1, Some(1)).unwrap();
+        cache.set("lru", "b", &2, None).unwrap();
+
+        sleep(Duration::from_millis(1100));
+        assert_eq!(cache.get::<u32>("lru", "a").unwrap(), None);
+        assert_eq!(cache.map.len(), 1);
+        assert!(!cache.recency().ticks.contains_key("lru:a"));
+
+        // The slot of the expired entry is free again, so nothing gets evicted
+        cache.set("lru", "c", &3, None).unwrap();
+        assert_eq!(cache.get::<u32>("lru", "b").unwrap(), Some(2));
+        assert_eq!(cache.get::<u32>("lru", "c").unwrap(), Some(3));
+    }
+
+    #[test]
+    fn test_exists() {
+        let cache = MemCache::new();
+        cache.set("key", "present", &"value", None).unwrap();
+        cache.set("key", "expiring", &"value", Some(1)).unwrap();
+
+        assert!(cache.exists("key", "present").unwrap());
+        assert!(cache.exists("key", "expiring").unwrap());
+        assert!(!cache.exists("key", "absent").unwrap());
+
+        sleep(Duration::from_millis(1100));
+        assert!(!cache.exists("key", "expiring").unwrap());
+        assert!(!cache.map.contains_key("key:expiring"));
+        assert!(cache.exists("key", "present").unwrap());
+    }
+
+    #[test]
+    fn test_exists_does_not_refresh_entry() {
+        let cache = MemCache::with_capacity(2).unwrap();
+        cache.set("lru", "a", &1, None).unwrap();
+        cache.set("lru", "b", &2, None).unwrap();
+
+        assert!(cache.exists("lru", "a").unwrap());
+        cache.set("lru", "c", &3, None).unwrap();
+
+        assert!(!cache.exists("lru", "a").unwrap());
+        assert!(cache.exists("lru", "b").unwrap());
+        assert!(cache.exists("lru", "c").unwrap());
+    }
 }
@@ -1,6 +1,9 @@
 use anyhow::Result;
+use async_trait::async_trait;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::time::Duration;
 
 mod memcache;
 mod redis;
@@ -14,18 +17,94 @@ pub struct CacheConfig {
     pub redis_endpoint: String,
 }
 
+// Key, field, value and TTL in seconds
+pub type CacheEntry = (String, String, serde_json::Value, Option<u64>);
+
+// Values are passed as JSON to keep the trait object safe
+#[async_trait]
+pub trait CacheBackend: Debug + Send + Sync {
+    async fn get(&self, key: &str, field: &str) -> Result<Option<serde_json::Value>>;
+    async fn set(
+        &self,
+        key: &str,
+        field: &str,
+        value: serde_json::Value,
+        ttl: Option<u64>,
+    ) -> Result<()>;
+    async fn del(&self, key: &str, field: &str) -> Result<()>;
+
+    // Backends that do not track expiry report no TTL
+    async fn get_with_ttl(
+        &self,
+        key: &str,
+        field: &str,
+    ) -> Result<Option<(serde_json::Value, Option<Duration>)>> {
+        Ok(self.get(key, field).await?.map(|value| (value, None)))
+    }
+
+    // Backends that can check for a field without fetching its value should override this
+    async fn exists(&self, key: &str, field: &str) -> Result<bool> {
+        Ok(self.get(key, field).await?.is_some())
+    }
+
+    // Backends that cannot list the fields of a key return none
+    async fn get_all(&self, _key: &str) -> Result<Vec<(String, serde_json::Value)>> {
+        Ok(Vec::new())
+    }
+
+    // Backends that can batch requests should override these
+    async fn get_many(&self, keys: &[(String, String)]) -> Result<Vec<Option<serde_json::Value>>> {
+        let mut values = Vec::with_capacity(keys.len());
+        for (key, field) in keys {
+            values.push(self.get(key, field).await?);
+        }
+
+        Ok(values)
+    }
+
+    async fn set_many(&self, entries: &[CacheEntry]) -> Result<()> {
+        for (key, field, value, ttl) in entries {
+            self.set(key, field, value.clone(), *ttl).await?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Cache {
     Redis(Redis),
     Memory(MemCache),
+    // For stores that are not shipped with the binary; none of the
+    // built-in configs construct it, so it is only used by forks and tests
+    #[allow(dead_code)]
+    Custom(std::sync::Arc<dyn CacheBackend>),
 }
 
 impl Cache {
     pub async fn get<V: DeserializeOwned>(&self, key: &str, field: &str) -> Result<Option<V>> {
-        match self {
-            Cache::Redis(redis) => redis.get(key, field).await,
-            Cache::Memory(memory) => memory.get(key, field),
-        }
+        let value = self.backend().get(key, field).await?;
+        Ok(match value {
+            Some(value) => Some(serde_json::from_value(value)?),
+            None => None,
+        })
+    }
+
+    // Also returns how long the value has left before it expires
+    pub async fn get_with_ttl<V: DeserializeOwned>(
+        &self,
+        key: &str,
+        field: &str,
+    ) -> Result<Option<(V, Option<Duration>)>> {
+        Ok(match self.backend().get_with_ttl(key, field).await? {
+            Some((value, ttl)) => Some((serde_json::from_value(value)?, ttl)),
+            None => None,
+        })
+    }
+
+    // Avoids deserializing the value when only its presence matters
+    pub async fn exists(&self, key: &str, field: &str) -> Result<bool> {
+        self.backend().exists(key, field).await
     }
 
     pub async fn set<V: Serialize + Sync>(
@@ -35,9 +114,271 @@ impl Cache {
         value: &V,
         ttl: Option<u64>,
     ) -> Result<()> {
+        self.backend()
+            .set(key, field, serde_json::to_value(value)?, ttl)
+            .await
+    }
+
+    pub async fn get_many<V: DeserializeOwned>(
+        &self,
+        keys: &[(String, String)],
+    ) -> Result<Vec<Option<V>>> {
+        let mut values = Vec::with_capacity(keys.len());
+        for value in self.backend().get_many(keys).await? {
+            values.push(match value {
+                Some(value) => Some(serde_json::from_value(value)?),
+                None => None,
+            });
+        }
+
+        Ok(values)
+    }
+
+    pub async fn set_many(&self, entries: &[CacheEntry]) -> Result<()> {
+        self.backend().set_many(entries).await
+    }
+
+    pub async fn get_all<V: DeserializeOwned>(&self, key: &str) -> Result<Vec<(String, V)>> {
+        let mut values = Vec::new();
+        for (field, value) in self.backend().get_all(key).await? {
+            values.push((field, serde_json::from_value(value)?));
+        }
+
+        Ok(values)
+    }
+
+    pub async fn del(&self, key: &str, field: &str) -> Result<()> {
+        self.backend().del(key, field).await
+    }
+
+    // Entries of bounded caches can be evicted before they expire
+    pub fn is_bounded(&self) -> bool {
         match self {
-            Cache::Redis(redis) => redis.set(key, field, value, ttl).await,
-            Cache::Memory(memory) => memory.set(key, field, value, ttl),
+            Cache::Memory(memory) => memory.is_bounded(),
+            _ => false,
         }
     }
+
+    fn backend(&self) -> &dyn CacheBackend {
+        match self {
+            Cache::Redis(redis) => redis,
+            Cache::Memory(memory) => memory,
+            Cache::Custom(custom) => custom.as_ref(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Default)]
+    pub struct CountingBackend {
+        values: Mutex<HashMap<String, serde_json::Value>>,
+        pub gets: Arc<AtomicUsize>,
+        pub sets: Arc<AtomicUsize>,
+        pub dels: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl CacheBackend for CountingBackend {
+        async fn get(&self, key: &str, field: &str) -> Result<Option<serde_json::Value>> {
+            self.gets.fetch_add(1, Ordering::SeqCst);
+            Ok(self
+                .values
+                .lock()
+                .unwrap()
+                .get(&format!("{key}:{field}"))
+                .cloned())
+        }
+
+        async fn set(
+            &self,
+            key: &str,
+            field: &str,
+            value: serde_json::Value,
+            _ttl: Option<u64>,
+        ) -> Result<()> {
+            self.sets.fetch_add(1, Ordering::SeqCst);
+            self.values
+                .lock()
+                .unwrap()
+                .insert(format!("{key}:{field}"), value);
+            Ok(())
+        }
+
+        async fn del(&self, key: &str, field: &str) -> Result<()> {
+            self.dels.fetch_add(1, Ordering::SeqCst);
+            self.values
+                .lock()
+                .unwrap()
+                .remove(&format!("{key}:{field}"));
+            Ok(())
+        }
+
+        async fn get_all(&self, key: &str) -> Result<Vec<(String, serde_json::Value)>> {
+            let prefix = format!("{key}:");
+            let mut values = self
+                .values
+                .lock()
+                .unwrap()
+                .iter()
+                .filter_map(|(entry, value)| {
+                    entry
+                        .strip_prefix(&prefix)
+                        .map(|field| (field.to_string(), value.clone()))
+                })
+                .collect::<Vec<_>>();
+            values.sort_by(|a, b| a.0.cmp(&b.0));
+
+            Ok(values)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_backend() {
+        let backend = CountingBackend::default();
+        let (gets, sets, dels) = (
+            backend.gets.clone(),
+            backend.sets.clone(),
+            backend.dels.clone(),
+        );
+        let cache = Cache::Custom(Arc::new(backend));
+
+        cache
+            .set("key", "field", &vec![1, 2, 3], None)
+            .await
+            .unwrap();
+        assert_eq!(
+            cache.get::<Vec<u64>>("key", "field").await.unwrap(),
+            Some(vec![1, 2, 3])
+        );
+
+        cache.del("key", "field").await.unwrap();
+        assert_eq!(cache.get::<Vec<u64>>("key", "field").await.unwrap(), None);
+
+        assert_eq!(gets.load(Ordering::SeqCst), 2);
+        assert_eq!(sets.load(Ordering::SeqCst), 1);
+        assert_eq!(dels.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_all() {
+        let backend = Cache::Custom(Arc::new(CountingBackend::default()));
+        let memory = Cache::Memory(MemCache::new());
+
+        for cache in [&backend, &memory] {
+            cache.set("key", "a", &1, None).await.unwrap();
+            cache.set("key", "b", &2, None).await.unwrap();
+            cache.set("other", "c", &3, None).await.unwrap();
+        }
+
+        assert_eq!(
+            backend.get_all::<u64>("key").await.unwrap(),
+            vec![("a".to_string(), 1), ("b".to_string(), 2)]
+        );
+        assert!(memory.get_all::<u64>("key").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_many() {
+        let cache = Cache::Memory(MemCache::new());
+
+        cache
+            .set_many(&[
+                (
+                    "key".to_string(),
+                    "a".to_string(),
+                    serde_json::json!(1),
+                    None,
+                ),
+                (
+                    "key".to_string(),
+                    "c".to_string(),
+                    serde_json::json!(3),
+                    Some(60),
+                ),
+            ])
+            .await
+            .unwrap();
+
+        let values = cache
+            .get_many::<u64>(&[
+                ("key".to_string(), "c".to_string()),
+                ("key".to_string(), "b".to_string()),
+                ("key".to_string(), "a".to_string()),
+                ("other".to_string(), "a".to_string()),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(values, vec![Some(3), None, Some(1), None]);
+    }
+
+    #[tokio::test]
+    async fn test_get_many_empty() {
+        let cache = Cache::Memory(MemCache::new());
+        assert!(cache.get_many::<u64>(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_with_ttl() {
+        let memory = Cache::Memory(MemCache::new());
+        memory.set("key", "ttl", &1, Some(60)).await.unwrap();
+        memory.set("key", "no_ttl", &2, None).await.unwrap();
+
+        let (value, ttl) = memory
+            .get_with_ttl::<u64>("key", "ttl")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(value, 1);
+        let ttl = ttl.unwrap();
+        assert!(ttl > Duration::from_secs(58) && ttl <= Duration::from_secs(60));
+
+        assert_eq!(
+            memory.get_with_ttl::<u64>("key", "no_ttl").await.unwrap(),
+            Some((2, None))
+        );
+        assert_eq!(
+            memory.get_with_ttl::<u64>("key", "missing").await.unwrap(),
+            None
+        );
+
+        // Backends without expiry tracking fall back to a plain get
+        let custom = Cache::Custom(Arc::new(CountingBackend::default()));
+        custom.set("key", "ttl", &1, Some(60)).await.unwrap();
+        assert_eq!(
+            custom.get_with_ttl::<u64>("key", "ttl").await.unwrap(),
+            Some((1, None))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exists() {
+        let backend = Cache::Custom(Arc::new(CountingBackend::default()));
+        let memory = Cache::Memory(MemCache::new());
+
+        for cache in [&backend, &memory] {
+            cache.set("key", "present", &"value", None).await.unwrap();
+
+            assert!(cache.exists("key", "present").await.unwrap());
+            assert!(!cache.exists("key", "absent").await.unwrap());
+            assert!(!cache.exists("other", "present").await.unwrap());
+
+            cache.del("key", "present").await.unwrap();
+            assert!(!cache.exists("key", "present").await.unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_del() {
+        let cache = Cache::Memory(MemCache::new());
+
+        cache.set("key", "field", &"value", None).await.unwrap();
+        cache.del("key", "field").await.unwrap();
+        assert_eq!(cache.get::<String>("key", "field").await.unwrap(), None);
+    }
 }
@@ -1,9 +1,11 @@
-use crate::cache::CacheConfig;
+use crate::cache::{CacheBackend, CacheConfig, CacheEntry};
 use anyhow::Result;
+use async_trait::async_trait;
 use redis::Client;
 use redis::aio::MultiplexedConnection;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
+use std::time::Duration;
 use tracing::info;
 
 #[derive(Debug, Clone)]
@@ -37,6 +39,55 @@ impl Redis {
         })
     }
 
+    pub async fn get_with_ttl<V: DeserializeOwned>(
+        &self,
+        key: &str,
+        field: &str,
+    ) -> Result<Option<(V, Option<Duration>)>> {
+        // Negative TTLs mean that the field has no expiry or does not exist
+        let (res, ttls): (Option<String>, Vec<i64>) = redis::pipe()
+            .atomic()
+            .cmd("HGET")
+            .arg(key)
+            .arg(field)
+            .cmd("HPTTL")
+            .arg(key)
+            .arg("FIELDS")
+            .arg(1)
+            .arg(field)
+            .query_async(&mut self.connection.clone())
+            .await?;
+
+        let value = match res {
+            Some(res) => serde_json::from_str(&res)?,
+            None => return Ok(None),
+        };
+        let ttl = ttls
+            .first()
+            .filter(|ttl| **ttl >= 0)
+            .map(|ttl| Duration::from_millis(*ttl as u64));
+
+        Ok(Some((value, ttl)))
+    }
+
+    // The fields of a hash have to be checked with HEXISTS rather than EXISTS
+    pub async fn exists(&self, key: &str, field: &str) -> Result<bool> {
+        Ok(redis::cmd("HEXISTS")
+            .arg(key)
+            .arg(field)
+            .query_async(&mut self.connection.clone())
+            .await?)
+    }
+
+    pub async fn del(&self, key: &str, field: &str) -> Result<()> {
+        redis::cmd("HDEL")
+            .arg(key)
+            .arg(field)
+            .exec_async(&mut self.connection.clone())
+            .await?;
+        Ok(())
+    }
+
     pub async fn set<V: Serialize + Sync>(
         &self,
         key: &str,
@@ -69,6 +120,106 @@ impl Redis {
     }
 }
 
+#[async_trait]
+impl CacheBackend for Redis {
+    async fn get(&self, key: &str, field: &str) -> Result<Option<serde_json::Value>> {
+        Redis::get(self, key, field).await
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        field: &str,
+        value: serde_json::Value,
+        ttl: Option<u64>,
+    ) -> Result<()> {
+        Redis::set(self, key, field, &value, ttl).await
+    }
+
+    async fn del(&self, key: &str, field: &str) -> Result<()> {
+        Redis::del(self, key, field).await
+    }
+
+    async fn get_with_ttl(
+        &self,
+        key: &str,
+        field: &str,
+    ) -> Result<Option<(serde_json::Value, Option<Duration>)>> {
+        Redis::get_with_ttl(self, key, field).await
+    }
+
+    async fn exists(&self, key: &str, field: &str) -> Result<bool> {
+        Redis::exists(self, key, field).await
+    }
+
+    async fn get_all(&self, key: &str) -> Result<Vec<(String, serde_json::Value)>> {
+        let res: Vec<(String, String)> = redis::cmd("HGETALL")
+            .arg(key)
+            .query_async(&mut self.connection.clone())
+            .await?;
+
+        let mut values = Vec::with_capacity(res.len());
+        for (field, value) in res {
+            values.push((field, serde_json::from_str(&value)?));
+        }
+
+        Ok(values)
+    }
+
+    async fn get_many(&self, keys: &[(String, String)]) -> Result<Vec<Option<serde_json::Value>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipe = redis::pipe();
+        for (key, field) in keys {
+            pipe.cmd("HGET").arg(key).arg(field);
+        }
+
+        let res: Vec<Option<String>> = pipe.query_async(&mut self.connection.clone()).await?;
+
+        let mut values = Vec::with_capacity(res.len());
+        for value in res {
+            values.push(match value {
+                Some(value) => Some(serde_json::from_str(&value)?),
+                None => None,
+            });
+        }
+
+        Ok(values)
+    }
+
+    async fn set_many(&self, entries: &[CacheEntry]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+
+        for (key, field, value, ttl) in entries {
+            pipe.cmd("HSET")
+                .arg(key)
+                .arg(field)
+                .arg(serde_json::to_string(value)?)
+                .ignore();
+
+            if let Some(ttl) = ttl {
+                pipe.cmd("HEXPIRE")
+                    .arg(key)
+                    .arg(ttl)
+                    .arg("FIELDS")
+                    .arg(1)
+                    .arg(field)
+                    .ignore();
+            }
+        }
+
+        pipe.exec_async(&mut self.connection.clone()).await?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -176,4 +327,141 @@ mod test {
 
         assert!(ttl >= ttl_set - 1 && ttl <= ttl_set);
     }
+
+    #[tokio::test]
+    async fn test_get_with_ttl() {
+        let cache = Redis::new(&CacheConfig {
+            redis_endpoint: REDIS_ENDPOINT.to_string(),
+        })
+        .await
+        .unwrap();
+
+        let key = "test_get_ttl";
+        let data = Data {
+            data: "ttl".to_string(),
+        };
+        cache.set(key, "ttl", &data, Some(21)).await.unwrap();
+        cache.set(key, "no_ttl", &data, None).await.unwrap();
+
+        let (value, ttl) = cache
+            .get_with_ttl::<Data>(key, "ttl")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(value, data);
+        let ttl = ttl.unwrap();
+        assert!(ttl > Duration::from_secs(20) && ttl <= Duration::from_secs(21));
+
+        let (_, ttl) = cache
+            .get_with_ttl::<Data>(key, "no_ttl")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ttl, None);
+
+        assert!(
+            cache
+                .get_with_ttl::<Data>(key, "missing")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exists() {
+        let cache = Redis::new(&CacheConfig {
+            redis_endpoint: REDIS_ENDPOINT.to_string(),
+        })
+        .await
+        .unwrap();
+
+        let key = "test_exists";
+        let data = Data {
+            data: "exists".to_string(),
+        };
+        cache.set(key, "present", &data, None).await.unwrap();
+        cache.del(key, "absent").await.unwrap();
+
+        assert!(cache.exists(key, "present").await.unwrap());
+        assert!(!cache.exists(key, "absent").await.unwrap());
+        assert!(
+            !cache
+                .exists("test_exists_missing", "present")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_set_many() {
+        let cache = Redis::new(&CacheConfig {
+            redis_endpoint: REDIS_ENDPOINT.to_string(),
+        })
+        .await
+        .unwrap();
+
+        let key = "test_many";
+        cache
+            .set_many(&[
+                (
+                    key.to_string(),
+                    "a".to_string(),
+                    serde_json::json!("a"),
+                    None,
+                ),
+                (
+                    key.to_string(),
+                    "b".to_string(),
+                    serde_json::json!("b"),
+                    Some(21),
+                ),
+            ])
+            .await
+            .unwrap();
+
+        let values = cache
+            .get_many(&[
+                (key.to_string(), "b".to_string()),
+                (key.to_string(), "missing".to_string()),
+                (key.to_string(), "a".to_string()),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                Some(serde_json::json!("b")),
+                None,
+                Some(serde_json::json!("a"))
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_all() {
+        let cache = Redis::new(&CacheConfig {
+            redis_endpoint: REDIS_ENDPOINT.to_string(),
+        })
+        .await
+        .unwrap();
+
+        let key = "test_all";
+        cache.del(key, "a").await.unwrap();
+        cache.del(key, "b").await.unwrap();
+
+        cache.set(key, "a", &"a", None).await.unwrap();
+        cache.set(key, "b", &"b", None).await.unwrap();
+
+        let mut values = cache.get_all(key).await.unwrap();
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            values,
+            vec![
+                ("a".to_string(), serde_json::json!("a")),
+                ("b".to_string(), serde_json::json!("b"))
+            ]
+        );
+        assert!(cache.get_all("test_all_empty").await.unwrap().is_empty());
+    }
 }
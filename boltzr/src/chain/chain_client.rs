@@ -1,24 +1,101 @@
-use crate::chain::rpc_client::RpcClient;
-use crate::chain::types::{NetworkInfo, RawMempool, RpcParam, ZmqNotification};
-use crate::chain::utils::{Outpoint, Transaction, parse_transaction_hex};
+use crate::chain::reorg::{BlockHistory, ReorgEvent};
+use crate::chain::rpc_client::{RpcClient, RpcTransport};
+use crate::chain::rpc_metrics::MethodStats;
+use crate::chain::types::{
+    BlockHeader, BlockchainInfo, DescriptorInfo, FeeEstimate, ImportDescriptorResult,
+    MempoolAcceptResult, MempoolEntry, MempoolFeeEntry, MempoolInfo, NetworkInfo,
+    RPC_INVALID_ADDRESS_OR_KEY, RPC_VERIFY_ERROR, RPC_VERIFY_REJECTED, RawMempool, RpcParam,
+    SmartFeeEstimate, SpentStatus, VerboseTransaction, ZmqNotification,
+};
+use crate::chain::utils::{
+    Block, DescriptorMatch, Outpoint, RelevantTransaction, Transaction, parse_transaction_hex,
+};
 use crate::chain::zmq_client::ZmqClient;
-use crate::chain::{BaseClient, Client, Config};
+use crate::chain::{BaseClient, ChainError, Client, Config, ScanGroup};
+use crate::wallet::{DEFAULT_GAP_LIMIT, OutputDescriptor};
+use alloy::hex;
 use async_trait::async_trait;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast::Receiver;
-use tracing::{debug, error, info, trace};
+use tokio::sync::{Mutex, Semaphore, mpsc};
+use tracing::{Instrument, Span, debug, error, info, instrument, trace, warn};
 
 const MAX_WORKERS: usize = 16;
 const MEMPOOL_FETCH_CHUNK_SIZE: usize = 64;
+const MAX_CONCURRENT_BATCHES: usize = 8;
+const NETWORK_INFO_TTL: Duration = Duration::from_secs(30);
+const REORG_POLL_INTERVAL: Duration = Duration::from_secs(10);
+const REORG_DEPTH: usize = 32;
+const CONNECT_RETRY_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const CONNECT_RETRY_MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+// Ties the logs of a mempool scan and its workers together
+static SCAN_ID: AtomicU64 = AtomicU64::new(0);
+
+// What made a transaction relevant for a scan
+#[derive(Debug, Clone, PartialEq)]
+enum MatchReason {
+    Input(Outpoint),
+    Output(Vec<u8>),
+}
+
+impl MatchReason {
+    // Scripts can be long, so they are only logged at debug level
+    fn log(&self, symbol: &str, tx: &Transaction) {
+        match self {
+            MatchReason::Input(input) => debug!(
+                tx_id = tx.txid(),
+                vsize = tx.vsize(),
+                input = format!("{}:{}", input.tx_id(), input.vout),
+                "Mempool transaction of {} chain spends a relevant input",
+                symbol
+            ),
+            MatchReason::Output(script_pubkey) => debug!(
+                tx_id = tx.txid(),
+                vsize = tx.vsize(),
+                script_pubkey = hex::encode(script_pubkey),
+                "Mempool transaction of {} chain pays to a relevant output",
+                symbol
+            ),
+        }
+    }
+}
+
+// State of the incremental mempool scans
+#[derive(Debug, Default)]
+struct MempoolSeen {
+    transactions: HashMap<String, Transaction>,
+    // Filters of the previous scan
+    inputs: HashSet<Outpoint>,
+    outputs: HashSet<Vec<u8>>,
+}
 
 #[derive(Debug, Clone)]
-pub struct ChainClient {
-    client: RpcClient,
+pub struct ChainClient<R = RpcClient> {
+    client: R,
     client_type: crate::chain::types::Type,
     zmq_client: ZmqClient,
+
+    scan_workers: usize,
+    scan_chunk_size: usize,
+    // Shared by all workers and clones
+    batches: Arc<Semaphore>,
+
+    network_info_ttl: Duration,
+    network_info: Arc<Mutex<Option<(Instant, NetworkInfo)>>>,
+
+    reorg_poll_interval: Duration,
+    reorg_depth: usize,
+
+    descriptor_gap_limit: u32,
+
+    mempool_seen: Arc<Mutex<MempoolSeen>>,
 }
 
-impl PartialEq for ChainClient {
+impl<R: RpcTransport + PartialEq> PartialEq for ChainClient<R> {
     fn eq(&self, other: &Self) -> bool {
         self.client == other.client && self.client_type == other.client_type
     }
@@ -30,143 +107,251 @@ impl ChainClient {
         symbol: String,
         config: Config,
     ) -> anyhow::Result<Self> {
-        Ok(Self {
-            client_type,
-            client: RpcClient::new(symbol, config.clone())?,
-            zmq_client: ZmqClient::new(client_type, config),
-        })
-    }
-
-    fn is_relevant_tx(
-        relevant_inputs: &HashSet<Outpoint>,
-        relevant_outputs: &HashSet<Vec<u8>>,
-        tx: &Transaction,
-    ) -> bool {
-        tx.input_outpoints()
-            .iter()
-            .any(|input| relevant_inputs.contains(input))
-            || tx
-                .output_script_pubkeys()
-                .iter()
-                .any(|output| relevant_outputs.contains(output))
+        let client = RpcClient::new(symbol, config.clone())?;
+        Self::with_transport(client_type, client, config)
     }
 }
 
-#[async_trait]
-impl BaseClient for ChainClient {
-    fn kind(&self) -> String {
-        "Chain client".to_string()
-    }
+impl<R: RpcTransport> ChainClient<R> {
+    pub fn with_transport(
+        client_type: crate::chain::types::Type,
+        client: R,
+        config: Config,
+    ) -> anyhow::Result<Self> {
+        let scan_workers = config.mempool_scan_workers.unwrap_or(MAX_WORKERS);
+        let scan_chunk_size = config
+            .mempool_fetch_chunk_size
+            .unwrap_or(MEMPOOL_FETCH_CHUNK_SIZE);
+        let network_info_ttl = config.network_info_ttl.unwrap_or(NETWORK_INFO_TTL);
 
-    fn symbol(&self) -> String {
-        self.client.symbol.clone()
-    }
+        let max_concurrent_batches = config
+            .max_concurrent_batches
+            .unwrap_or(MAX_CONCURRENT_BATCHES);
+        if max_concurrent_batches < 1 {
+            return Err(anyhow::anyhow!(
+                "max concurrent batches have to be at least 1"
+            ));
+        }
 
-    async fn connect(&mut self) -> anyhow::Result<()> {
-        let info = self.network_info().await?;
-        let notifications = self
-            .client
-            .request::<Vec<ZmqNotification>>("getzmqnotifications", None)
-            .await?;
-        self.zmq_client.connect(notifications).await?;
+        let reorg_poll_interval = config.reorg_poll_interval.unwrap_or(REORG_POLL_INTERVAL);
+        let reorg_depth = config.reorg_depth.unwrap_or(REORG_DEPTH);
+        if reorg_depth < 1 {
+            return Err(anyhow::anyhow!("reorg depth has to be at least 1"));
+        }
 
-        info!(
-            "Connected to {} chain client: {}",
-            self.client.symbol, info.subversion
-        );
+        let descriptor_gap_limit = config.descriptor_gap_limit.unwrap_or(DEFAULT_GAP_LIMIT);
+        if descriptor_gap_limit < 1 {
+            return Err(anyhow::anyhow!("gap limit has to be at least 1"));
+        }
 
-        Ok(())
+        Self {
+            client_type,
+            client,
+            zmq_client: ZmqClient::new(client_type, config),
+            scan_workers: MAX_WORKERS,
+            scan_chunk_size: MEMPOOL_FETCH_CHUNK_SIZE,
+            batches: Arc::new(Semaphore::new(max_concurrent_batches)),
+            network_info_ttl,
+            network_info: Arc::new(Mutex::new(None)),
+            reorg_poll_interval,
+            reorg_depth,
+            descriptor_gap_limit,
+            mempool_seen: Arc::new(Mutex::new(MempoolSeen::default())),
+        }
+        .with_scan_config(scan_workers, scan_chunk_size)
     }
-}
 
-#[async_trait]
-impl Client for ChainClient {
-    fn tx_receiver(&self) -> Receiver<Transaction> {
-        self.zmq_client.tx_sender.subscribe()
-    }
+    pub fn with_scan_config(mut self, workers: usize, chunk_size: usize) -> anyhow::Result<Self> {
+        if workers < 1 {
+            return Err(anyhow::anyhow!(
+                "mempool scan workers have to be at least 1"
+            ));
+        }
+        if chunk_size < 1 {
+            return Err(anyhow::anyhow!(
+                "mempool fetch chunk size has to be at least 1"
+            ));
+        }
 
-    async fn scan_mempool(
-        &self,
-        relevant_inputs: &HashSet<Outpoint>,
-        relevant_outputs: &HashSet<Vec<u8>>,
-    ) -> anyhow::Result<Vec<Transaction>> {
-        info!("Scanning mempool of {} chain", self.client.symbol);
+        self.scan_workers = workers;
+        self.scan_chunk_size = chunk_size;
+        Ok(self)
+    }
 
-        let mempool = self
-            .client
-            .request::<RawMempool>("getrawmempool", None)
-            .await?;
+    // Fans the fetching of the raw transactions out to multiple workers
+    fn fetch_mempool(&self, mempool: RawMempool) -> mpsc::Receiver<String> {
         let mempool_size = mempool.len();
+        let (tx, rx) = mpsc::channel(1_024);
 
-        if mempool_size == 0 {
-            debug!("Mempool of {} chain is empty", self.client.symbol);
-            return Ok(Vec::default());
-        }
-
-        let (tx, mut rx) = tokio::sync::mpsc::channel(1_024);
-
-        let fetcher_threads = std::cmp::min(num_cpus::get() / 2, MAX_WORKERS);
+        let fetcher_threads = (num_cpus::get() / 2).clamp(1, self.scan_workers);
         debug!(
             "Scanning {} mempool transactions of {} chain with {} workers",
-            mempool_size, self.client.symbol, fetcher_threads
+            mempool_size,
+            self.client.symbol(),
+            fetcher_threads
         );
-        for chunk in mempool.chunks(std::cmp::max(mempool_size / fetcher_threads, 1)) {
+
+        // Batches are dealt out round robin, so that the workers go through the mempool in its order
+        let mut worker_batches = vec![Vec::new(); fetcher_threads];
+        for (i, batch) in mempool.chunks(self.scan_chunk_size).enumerate() {
+            worker_batches[i % fetcher_threads].push(batch.to_vec());
+        }
+
+        for batches in worker_batches
+            .into_iter()
+            .filter(|batches| !batches.is_empty())
+        {
             let tx_cp = tx.clone();
             let self_cp = self.clone();
-            let chunk = chunk.to_vec();
-
-            tokio::spawn(async move {
-                let tx_chunks = chunk.chunks(MEMPOOL_FETCH_CHUNK_SIZE);
-                for tx_ids in tx_chunks {
-                    let txs_hex = match self_cp
-                        .client
-                        .request_batch::<String>(
-                            "getrawtransaction",
-                            tx_ids
-                                .iter()
-                                .map(|tx_id| vec![RpcParam::Str(tx_id.clone())])
-                                .collect(),
-                        )
-                        .await
-                    {
-                        Ok(txs) => txs,
-
-                        // When the entire request fails, something is terribly wrong
-                        Err(err) => {
-                            error!(
-                                "Could not fetch {} mempool transactions: {}",
-                                self_cp.symbol(),
-                                err
+
+            tokio::spawn(
+                async move {
+                    for tx_ids in batches {
+                        // No need to fetch more when nobody is listening anymore
+                        if tx_cp.is_closed() {
+                            debug!(
+                                "Stopping {} mempool fetcher because the scan was dropped",
+                                self_cp.symbol()
                             );
-                            break;
+                            return;
                         }
-                    };
-
-                    for tx_hex in txs_hex {
-                        match tx_hex {
-                            Ok(tx_hex) => {
-                                if let Err(err) = tx_cp.send(tx_hex).await {
-                                    error!("Could not send to mempool channel: {}", err);
-                                    break;
-                                }
+
+                        let permit = match self_cp.batches.acquire().await {
+                            Ok(permit) => permit,
+                            Err(err) => {
+                                error!(
+                                    "Could not acquire {} mempool batch permit: {}",
+                                    self_cp.symbol(),
+                                    err
+                                );
+                                break;
+                            }
+                        };
+
+                        let txs_hex = match self_cp
+                            .client
+                            .request_batch::<String>(
+                                "getrawtransaction",
+                                tx_ids
+                                    .iter()
+                                    .map(|tx_id| vec![RpcParam::Str(tx_id.clone())])
+                                    .collect(),
+                            )
+                            .await
+                        {
+                            Ok(txs) => txs,
+
+                            // A hung node should not stall the whole scan
+                            Err(err)
+                                if matches!(
+                                    err.downcast_ref::<ChainError>(),
+                                    Some(ChainError::Timeout(_))
+                                ) =>
+                            {
+                                warn!(
+                                    "Fetching {} {} mempool transactions timed out: {}",
+                                    tx_ids.len(),
+                                    self_cp.symbol(),
+                                    err
+                                );
+                                continue;
                             }
 
-                            // When a single transaction request fails, it's fine.
-                            // Can happen if the transaction was evicted from the mempool
+                            // When the entire request fails, something is terribly wrong
                             Err(err) => {
-                                trace!(
-                                    "Could not fetch single {} mempool transaction: {}",
+                                error!(
+                                    "Could not fetch {} mempool transactions: {}",
                                     self_cp.symbol(),
                                     err
                                 );
+                                break;
                             }
                         };
+                        // Waiting for the receiver should not block other batches
+                        drop(permit);
+
+                        for tx_hex in txs_hex {
+                            match tx_hex {
+                                Ok(tx_hex) => {
+                                    if tx_cp.send(tx_hex).await.is_err() {
+                                        debug!(
+                                            "Stopping {} mempool fetcher because the scan was dropped",
+                                            self_cp.symbol()
+                                        );
+                                        return;
+                                    }
+                                }
+
+                                // When a single transaction request fails, it's fine.
+                                // Can happen if the transaction was evicted from the mempool
+                                Err(err) => {
+                                    trace!(
+                                        "Could not fetch single {} mempool transaction: {}",
+                                        self_cp.symbol(),
+                                        err
+                                    );
+                                }
+                            };
+                        }
                     }
                 }
-            });
+                .instrument(Span::current()),
+            );
         }
         drop(tx);
 
+        rx
+    }
+
+    async fn scan_mempool_raw_with_progress(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+        on_progress: impl Fn(usize, usize) + Send + Sync,
+    ) -> Result<Vec<(Transaction, String)>, ChainError> {
+        self.scan_mempool_filtered(
+            |tx| match Self::match_reason(relevant_inputs, relevant_outputs, tx) {
+                Some(reason) => {
+                    reason.log(self.client.symbol(), tx);
+                    true
+                }
+                None => false,
+            },
+            on_progress,
+        )
+        .await
+    }
+
+    #[instrument(
+        name = "scan_mempool",
+        skip_all,
+        fields(
+            symbol = self.client.symbol(),
+            scan_id = SCAN_ID.fetch_add(1, Ordering::Relaxed),
+            mempool_size = tracing::field::Empty,
+        )
+    )]
+    async fn scan_mempool_filtered(
+        &self,
+        predicate: impl Fn(&Transaction) -> bool + Send + Sync,
+        on_progress: impl Fn(usize, usize) + Send + Sync,
+    ) -> Result<Vec<(Transaction, String)>, ChainError> {
+        info!("Scanning mempool of {} chain", self.client.symbol());
+
+        let mempool = self
+            .client
+            .request::<RawMempool>("getrawmempool", None)
+            .await?;
+        let mempool_size = mempool.len();
+        Span::current().record("mempool_size", mempool_size);
+
+        if mempool_size == 0 {
+            debug!("Mempool of {} chain is empty", self.client.symbol());
+            return Ok(Vec::default());
+        }
+
+        let mut rx = self.fetch_mempool(mempool);
+
         let mut relevant_txs = Vec::new();
 
         let mut i = 0;
@@ -176,188 +361,2102 @@ impl Client for ChainClient {
                 None => break,
             };
             let tx = parse_transaction_hex(&self.client_type, &tx_hex)?;
-            if Self::is_relevant_tx(relevant_inputs, relevant_outputs, &tx) {
-                relevant_txs.push(tx);
+            if predicate(&tx) {
+                relevant_txs.push((tx, tx_hex));
             }
 
             i += 1;
             if i % 1_000 == 0 {
+                on_progress(i, mempool_size);
                 trace!(
                     "Scanned {}/{} transactions of {} chain mempool",
-                    i, mempool_size, self.client.symbol
+                    i,
+                    mempool_size,
+                    self.client.symbol()
                 );
             }
         }
 
+        if i % 1_000 != 0 {
+            on_progress(i, mempool_size);
+        }
+
         debug!(
             "Scanned {} mempool transactions of {} chain",
-            mempool_size, self.client.symbol
+            mempool_size,
+            self.client.symbol()
         );
 
         if !relevant_txs.is_empty() {
             info!(
                 "Found {} relevant transactions in mempool of {} chain",
                 relevant_txs.len(),
-                self.client.symbol
+                self.client.symbol()
             );
         }
 
         Ok(relevant_txs)
     }
 
-    async fn network_info(&self) -> anyhow::Result<NetworkInfo> {
-        self.client.request("getnetworkinfo", None).await
+    async fn mempool_by_fee_rate(&self) -> Result<RawMempool, ChainError> {
+        match self
+            .client
+            .request::<HashMap<String, MempoolFeeEntry>>(
+                "getrawmempool",
+                Some(vec![RpcParam::Json(serde_json::Value::Bool(true))]),
+            )
+            .await
+        {
+            Ok(entries) => Ok(RawMempool::by_fee_rate(entries)),
+            // The verbose mempool is a lot bigger, so it is more likely to run into limits of the node
+            Err(err) => {
+                warn!(
+                    "Could not fetch verbose mempool of {} chain, scanning it unordered: {}",
+                    self.client.symbol(),
+                    err
+                );
+                Ok(self
+                    .client
+                    .request::<RawMempool>("getrawmempool", None)
+                    .await?)
+            }
+        }
     }
-}
 
-#[cfg(test)]
-pub mod test {
-    use crate::chain::chain_client::ChainClient;
-    use crate::chain::types::{RawMempool, RpcParam, Type};
-    use crate::chain::utils::{Transaction, parse_transaction_hex};
-    use crate::chain::{BaseClient, Client, Config};
-    use serial_test::serial;
-    use std::collections::HashSet;
-    use std::sync::OnceLock;
+    // Transactions are checked in the order in which they are fetched
+    fn stream_mempool(
+        &self,
+        mempool: RawMempool,
+        relevant_inputs: HashSet<Outpoint>,
+        relevant_outputs: HashSet<Vec<u8>>,
+    ) -> mpsc::Receiver<Transaction> {
+        let (tx, rx) = mpsc::channel(1_024);
+        if mempool.is_empty() {
+            debug!("Mempool of {} chain is empty", self.client.symbol());
+            return rx;
+        }
 
-    const PORT: u16 = 18_443;
+        let mut hex_rx = self.fetch_mempool(mempool);
+        let self_cp = self.clone();
 
-    pub fn get_client() -> ChainClient {
-        static CLIENT: OnceLock<ChainClient> = OnceLock::new();
-        CLIENT
-            .get_or_init(|| {
-                ChainClient::new(
-                    Type::Bitcoin,
-                    "BTC".to_string(),
-                    Config {
-                        host: "127.0.0.1".to_string(),
-                        port: PORT,
-                        cookie: None,
-                        user: Some("boltz".to_string()),
-                        password: Some("anoVB0m1KvX0SmpPxvaLVADg0UQVLQTEx3jCD3qtuRI".to_string()),
+        tokio::spawn(async move {
+            loop {
+                // Dropping the receiver of the fetched transactions stops the fetchers
+                let tx_hex = tokio::select! {
+                    tx_hex = hex_rx.recv() => match tx_hex {
+                        Some(tx_hex) => tx_hex,
+                        None => break,
                     },
-                )
-                .unwrap()
-            })
-            .clone()
-    }
+                    _ = tx.closed() => {
+                        debug!(
+                            "Stopping mempool scan of {} chain because the receiver was dropped",
+                            self_cp.symbol()
+                        );
+                        break;
+                    }
+                };
 
-    async fn generate_block(client: &ChainClient) {
-        client
-            .client
-            .request::<serde_json::Value>(
-                "generatetoaddress",
-                Some(vec![
-                    RpcParam::Int(1),
-                    RpcParam::Str(
-                        client
-                            .client
-                            .request::<String>("getnewaddress", None)
-                            .await
-                            .unwrap(),
-                    ),
-                ]),
-            )
-            .await
-            .unwrap();
-    }
+                let transaction = match parse_transaction_hex(&self_cp.client_type, &tx_hex) {
+                    Ok(transaction) => transaction,
+                    Err(err) => {
+                        error!(
+                            "Could not parse {} mempool transaction: {}",
+                            self_cp.symbol(),
+                            err
+                        );
+                        break;
+                    }
+                };
 
-    async fn send_transaction(client: &ChainClient) -> Transaction {
-        let tx_id = client
-            .client
-            .request::<String>(
-                "sendtoaddress",
-                Some(vec![
-                    RpcParam::Str(
-                        client
-                            .client
-                            .request::<String>("getnewaddress", None)
-                            .await
-                            .unwrap(),
-                    ),
-                    RpcParam::Float(0.21),
-                ]),
-            )
-            .await
-            .unwrap();
+                if Self::is_relevant_tx(&relevant_inputs, &relevant_outputs, &transaction)
+                    && tx.send(transaction).await.is_err()
+                {
+                    debug!(
+                        "Stopping mempool scan of {} chain because the receiver was dropped",
+                        self_cp.symbol()
+                    );
+                    break;
+                }
+            }
+        });
 
-        parse_transaction_hex(
-            &Type::Bitcoin,
-            &client
-                .client
-                .request::<String>("getrawtransaction", Some(vec![RpcParam::Str(tx_id)]))
-                .await
-                .unwrap(),
-        )
-        .unwrap()
+        rx
     }
 
-    #[tokio::test]
-    async fn test_connect() {
-        let mut client = get_client();
-
-        client.connect().await.unwrap();
+    async fn blockchain_info(&self) -> anyhow::Result<BlockchainInfo> {
+        self.client.request("getblockchaininfo", None).await
+    }
 
-        assert_ne!(
-            client.network_info().await.unwrap().subversion,
-            "".to_string()
-        );
+    // Waits for the node to come up before connecting, which can take a while when
+    // it is started at the same time as the backend
+    pub async fn connect_with_retry(&mut self, max_wait: Duration) -> anyhow::Result<()> {
+        self.wait_for_network_info(max_wait).await?;
+        self.connect().await
     }
 
-    #[tokio::test]
-    #[serial(BTC)]
-    async fn scan_mempool_empty() {
-        let client = get_client();
+    async fn wait_for_network_info(&self, max_wait: Duration) -> anyhow::Result<NetworkInfo> {
+        let started = Instant::now();
+        let mut backoff = CONNECT_RETRY_INITIAL_BACKOFF;
+        let mut attempt = 1;
 
-        generate_block(&client).await;
+        loop {
+            let err = match self.network_info(true).await {
+                Ok(info) => return Ok(info),
+                Err(err) => err,
+            };
 
-        let mempool = client
-            .client
-            .request::<RawMempool>("getrawmempool", None)
-            .await
-            .unwrap();
-        assert!(mempool.is_empty());
+            let elapsed = started.elapsed();
+            if elapsed >= max_wait {
+                return Err(anyhow::anyhow!(
+                    "could not connect to {} chain client after {} attempts: {}",
+                    self.client.symbol(),
+                    attempt,
+                    err
+                ));
+            }
 
-        let transactions = client
-            .scan_mempool(&HashSet::new(), &HashSet::new())
+            let delay = backoff.min(max_wait - elapsed);
+            warn!(
+                "Could not connect to {} chain client (attempt {}): {}; retrying in {:?}",
+                self.client.symbol(),
+                attempt,
+                err,
+                delay
+            );
+
+            tokio::time::sleep(delay).await;
+            backoff = (backoff * 2).min(CONNECT_RETRY_MAX_BACKOFF);
+            attempt += 1;
+        }
+    }
+
+    async fn zmq_notifications(&self) -> anyhow::Result<Vec<ZmqNotification>> {
+        self.client
+            .request::<Vec<ZmqNotification>>("getzmqnotifications", None)
             .await
-            .unwrap();
-        assert_eq!(transactions.len(), 0);
     }
 
-    #[tokio::test]
-    #[serial(BTC)]
-    async fn scan_mempool_relevant_input() {
-        let client = get_client();
-        let tx = send_transaction(&client).await;
+    async fn poll_reorg(&self, history: &mut BlockHistory) -> anyhow::Result<Option<ReorgEvent>> {
+        let best = self.get_best_block_hash().await?;
+        if history.tip().is_some_and(|(_, tip)| *tip == best) {
+            return Ok(None);
+        }
 
-        let mut inputs = HashSet::new();
-        inputs.insert(tx.input_outpoints()[0].clone());
+        let mut header = self.get_block_header(&best).await?;
 
-        let transactions = client.scan_mempool(&inputs, &HashSet::new()).await.unwrap();
-        assert_eq!(transactions.len(), 1);
-        assert_eq!(transactions[0], tx);
+        // Too far ahead of what was recorded to walk back efficiently
+        let far_ahead = match history.tip() {
+            Some((tip_height, _)) => header.height > tip_height + history.depth() as u64,
+            None => false,
+        };
+        if far_ahead {
+            let event = self.find_orphaned_tip(history).await?;
+            history.reset(vec![(header.height, header.hash)]);
+            return Ok(event);
+        }
 
-        generate_block(&client).await;
-    }
+        // Walk back until the new chain connects to a recorded block
+        let mut blocks = Vec::new();
+        loop {
+            if history.contains(header.height, &header.hash) {
+                break;
+            }
 
-    #[tokio::test]
+            let below_history = history
+                .lowest_height()
+                .is_none_or(|lowest| header.height <= lowest);
+            let previous = header.previous_block_hash.clone();
+            blocks.push((header.height, header.hash));
+
+            match previous {
+                Some(previous) if !below_history => {
+                    header = self.get_block_header(&previous).await?;
+                }
+                _ => break,
+            }
+        }
+        blocks.reverse();
+
+        Ok(history.connect(blocks))
+    }
+
+    // Checks the recorded blocks against the blocks at their height in the
+    // current chain, for when walking back from the new tip would take too long
+    async fn find_orphaned_tip(
+        &self,
+        history: &BlockHistory,
+    ) -> anyhow::Result<Option<ReorgEvent>> {
+        let from_height = match history.tip() {
+            Some((height, _)) => *height,
+            None => return Ok(None),
+        };
+
+        for (height, hash) in history.blocks().rev() {
+            let current = self
+                .client
+                .request::<String>("getblockhash", Some(vec![RpcParam::Int(*height as i64)]))
+                .await?;
+            if current != *hash {
+                continue;
+            }
+
+            if *height == from_height {
+                return Ok(None);
+            }
+
+            return Ok(Some(ReorgEvent {
+                from_height,
+                common_ancestor: *height,
+                deep: false,
+            }));
+        }
+
+        Ok(Some(ReorgEvent {
+            from_height,
+            common_ancestor: history
+                .lowest_height()
+                .unwrap_or_default()
+                .saturating_sub(1),
+            deep: true,
+        }))
+    }
+
+    fn map_not_found(tx_id: &str, err: anyhow::Error) -> ChainError {
+        match ChainError::from(err) {
+            ChainError::Rpc(err) if err.code == RPC_INVALID_ADDRESS_OR_KEY => {
+                ChainError::NotFound(tx_id.to_string())
+            }
+            err => err,
+        }
+    }
+
+    fn is_relevant_tx(
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+        tx: &Transaction,
+    ) -> bool {
+        Self::match_reason(relevant_inputs, relevant_outputs, tx).is_some()
+    }
+
+    fn match_reason(
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+        tx: &Transaction,
+    ) -> Option<MatchReason> {
+        if let Some(input) = tx
+            .input_outpoints()
+            .into_iter()
+            .find(|input| relevant_inputs.contains(input))
+        {
+            return Some(MatchReason::Input(input));
+        }
+
+        tx.output_script_pubkeys()
+            .into_iter()
+            .find(|output| relevant_outputs.contains(output))
+            .map(MatchReason::Output)
+    }
+}
+
+#[async_trait]
+impl<R: RpcTransport> BaseClient for ChainClient<R> {
+    fn kind(&self) -> String {
+        "Chain client".to_string()
+    }
+
+    fn symbol(&self) -> String {
+        self.client.symbol().to_string()
+    }
+
+    async fn connect(&mut self) -> anyhow::Result<()> {
+        let info = self.network_info(false).await?;
+        let notifications = self.zmq_notifications().await?;
+        self.zmq_client.connect(notifications).await?;
+
+        // A node that is still syncing is usable, but its view of the chain is behind
+        match self.blockchain_info().await {
+            Ok(info) if info.initial_block_download => warn!(
+                "{} chain client is in initial block download: {:.2}% synced",
+                self.client.symbol(),
+                info.verification_progress * 100.0
+            ),
+            Ok(_) => {}
+            Err(err) => warn!(
+                "Could not check sync status of {} chain client: {}",
+                self.client.symbol(),
+                err
+            ),
+        }
+
+        info!(
+            "Connected to {} chain client: {}",
+            self.client.symbol(),
+            info.subversion
+        );
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<R: RpcTransport> Client for ChainClient<R> {
+    fn rpc_stats(&self) -> HashMap<String, MethodStats> {
+        self.client.metrics()
+    }
+
+    fn tx_receiver(&self) -> Receiver<Transaction> {
+        self.zmq_client.tx_sender.subscribe()
+    }
+
+    async fn subscribe_raw_tx(&self) -> Result<mpsc::Receiver<Transaction>, ChainError> {
+        let notifications = self.zmq_notifications().await?;
+        Ok(self.zmq_client.subscribe_raw_tx(notifications)?)
+    }
+
+    async fn subscribe_raw_block(&self) -> Result<mpsc::Receiver<Block>, ChainError> {
+        let notifications = self.zmq_notifications().await?;
+        Ok(self.zmq_client.subscribe_raw_block(notifications)?)
+    }
+
+    async fn watch_reorgs(&self) -> Result<mpsc::Receiver<ReorgEvent>, ChainError> {
+        let mut history = BlockHistory::new(self.reorg_depth);
+        self.poll_reorg(&mut history).await?;
+
+        let (sender, receiver) = mpsc::channel(16);
+        let self_cp = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = sender.closed() => break,
+                    _ = tokio::time::sleep(self_cp.reorg_poll_interval) => {}
+                }
+
+                match self_cp.poll_reorg(&mut history).await {
+                    Ok(Some(event)) => {
+                        warn!(
+                            "Detected {} chain reorg from height {} to common ancestor {}",
+                            self_cp.client.symbol(),
+                            event.from_height,
+                            event.common_ancestor
+                        );
+                        if event.deep {
+                            warn!(
+                                "{} chain reorg is deeper than the {} recorded blocks; common ancestor might be lower than {}",
+                                self_cp.client.symbol(),
+                                history.depth(),
+                                event.common_ancestor
+                            );
+                        }
+                        if sender.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        warn!(
+                            "Could not poll {} chain for reorgs: {}",
+                            self_cp.client.symbol(),
+                            err
+                        );
+                    }
+                }
+            }
+
+            debug!(
+                "Stopped watching {} chain for reorgs",
+                self_cp.client.symbol()
+            );
+        });
+
+        Ok(receiver)
+    }
+
+    async fn scan_mempool(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+    ) -> Result<Vec<Transaction>, ChainError> {
+        self.scan_mempool_with_progress(relevant_inputs, relevant_outputs, &|_, _| {})
+            .await
+    }
+
+    async fn scan_mempool_with_progress(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+        on_progress: &(dyn Fn(usize, usize) + Send + Sync),
+    ) -> Result<Vec<Transaction>, ChainError> {
+        Ok(self
+            .scan_mempool_raw_with_progress(relevant_inputs, relevant_outputs, on_progress)
+            .await?
+            .into_iter()
+            .map(|(tx, _)| tx)
+            .collect())
+    }
+
+    async fn scan_mempool_with_filter(
+        &self,
+        predicate: &(dyn Fn(&Transaction) -> bool + Send + Sync),
+    ) -> Result<Vec<Transaction>, ChainError> {
+        Ok(self
+            .scan_mempool_filtered(predicate, |_, _| {})
+            .await?
+            .into_iter()
+            .map(|(tx, _)| tx)
+            .collect())
+    }
+
+    async fn scan_mempool_raw(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+    ) -> Result<Vec<(Transaction, String)>, ChainError> {
+        self.scan_mempool_raw_with_progress(relevant_inputs, relevant_outputs, |_, _| {})
+            .await
+    }
+
+    async fn scan_mempool_incremental(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+    ) -> Result<Vec<Transaction>, ChainError> {
+        let mempool = self
+            .client
+            .request::<RawMempool>("getrawmempool", None)
+            .await?;
+
+        // Held during the scan so that concurrent scans don't fetch the same transactions
+        let mut seen = self.mempool_seen.lock().await;
+
+        // Forget transactions that were evicted or confirmed
+        let current = mempool.iter().collect::<HashSet<_>>();
+        seen.transactions.retain(|tx_id, _| current.contains(tx_id));
+
+        // Transactions that were seen already are checked against the filters
+        // that were added since the previous scan
+        let added_inputs = relevant_inputs
+            .difference(&seen.inputs)
+            .cloned()
+            .collect::<HashSet<_>>();
+        let added_outputs = relevant_outputs
+            .difference(&seen.outputs)
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        let mut relevant_txs = Vec::new();
+        if !added_inputs.is_empty() || !added_outputs.is_empty() {
+            relevant_txs.extend(
+                seen.transactions
+                    .values()
+                    .filter(|tx| {
+                        Self::is_relevant_tx(&added_inputs, &added_outputs, tx)
+                            && !Self::is_relevant_tx(&seen.inputs, &seen.outputs, tx)
+                    })
+                    .cloned(),
+            );
+        }
+        seen.inputs = relevant_inputs.clone();
+        seen.outputs = relevant_outputs.clone();
+
+        let new = mempool
+            .iter()
+            .filter(|tx_id| !seen.transactions.contains_key(*tx_id))
+            .cloned()
+            .collect::<RawMempool>();
+        debug!(
+            "Incrementally scanning {} new of {} mempool transactions of {} chain",
+            new.len(),
+            mempool.len(),
+            self.client.symbol()
+        );
+        if new.is_empty() {
+            return Ok(relevant_txs);
+        }
+
+        let mut rx = self.fetch_mempool(new);
+
+        while let Some(tx_hex) = rx.recv().await {
+            let tx = parse_transaction_hex(&self.client_type, &tx_hex)?;
+            if Self::is_relevant_tx(relevant_inputs, relevant_outputs, &tx) {
+                relevant_txs.push(tx.clone());
+            }
+
+            // Only fetched transactions are skipped next time
+            seen.transactions.insert(tx.txid(), tx);
+        }
+
+        Ok(relevant_txs)
+    }
+
+    async fn scan_mempool_detailed(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+    ) -> Result<Vec<RelevantTransaction>, ChainError> {
+        Ok(self
+            .scan_mempool_raw(relevant_inputs, relevant_outputs)
+            .await?
+            .into_iter()
+            .filter_map(|(tx, _)| RelevantTransaction::new(tx, relevant_inputs, relevant_outputs))
+            .collect())
+    }
+
+    async fn scan_mempool_multi(
+        &self,
+        groups: &[ScanGroup],
+    ) -> Result<HashMap<String, Vec<Transaction>>, ChainError> {
+        let mut relevant_txs = groups
+            .iter()
+            .map(|(id, _, _)| (id.clone(), Vec::new()))
+            .collect::<HashMap<_, _>>();
+        if groups.is_empty() {
+            return Ok(relevant_txs);
+        }
+
+        let mempool = self
+            .client
+            .request::<RawMempool>("getrawmempool", None)
+            .await?;
+        debug!(
+            "Scanning {} mempool transactions of {} chain for {} groups",
+            mempool.len(),
+            self.client.symbol(),
+            groups.len()
+        );
+        if mempool.is_empty() {
+            return Ok(relevant_txs);
+        }
+
+        let mut rx = self.fetch_mempool(mempool);
+        while let Some(tx_hex) = rx.recv().await {
+            let tx = parse_transaction_hex(&self.client_type, &tx_hex)?;
+
+            for (id, inputs, outputs) in groups {
+                if Self::is_relevant_tx(inputs, outputs, &tx) {
+                    relevant_txs.entry(id.clone()).or_default().push(tx.clone());
+                }
+            }
+        }
+
+        Ok(relevant_txs)
+    }
+
+    async fn scan_mempool_descriptors(
+        &self,
+        descriptors: &[OutputDescriptor],
+    ) -> Result<Vec<DescriptorMatch>, ChainError> {
+        let mut scripts = HashMap::new();
+        for (position, descriptor) in descriptors.iter().enumerate() {
+            for (index, script) in descriptor
+                .script_pubkeys(self.descriptor_gap_limit)?
+                .into_iter()
+                .enumerate()
+            {
+                // The first descriptor that derives a script claims it
+                scripts.entry(script).or_insert((position, index as u32));
+            }
+        }
+        if scripts.is_empty() {
+            return Ok(Vec::default());
+        }
+
+        let outputs = scripts.keys().cloned().collect::<HashSet<_>>();
+        Ok(self
+            .scan_mempool_raw(&HashSet::new(), &outputs)
+            .await?
+            .into_iter()
+            .flat_map(|(tx, _)| DescriptorMatch::from_transaction(tx, &scripts))
+            .collect())
+    }
+
+    async fn scan_mempool_stream(
+        &self,
+        relevant_inputs: HashSet<Outpoint>,
+        relevant_outputs: HashSet<Vec<u8>>,
+    ) -> Result<mpsc::Receiver<Transaction>, ChainError> {
+        info!("Streaming mempool scan of {} chain", self.client.symbol());
+
+        let mempool = self
+            .client
+            .request::<RawMempool>("getrawmempool", None)
+            .await?;
+
+        Ok(self.stream_mempool(mempool, relevant_inputs, relevant_outputs))
+    }
+
+    async fn scan_mempool_ordered(
+        &self,
+        relevant_inputs: HashSet<Outpoint>,
+        relevant_outputs: HashSet<Vec<u8>>,
+    ) -> Result<mpsc::Receiver<Transaction>, ChainError> {
+        info!(
+            "Streaming mempool scan of {} chain by fee rate",
+            self.client.symbol()
+        );
+
+        let mempool = self.mempool_by_fee_rate().await?;
+        Ok(self.stream_mempool(mempool, relevant_inputs, relevant_outputs))
+    }
+
+    async fn network_info(&self, force_refresh: bool) -> Result<NetworkInfo, ChainError> {
+        // Held during the request so that concurrent callers share one call
+        let mut cached = self.network_info.lock().await;
+        if let Some((_, info)) = cached.as_ref().filter(|(fetched_at, _)| {
+            !force_refresh && fetched_at.elapsed() < self.network_info_ttl
+        }) {
+            return Ok(info.clone());
+        }
+
+        let info = self
+            .client
+            .request::<NetworkInfo>("getnetworkinfo", None)
+            .await?;
+        *cached = Some((Instant::now(), info.clone()));
+
+        Ok(info)
+    }
+
+    async fn send_raw_transaction(&self, tx_hex: &str) -> Result<String, ChainError> {
+        self.client
+            .request::<String>(
+                "sendrawtransaction",
+                Some(vec![RpcParam::Str(tx_hex.to_string())]),
+            )
+            .await
+            .map_err(|err| match ChainError::from(err) {
+                ChainError::Rpc(err)
+                    if err.code == RPC_VERIFY_REJECTED || err.code == RPC_VERIFY_ERROR =>
+                {
+                    ChainError::Rejected(err.message)
+                }
+                err => err,
+            })
+    }
+
+    async fn test_mempool_accept(&self, tx_hex: &str) -> Result<MempoolAcceptResult, ChainError> {
+        self.client
+            .request::<Vec<MempoolAcceptResult>>(
+                "testmempoolaccept",
+                Some(vec![RpcParam::Json(serde_json::json!([tx_hex]))]),
+            )
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ChainError::Other("testmempoolaccept returned no result".to_string()))
+    }
+
+    async fn estimate_fee(&self, conf_target: u16) -> Result<FeeEstimate, ChainError> {
+        let estimate = self
+            .client
+            .request::<SmartFeeEstimate>(
+                "estimatesmartfee",
+                Some(vec![RpcParam::Int(conf_target as i64)]),
+            )
+            .await?;
+        if let Some(errors) = &estimate.errors {
+            trace!(
+                "{} chain fee estimation returned errors: {:?}",
+                self.client.symbol(),
+                errors
+            );
+        }
+
+        Ok(FeeEstimate::try_from(estimate)?)
+    }
+
+    async fn get_transaction(&self, tx_id: &str) -> Result<Transaction, ChainError> {
+        let tx_hex = self
+            .client
+            .request::<String>(
+                "getrawtransaction",
+                Some(vec![RpcParam::Str(tx_id.to_string())]),
+            )
+            .await
+            .map_err(|err| Self::map_not_found(tx_id, err))?;
+
+        Ok(parse_transaction_hex(&self.client_type, &tx_hex)?)
+    }
+
+    async fn get_transactions(
+        &self,
+        tx_ids: &[String],
+    ) -> Result<Vec<Result<Transaction, ChainError>>, ChainError> {
+        let mut transactions = Vec::with_capacity(tx_ids.len());
+
+        for chunk in tx_ids.chunks(self.scan_chunk_size) {
+            let txs_hex = self
+                .client
+                .request_batch::<String>(
+                    "getrawtransaction",
+                    chunk
+                        .iter()
+                        .map(|tx_id| vec![RpcParam::Str(tx_id.clone())])
+                        .collect(),
+                )
+                .await?;
+
+            transactions.extend(
+                chunk
+                    .iter()
+                    .zip(txs_hex)
+                    .map(|(tx_id, tx_hex)| match tx_hex {
+                        Ok(tx_hex) => parse_transaction_hex(&self.client_type, &tx_hex)
+                            .map_err(ChainError::from),
+                        Err(err) => Err(Self::map_not_found(tx_id, err)),
+                    }),
+            );
+        }
+
+        Ok(transactions)
+    }
+
+    async fn get_tx_confirmations(&self, tx_id: &str) -> Result<u32, ChainError> {
+        let tx = self
+            .client
+            .request::<VerboseTransaction>(
+                "getrawtransaction",
+                Some(vec![RpcParam::Str(tx_id.to_string()), RpcParam::Int(1)]),
+            )
+            .await
+            .map_err(|err| Self::map_not_found(tx_id, err))?;
+
+        Ok(tx.confirmations.unwrap_or_default())
+    }
+
+    async fn watch_address(&self, address: &str, rescan: bool) -> Result<(), ChainError> {
+        if !self.network_info(false).await?.supports_descriptors() {
+            debug!(
+                "Importing {} address {} (rescan: {})",
+                self.client.symbol(),
+                address,
+                rescan
+            );
+            return self
+                .client
+                .request_null(
+                    "importaddress",
+                    Some(vec![
+                        RpcParam::Str(address.to_string()),
+                        RpcParam::Str("".to_string()),
+                        RpcParam::Json(serde_json::json!(rescan)),
+                    ]),
+                )
+                .await
+                .map_err(ChainError::from);
+        }
+
+        let descriptor = self
+            .client
+            .request::<DescriptorInfo>(
+                "getdescriptorinfo",
+                Some(vec![RpcParam::Str(format!("addr({address})"))]),
+            )
+            .await?
+            .descriptor;
+        debug!(
+            "Importing {} descriptor {} (rescan: {})",
+            self.client.symbol(),
+            descriptor,
+            rescan
+        );
+
+        // Rescans start at the given timestamp
+        let timestamp = if rescan {
+            serde_json::json!(0)
+        } else {
+            serde_json::json!("now")
+        };
+        let res = self
+            .client
+            .request::<Vec<ImportDescriptorResult>>(
+                "importdescriptors",
+                Some(vec![RpcParam::Json(serde_json::json!([{
+                    "desc": descriptor,
+                    "timestamp": timestamp,
+                }]))]),
+            )
+            .await?;
+
+        match res.into_iter().next() {
+            Some(ImportDescriptorResult { success: true, .. }) => Ok(()),
+            Some(ImportDescriptorResult {
+                error: Some(err), ..
+            }) => Err(ChainError::Rpc(err)),
+            _ => Err(ChainError::Other(format!(
+                "could not import address {address}"
+            ))),
+        }
+    }
+
+    async fn get_mempool_size(&self) -> Result<usize, ChainError> {
+        Ok(self.get_mempool_info().await?.size)
+    }
+
+    async fn get_mempool_info(&self) -> Result<MempoolInfo, ChainError> {
+        Ok(self.client.request("getmempoolinfo", None).await?)
+    }
+
+    async fn get_mempool_entry(&self, tx_id: &str) -> Result<MempoolEntry, ChainError> {
+        self.client
+            .request(
+                "getmempoolentry",
+                Some(vec![RpcParam::Str(tx_id.to_string())]),
+            )
+            .await
+            .map_err(|err| match Self::map_not_found(tx_id, err) {
+                ChainError::NotFound(tx_id) => ChainError::NotInMempool(tx_id),
+                err => err,
+            })
+    }
+
+    async fn is_outpoint_spent(&self, outpoint: &Outpoint) -> Result<SpentStatus, ChainError> {
+        // With the mempool included, outputs spent by mempool transactions are not returned
+        let tx_out = self
+            .client
+            .request_optional::<serde_json::Value>(
+                "gettxout",
+                Some(vec![
+                    RpcParam::Str(outpoint.tx_id()),
+                    RpcParam::Int(outpoint.vout as i64),
+                    RpcParam::Json(serde_json::Value::Bool(true)),
+                ]),
+            )
+            .await?;
+        if tx_out.is_some() {
+            return Ok(SpentStatus::Unspent);
+        }
+
+        let inputs = HashSet::from([outpoint.clone()]);
+        let spending_tx = self
+            .scan_mempool(&inputs, &HashSet::new())
+            .await?
+            .into_iter()
+            .find(|tx| tx.input_outpoints().contains(outpoint));
+
+        Ok(match spending_tx {
+            Some(tx) => SpentStatus::SpentInMempool(tx.txid()),
+            None => SpentStatus::SpentOnChain,
+        })
+    }
+
+    async fn is_initial_block_download(&self) -> Result<bool, ChainError> {
+        Ok(self.blockchain_info().await?.initial_block_download)
+    }
+
+    async fn sync_progress(&self) -> Result<f64, ChainError> {
+        Ok(self.blockchain_info().await?.verification_progress)
+    }
+
+    async fn get_block_count(&self) -> Result<u64, ChainError> {
+        Ok(self.client.request("getblockcount", None).await?)
+    }
+
+    async fn get_best_block_hash(&self) -> Result<String, ChainError> {
+        Ok(self.client.request("getbestblockhash", None).await?)
+    }
+
+    async fn get_block_header(&self, hash: &str) -> Result<BlockHeader, ChainError> {
+        Ok(self
+            .client
+            .request(
+                "getblockheader",
+                Some(vec![RpcParam::Str(hash.to_string())]),
+            )
+            .await?)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use crate::chain::chain_client::{ChainClient, MEMPOOL_FETCH_CHUNK_SIZE};
+    use crate::chain::reorg::{BlockHistory, ReorgEvent};
+    use crate::chain::types::{RawMempool, RpcParam, SpentStatus, Type};
+    use crate::chain::utils::{Block, Outpoint, Transaction, parse_transaction_hex};
+    use crate::chain::{BaseClient, ChainError, Client, Config};
+    use axum::extract::State;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use bitcoin::hashes::Hash;
+    use rstest::rstest;
+    use serial_test::serial;
+    use std::collections::HashSet;
+    use std::sync::{Arc, Mutex, OnceLock};
+    use std::time::Duration;
+
+    const PORT: u16 = 18_443;
+    const ADDRESS: &str = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+
+    pub fn get_client() -> ChainClient {
+        static CLIENT: OnceLock<ChainClient> = OnceLock::new();
+        CLIENT
+            .get_or_init(|| {
+                ChainClient::new(
+                    Type::Bitcoin,
+                    "BTC".to_string(),
+                    Config {
+                        host: "127.0.0.1".to_string(),
+                        port: PORT,
+                        cookie: None,
+                        user: Some("boltz".to_string()),
+                        password: Some("anoVB0m1KvX0SmpPxvaLVADg0UQVLQTEx3jCD3qtuRI".to_string()),
+                        ..Default::default()
+                    },
+                )
+                .unwrap()
+            })
+            .clone()
+    }
+
+    async fn generate_block(client: &ChainClient) {
+        client
+            .client
+            .request::<serde_json::Value>(
+                "generatetoaddress",
+                Some(vec![
+                    RpcParam::Int(1),
+                    RpcParam::Str(
+                        client
+                            .client
+                            .request::<String>("getnewaddress", None)
+                            .await
+                            .unwrap(),
+                    ),
+                ]),
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn send_transaction(client: &ChainClient) -> Transaction {
+        let tx_id = client
+            .client
+            .request::<String>(
+                "sendtoaddress",
+                Some(vec![
+                    RpcParam::Str(
+                        client
+                            .client
+                            .request::<String>("getnewaddress", None)
+                            .await
+                            .unwrap(),
+                    ),
+                    RpcParam::Float(0.21),
+                ]),
+            )
+            .await
+            .unwrap();
+
+        parse_transaction_hex(
+            &Type::Bitcoin,
+            &client
+                .client
+                .request::<String>("getrawtransaction", Some(vec![RpcParam::Str(tx_id)]))
+                .await
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    async fn create_funded_transaction(client: &ChainClient) -> String {
+        let address = client
+            .client
+            .request::<String>("getnewaddress", None)
+            .await
+            .unwrap();
+        let mut outputs = serde_json::Map::new();
+        outputs.insert(address, serde_json::json!(0.1));
+
+        let raw = client
+            .client
+            .request::<String>(
+                "createrawtransaction",
+                Some(vec![
+                    RpcParam::Json(serde_json::json!([])),
+                    RpcParam::Json(serde_json::Value::Object(outputs)),
+                ]),
+            )
+            .await
+            .unwrap();
+
+        let funded = client
+            .client
+            .request::<serde_json::Value>("fundrawtransaction", Some(vec![RpcParam::Str(raw)]))
+            .await
+            .unwrap();
+        funded["hex"].as_str().unwrap().to_string()
+    }
+
+    async fn sign_transaction(client: &ChainClient, tx_hex: String) -> String {
+        let signed = client
+            .client
+            .request::<serde_json::Value>(
+                "signrawtransactionwithwallet",
+                Some(vec![RpcParam::Str(tx_hex)]),
+            )
+            .await
+            .unwrap();
+        signed["hex"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_send_raw_transaction() {
+        let client = get_client();
+
+        let funded = create_funded_transaction(&client).await;
+        let signed = sign_transaction(&client, funded).await;
+
+        let tx_id = client.send_raw_transaction(&signed).await.unwrap();
+        let tx = parse_transaction_hex(&Type::Bitcoin, &signed).unwrap();
+        match tx {
+            Transaction::Bitcoin(tx) => assert_eq!(tx.compute_txid().to_string(), tx_id),
+            Transaction::Elements(_) => unreachable!(),
+        };
+
+        let mempool = client
+            .client
+            .request::<RawMempool>("getrawmempool", None)
+            .await
+            .unwrap();
+        assert!(mempool.contains(&tx_id));
+
+        generate_block(&client).await;
+
+        let wallet_tx = client
+            .client
+            .request::<serde_json::Value>("gettransaction", Some(vec![RpcParam::Str(tx_id)]))
+            .await
+            .unwrap();
+        assert_eq!(wallet_tx["confirmations"].as_u64().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_send_raw_transaction_rejected() {
+        let client = get_client();
+
+        // Not signing the transaction makes the node reject it
+        let funded = create_funded_transaction(&client).await;
+
+        let err = client.send_raw_transaction(&funded).await.err().unwrap();
+        match err {
+            ChainError::Rejected(reason) => assert!(!reason.is_empty()),
+            _ => panic!("unexpected error: {err}"),
+        };
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_mempool_accept_allowed() {
+        let client = get_client();
+
+        let funded = create_funded_transaction(&client).await;
+        let signed = sign_transaction(&client, funded).await;
+
+        let result = client.test_mempool_accept(&signed).await.unwrap();
+        assert!(result.allowed);
+        assert_eq!(result.reject_reason, None);
+        assert!(result.vsize.unwrap() > 0);
+        assert!(result.fees.unwrap().base > 0.0);
+
+        // Nothing was broadcast
+        let mempool = client
+            .client
+            .request::<RawMempool>("getrawmempool", None)
+            .await
+            .unwrap();
+        let tx_id = match parse_transaction_hex(&Type::Bitcoin, &signed).unwrap() {
+            Transaction::Bitcoin(tx) => tx.compute_txid().to_string(),
+            Transaction::Elements(_) => unreachable!(),
+        };
+        assert!(!mempool.contains(&tx_id));
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_mempool_accept_rejected() {
+        let client = get_client();
+
+        let funded = create_funded_transaction(&client).await;
+
+        let result = client.test_mempool_accept(&funded).await.unwrap();
+        assert!(!result.allowed);
+        assert!(!result.reject_reason.unwrap().is_empty());
+        assert_eq!(result.fees, None);
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_send_raw_transaction_invalid_hex() {
+        let client = get_client();
+
+        let err = client.send_raw_transaction("not hex").await.err().unwrap();
+        assert!(matches!(err, ChainError::Rpc(_)));
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_estimate_fee() {
+        let client = get_client();
+
+        // The fee estimator needs to see transactions confirm over multiple blocks
+        for _ in 0..20 {
+            for _ in 0..5 {
+                send_transaction(&client).await;
+            }
+            generate_block(&client).await;
+        }
+
+        let estimate = client.estimate_fee(2).await.unwrap();
+        assert!(estimate.sat_per_vbyte > 0.0);
+        assert!(estimate.blocks >= 2);
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_get_transaction() {
+        let client = get_client();
+        let tx = send_transaction(&client).await;
+
+        let tx_id = match &tx {
+            Transaction::Bitcoin(tx) => tx.compute_txid().to_string(),
+            Transaction::Elements(_) => unreachable!(),
+        };
+
+        let fetched = client.get_transaction(&tx_id).await.unwrap();
+        assert_eq!(fetched, tx);
+        assert_eq!(fetched.input_outpoints(), tx.input_outpoints());
+        assert_eq!(fetched.output_script_pubkeys(), tx.output_script_pubkeys());
+
+        generate_block(&client).await;
+    }
+
+    #[tokio::test]
+    async fn test_get_transaction_not_found() {
+        let client = get_client();
+
+        let tx_id = "00".repeat(32);
+        let err = client.get_transaction(&tx_id).await.err().unwrap();
+        assert_eq!(err, ChainError::NotFound(tx_id));
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_get_transactions() {
+        let client = get_client().with_scan_config(1, 2).unwrap();
+
+        let mut txs = Vec::new();
+        for _ in 0..3 {
+            txs.push(send_transaction(&client).await);
+        }
+        let tx_ids = txs
+            .iter()
+            .map(|tx| match tx {
+                Transaction::Bitcoin(tx) => tx.compute_txid().to_string(),
+                Transaction::Elements(_) => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        let missing = "00".repeat(32);
+        let res = client
+            .get_transactions(&[
+                tx_ids[0].clone(),
+                missing.clone(),
+                tx_ids[1].clone(),
+                "not a transaction id".to_string(),
+                tx_ids[2].clone(),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(res.len(), 5);
+
+        assert_eq!(res[0].as_ref().unwrap(), &txs[0]);
+        assert_eq!(
+            res[1].as_ref().err().unwrap(),
+            &ChainError::NotFound(missing)
+        );
+        assert_eq!(res[2].as_ref().unwrap(), &txs[1]);
+        assert!(matches!(res[3].as_ref().err().unwrap(), ChainError::Rpc(_)));
+        assert_eq!(res[4].as_ref().unwrap(), &txs[2]);
+
+        generate_block(&client).await;
+    }
+
+    #[tokio::test]
+    async fn test_get_transactions_empty() {
+        let client = get_client();
+        assert!(client.get_transactions(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_get_tx_confirmations() {
+        let client = get_client();
+        let tx = send_transaction(&client).await;
+
+        let tx_id = match &tx {
+            Transaction::Bitcoin(tx) => tx.compute_txid().to_string(),
+            Transaction::Elements(_) => unreachable!(),
+        };
+
+        assert_eq!(client.get_tx_confirmations(&tx_id).await.unwrap(), 0);
+
+        generate_block(&client).await;
+        assert!(client.get_tx_confirmations(&tx_id).await.unwrap() >= 1);
+
+        generate_block(&client).await;
+        assert_eq!(client.get_tx_confirmations(&tx_id).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_tx_confirmations_not_found() {
+        let client = get_client();
+
+        let tx_id = "00".repeat(32);
+        let err = client.get_tx_confirmations(&tx_id).await.err().unwrap();
+        assert_eq!(err, ChainError::NotFound(tx_id));
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_get_mempool_info() {
+        let client = get_client();
+        send_transaction(&client).await;
+
+        let size = client.get_mempool_size().await.unwrap();
+        assert!(size >= 1);
+
+        let info = client.get_mempool_info().await.unwrap();
+        assert_eq!(info.size, size);
+        assert!(info.bytes > 0);
+
+        generate_block(&client).await;
+        assert_eq!(client.get_mempool_size().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_get_mempool_entry() {
+        let client = get_client();
+        let tx = send_transaction(&client).await;
+        let tx_id = tx.txid();
+
+        let entry = client.get_mempool_entry(&tx_id).await.unwrap();
+        assert!(entry.vsize > 0);
+        assert!(entry.fees.base > 0.0);
+        assert!(entry.fees.ancestor >= entry.fees.base);
+        assert!(entry.fees.descendant >= entry.fees.base);
+        assert!(entry.ancestor_count >= 1);
+        assert!(entry.ancestor_size >= entry.vsize);
+        assert_eq!(entry.descendant_count, 1);
+        assert_eq!(entry.descendant_size, entry.vsize);
+
+        generate_block(&client).await;
+        assert_eq!(
+            client.get_mempool_entry(&tx_id).await.err().unwrap(),
+            ChainError::NotInMempool(tx_id)
+        );
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_get_block_count() {
+        let client = get_client();
+
+        let count = client.get_block_count().await.unwrap();
+        generate_block(&client).await;
+        assert_eq!(client.get_block_count().await.unwrap(), count + 1);
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_get_best_block_hash() {
+        let client = get_client();
+
+        let previous = client.get_best_block_hash().await.unwrap();
+        generate_block(&client).await;
+
+        let best = client.get_best_block_hash().await.unwrap();
+        assert_ne!(best, previous);
+
+        let header = client.get_block_header(&best).await.unwrap();
+        assert_eq!(header.hash, best);
+        assert_eq!(header.height, client.get_block_count().await.unwrap());
+        assert_eq!(header.previous_block_hash, Some(previous));
+    }
+
+    #[tokio::test]
+    async fn test_get_block_header_genesis() {
+        let client = get_client();
+
+        let genesis = client
+            .client
+            .request::<String>("getblockhash", Some(vec![RpcParam::Int(0)]))
+            .await
+            .unwrap();
+
+        let header = client.get_block_header(&genesis).await.unwrap();
+        assert_eq!(header.height, 0);
+        assert_eq!(header.previous_block_hash, None);
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_subscribe_raw_tx() {
+        let client = get_client();
+        let mut receiver = client.subscribe_raw_tx().await.unwrap();
+
+        // Give the socket some time to connect
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let tx = send_transaction(&client).await;
+
+        let received = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let received = receiver.recv().await.unwrap();
+                if received == tx {
+                    return received;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(received, tx);
+
+        generate_block(&client).await;
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn test_subscribe_raw_block() {
+        let client = get_client();
+        let mut receiver = client.subscribe_raw_block().await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        generate_block(&client).await;
+        let best_block = client.get_best_block_hash().await.unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let block = receiver.recv().await.unwrap();
+                if block.hash() == best_block {
+                    return block;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert!(matches!(received, Block::Bitcoin(_)));
+    }
+
+    #[rstest]
+    #[case(0, 64, "mempool scan workers have to be at least 1")]
+    #[case(16, 0, "mempool fetch chunk size has to be at least 1")]
+    fn test_with_scan_config_invalid(
+        #[case] workers: usize,
+        #[case] chunk_size: usize,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            get_client()
+                .with_scan_config(workers, chunk_size)
+                .err()
+                .unwrap()
+                .to_string(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_with_scan_config_from_config() {
+        let client = ChainClient::new(
+            Type::Bitcoin,
+            "BTC".to_string(),
+            Config {
+                user: Some("boltz".to_string()),
+                password: Some("boltz".to_string()),
+                mempool_scan_workers: Some(2),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(client.scan_workers, 2);
+        assert_eq!(client.scan_chunk_size, MEMPOOL_FETCH_CHUNK_SIZE);
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn scan_mempool_single_worker() {
+        let client = get_client().with_scan_config(1, 1).unwrap();
+
+        let txs = vec![
+            send_transaction(&client).await,
+            send_transaction(&client).await,
+            send_transaction(&client).await,
+        ];
+
+        let outputs = txs
+            .iter()
+            .map(|tx| tx.output_script_pubkeys()[0].clone())
+            .collect::<HashSet<_>>();
+
+        let transactions = client
+            .scan_mempool(&HashSet::new(), &outputs)
+            .await
+            .unwrap();
+        assert_eq!(transactions.len(), txs.len());
+        for tx in txs {
+            assert!(transactions.contains(&tx));
+        }
+
+        generate_block(&client).await;
+    }
+
+    #[tokio::test]
+    async fn test_connect() {
+        let mut client = get_client();
+
+        client.connect().await.unwrap();
+
+        assert_ne!(
+            client.network_info(true).await.unwrap().subversion,
+            "".to_string()
+        );
+    }
+
+    type Requests = Arc<Mutex<Vec<(String, serde_json::Value)>>>;
+
+    // Answers every request with the result returned by the closure
+    async fn start_mock_node<F>(config: Config, respond: F) -> (ChainClient, Requests)
+    where
+        F: Fn(&str, &serde_json::Value) -> serde_json::Value + Clone + Send + Sync + 'static,
+    {
+        let requests = Requests::default();
+        let router = Router::new()
+            .route(
+                "/",
+                post(
+                    move |State(requests): State<Requests>, Json(body): Json<serde_json::Value>| {
+                        let respond = respond.clone();
+                        async move {
+                            let method = body["method"].as_str().unwrap().to_string();
+                            let params = body["params"].clone();
+                            let result = respond(&method, &params);
+                            requests.lock().unwrap().push((method, params));
+
+                            serde_json::json!({ "result": result, "error": null }).to_string()
+                        }
+                    },
+                ),
+            )
+            .with_state(requests.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        let client = ChainClient::new(
+            Type::Bitcoin,
+            "BTC".to_string(),
+            Config {
+                host: "127.0.0.1".to_string(),
+                port,
+                user: Some("boltz".to_string()),
+                password: Some("boltz".to_string()),
+                ..config
+            },
+        )
+        .unwrap();
+
+        (client, requests)
+    }
+
+    fn methods(requests: &Requests) -> Vec<String> {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(method, _)| method.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_network_info_cached() {
+        let (client, requests) = start_mock_node(
+            Config {
+                network_info_ttl: Some(Duration::from_millis(100)),
+                ..Default::default()
+            },
+            |_, _| serde_json::json!({ "subversion": "/Satoshi:28.0.0/" }),
+        )
+        .await;
+
+        let info = client.network_info(false).await.unwrap();
+        assert_eq!(info.subversion, "/Satoshi:28.0.0/");
+        client.network_info(false).await.unwrap();
+        assert_eq!(methods(&requests), vec!["getnetworkinfo"]);
+
+        client.network_info(true).await.unwrap();
+        assert_eq!(methods(&requests).len(), 2);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        client.network_info(false).await.unwrap();
+        assert_eq!(methods(&requests).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_rpc_stats() {
+        let (client, _) = start_mock_node(
+            Config::default(),
+            |_, _| serde_json::json!({ "subversion": "/Satoshi:28.0.0/" }),
+        )
+        .await;
+        assert!(client.rpc_stats().is_empty());
+
+        client.network_info(true).await.unwrap();
+        client.network_info(true).await.unwrap();
+
+        let stats = client.rpc_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats["getnetworkinfo"].count, 2);
+        assert_eq!(stats["getnetworkinfo"].error_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_watch_reorgs() {
+        let chain = Arc::new(Mutex::new(
+            (0..=5)
+                .map(|height| format!("a{height}"))
+                .collect::<Vec<_>>(),
+        ));
+
+        let chain_cp = chain.clone();
+        let (client, _) = start_mock_node(
+            Config {
+                reorg_poll_interval: Some(Duration::from_millis(10)),
+                ..Default::default()
+            },
+            move |method, params| {
+                let chain = chain_cp.lock().unwrap();
+                match method {
+                    "getbestblockhash" => serde_json::json!(chain.last().unwrap()),
+                    "getblockheader" => {
+                        let hash = params[0].as_str().unwrap();
+                        let height = chain.iter().position(|block| block == hash).unwrap();
+                        let previous = height
+                            .checked_sub(1)
+                            .map(|previous| chain[previous].clone());
+                        serde_json::json!({
+                            "hash": hash,
+                            "height": height,
+                            "previousblockhash": previous,
+                        })
+                    }
+                    _ => unreachable!(),
+                }
+            },
+        )
+        .await;
+
+        let mut reorgs = client.watch_reorgs().await.unwrap();
+
+        // Extending the chain is no reorg
+        chain.lock().unwrap().push("a6".to_string());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(reorgs.try_recv().is_err());
+
+        // A competing chain replaces everything after height 3
+        {
+            let mut chain = chain.lock().unwrap();
+            chain.truncate(4);
+            chain.extend((4..=7).map(|height| format!("b{height}")));
+        }
+
+        let event = tokio::time::timeout(Duration::from_secs(5), reorgs.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            event,
+            ReorgEvent {
+                from_height: 6,
+                common_ancestor: 3,
+                deep: false,
+            }
+        );
+    }
+
+    #[rstest]
+    #[case::extended(5, None)]
+    #[case::fork_within_history(3, Some(ReorgEvent { from_height: 5, common_ancestor: 3, deep: false }))]
+    #[case::fork_below_history(1, Some(ReorgEvent { from_height: 5, common_ancestor: 2, deep: true }))]
+    #[tokio::test]
+    async fn test_poll_reorg_far_ahead(#[case] fork: usize, #[case] expected: Option<ReorgEvent>) {
+        let chain = (0..=12)
+            .map(|height| {
+                if height <= fork {
+                    format!("a{height}")
+                } else {
+                    format!("b{height}")
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let (client, _) = start_mock_node(Config::default(), move |method, params| match method {
+            "getbestblockhash" => serde_json::json!(chain.last().unwrap()),
+            "getblockheader" => {
+                let hash = params[0].as_str().unwrap();
+                let height = chain.iter().position(|block| block == hash).unwrap();
+                serde_json::json!({
+                    "hash": hash,
+                    "height": height,
+                    "previousblockhash": chain[height - 1],
+                })
+            }
+            "getblockhash" => serde_json::json!(chain[params[0].as_u64().unwrap() as usize]),
+            _ => unreachable!(),
+        })
+        .await;
+
+        let mut history = BlockHistory::new(3);
+        history.connect(
+            (3..=5)
+                .map(|height| (height, format!("a{height}")))
+                .collect(),
+        );
+
+        assert_eq!(client.poll_reorg(&mut history).await.unwrap(), expected);
+        assert_eq!(history.lowest_height(), Some(12));
+    }
+
+    #[test]
+    fn test_reorg_depth_invalid() {
+        let res = ChainClient::new(
+            Type::Bitcoin,
+            "BTC".to_string(),
+            Config {
+                user: Some("boltz".to_string()),
+                password: Some("boltz".to_string()),
+                reorg_depth: Some(0),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            res.err().unwrap().to_string(),
+            "reorg depth has to be at least 1"
+        );
+    }
+
+    #[test]
+    fn test_descriptor_gap_limit_invalid() {
+        let res = ChainClient::new(
+            Type::Bitcoin,
+            "BTC".to_string(),
+            Config {
+                user: Some("boltz".to_string()),
+                password: Some("boltz".to_string()),
+                descriptor_gap_limit: Some(0),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            res.err().unwrap().to_string(),
+            "gap limit has to be at least 1"
+        );
+    }
+
+    #[rstest]
+    #[case(false, serde_json::json!("now"))]
+    #[case(true, serde_json::json!(0))]
+    #[tokio::test]
+    async fn test_watch_address_descriptors(
+        #[case] rescan: bool,
+        #[case] timestamp: serde_json::Value,
+    ) {
+        let (client, requests) =
+            start_mock_node(Config::default(), |method, params| match method {
+                "getnetworkinfo" => serde_json::json!({ "subversion": "/Satoshi:28.0.0/" }),
+                "getdescriptorinfo" => serde_json::json!({
+                    "descriptor": format!("{}#checksum", params[0].as_str().unwrap()),
+                }),
+                "importdescriptors" => serde_json::json!([{ "success": true }]),
+                _ => unreachable!(),
+            })
+            .await;
+
+        client.watch_address(ADDRESS, rescan).await.unwrap();
+
+        assert_eq!(
+            methods(&requests),
+            vec!["getnetworkinfo", "getdescriptorinfo", "importdescriptors"]
+        );
+        assert_eq!(
+            requests.lock().unwrap()[2].1,
+            serde_json::json!([[{
+                "desc": format!("addr({ADDRESS})#checksum"),
+                "timestamp": timestamp,
+            }]])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_address_descriptors_error() {
+        let (client, _) = start_mock_node(Config::default(), |method, _| match method {
+            "getnetworkinfo" => serde_json::json!({ "subversion": "/Satoshi:28.0.0/" }),
+            "getdescriptorinfo" => serde_json::json!({ "descriptor": "addr()#checksum" }),
+            "importdescriptors" => serde_json::json!([{
+                "success": false,
+                "error": { "code": -4, "message": "Cannot import descriptor without private keys to a wallet with private keys enabled" },
+            }]),
+            _ => unreachable!(),
+        })
+        .await;
+
+        assert_eq!(
+            client
+                .watch_address(ADDRESS, false)
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "Cannot import descriptor without private keys to a wallet with private keys enabled"
+        );
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    #[tokio::test]
+    async fn test_watch_address_legacy(#[case] rescan: bool) {
+        let (client, requests) = start_mock_node(Config::default(), |method, _| match method {
+            "getnetworkinfo" => serde_json::json!({ "subversion": "/Satoshi:0.20.1/" }),
+            "importaddress" => serde_json::Value::Null,
+            _ => unreachable!(),
+        })
+        .await;
+
+        client.watch_address(ADDRESS, rescan).await.unwrap();
+
+        assert_eq!(methods(&requests), vec!["getnetworkinfo", "importaddress"]);
+        assert_eq!(
+            requests.lock().unwrap()[1].1,
+            serde_json::json!([ADDRESS, "", rescan])
+        );
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn scan_mempool_empty() {
+        let client = get_client();
+
+        generate_block(&client).await;
+
+        let mempool = client
+            .client
+            .request::<RawMempool>("getrawmempool", None)
+            .await
+            .unwrap();
+        assert!(mempool.is_empty());
+
+        let transactions = client
+            .scan_mempool(&HashSet::new(), &HashSet::new())
+            .await
+            .unwrap();
+        assert_eq!(transactions.len(), 0);
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn is_initial_block_download() {
+        let client = get_client();
+        generate_block(&client).await;
+
+        assert!(!client.is_initial_block_download().await.unwrap());
+
+        let info = client.blockchain_info().await.unwrap();
+        assert_eq!(info.chain, "regtest");
+        assert_eq!(info.blocks, info.headers);
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn sync_progress() {
+        let client = get_client();
+        generate_block(&client).await;
+
+        let progress = client.sync_progress().await.unwrap();
+        assert!(progress > 0.0 && progress <= 1.0);
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn is_outpoint_spent_unspent() {
+        let client = get_client();
+        let tx = send_transaction(&client).await;
+        let outpoint = Outpoint {
+            hash: match &tx {
+                Transaction::Bitcoin(tx) => tx.compute_txid().to_byte_array().to_vec(),
+                Transaction::Elements(_) => unreachable!(),
+            },
+            vout: 0,
+        };
+
+        // Unconfirmed outputs count as unspent
+        assert_eq!(
+            client.is_outpoint_spent(&outpoint).await.unwrap(),
+            SpentStatus::Unspent
+        );
+
+        generate_block(&client).await;
+        assert_eq!(
+            client.is_outpoint_spent(&outpoint).await.unwrap(),
+            SpentStatus::Unspent
+        );
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn is_outpoint_spent_in_mempool() {
+        let client = get_client();
+        let tx = send_transaction(&client).await;
+        let outpoint = tx.input_outpoints()[0].clone();
+
+        assert_eq!(
+            client.is_outpoint_spent(&outpoint).await.unwrap(),
+            SpentStatus::SpentInMempool(tx.txid())
+        );
+
+        generate_block(&client).await;
+        assert_eq!(
+            client.is_outpoint_spent(&outpoint).await.unwrap(),
+            SpentStatus::SpentOnChain
+        );
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn scan_mempool_relevant_input() {
+        let client = get_client();
+        let tx = send_transaction(&client).await;
+
+        let mut inputs = HashSet::new();
+        inputs.insert(tx.input_outpoints()[0].clone());
+
+        let transactions = client.scan_mempool(&inputs, &HashSet::new()).await.unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0], tx);
+
+        generate_block(&client).await;
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn scan_mempool_relevant_output() {
+        let client = get_client();
+        let tx = send_transaction(&client).await;
+
+        let mut outputs = HashSet::new();
+        outputs.insert(tx.output_script_pubkeys()[0].clone());
+
+        let transactions = client
+            .scan_mempool(&HashSet::new(), &outputs)
+            .await
+            .unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0], tx);
+
+        generate_block(&client).await;
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn scan_mempool_raw() {
+        let client = get_client();
+        let tx = send_transaction(&client).await;
+
+        let tx_id = match &tx {
+            Transaction::Bitcoin(tx) => tx.compute_txid().to_string(),
+            Transaction::Elements(_) => unreachable!(),
+        };
+        let node_hex = client
+            .client
+            .request::<String>("getrawtransaction", Some(vec![RpcParam::Str(tx_id)]))
+            .await
+            .unwrap();
+
+        let mut outputs = HashSet::new();
+        outputs.insert(tx.output_script_pubkeys()[0].clone());
+
+        let transactions = client
+            .scan_mempool_raw(&HashSet::new(), &outputs)
+            .await
+            .unwrap();
+        assert_eq!(transactions.len(), 1);
+
+        let (scanned, tx_hex) = &transactions[0];
+        assert_eq!(scanned, &tx);
+        assert_eq!(tx_hex, &node_hex);
+        assert_eq!(
+            &parse_transaction_hex(&Type::Bitcoin, tx_hex).unwrap(),
+            scanned
+        );
+
+        generate_block(&client).await;
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn scan_mempool_incremental() {
+        let client = get_client();
+        generate_block(&client).await;
+
+        let first = send_transaction(&client).await;
+        let mut outputs = HashSet::new();
+        outputs.insert(first.output_script_pubkeys()[0].clone());
+
+        assert_eq!(
+            client
+                .scan_mempool_incremental(&HashSet::new(), &outputs)
+                .await
+                .unwrap(),
+            vec![first.clone()]
+        );
+        assert_eq!(client.mempool_seen.lock().await.transactions.len(), 1);
+
+        let second = send_transaction(&client).await;
+        outputs.insert(second.output_script_pubkeys()[0].clone());
+
+        // The first transaction is still relevant, but was returned already
+        assert_eq!(
+            client
+                .scan_mempool_incremental(&HashSet::new(), &outputs)
+                .await
+                .unwrap(),
+            vec![second.clone()]
+        );
+        assert_eq!(
+            client
+                .mempool_seen
+                .lock()
+                .await
+                .transactions
+                .keys()
+                .cloned()
+                .collect::<HashSet<_>>(),
+            HashSet::from([first.txid(), second.txid()])
+        );
+
+        // Transactions that were seen already are relevant to filters added later
+        let third = send_transaction(&client).await;
+        assert!(
+            client
+                .scan_mempool_incremental(&HashSet::new(), &outputs)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        outputs.insert(third.output_script_pubkeys()[0].clone());
+        assert_eq!(
+            client
+                .scan_mempool_incremental(&HashSet::new(), &outputs)
+                .await
+                .unwrap(),
+            vec![third.clone()]
+        );
+
+        assert!(
+            client
+                .scan_mempool_incremental(&HashSet::new(), &outputs)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Confirmed transactions are pruned
+        generate_block(&client).await;
+        assert!(
+            client
+                .scan_mempool_incremental(&HashSet::new(), &outputs)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(client.mempool_seen.lock().await.transactions.is_empty());
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn scan_mempool_detailed() {
+        let client = get_client();
+        let tx = send_transaction(&client).await;
+
+        // The payment of 0.21 BTC made by send_transaction
+        let payment = tx
+            .outputs()
+            .into_iter()
+            .find(|output| output.value_sat == Some(21_000_000))
+            .unwrap();
+
+        let mut outputs = HashSet::new();
+        outputs.insert(payment.script_pubkey.clone());
+
+        let inputs = tx.input_outpoints().into_iter().collect::<HashSet<_>>();
+
+        let transactions = client
+            .scan_mempool_detailed(&inputs, &outputs)
+            .await
+            .unwrap();
+        assert_eq!(transactions.len(), 1);
+
+        let relevant = &transactions[0];
+        assert_eq!(relevant.transaction, tx);
+        assert_eq!(relevant.inputs, tx.input_outpoints());
+        assert_eq!(relevant.outputs.len(), 1);
+        assert_eq!(relevant.outputs[0].vout, payment.vout);
+        assert_eq!(relevant.outputs[0].value_sat, Some(21_000_000));
+        assert_eq!(relevant.outputs[0].script_pubkey, payment.script_pubkey);
+
+        generate_block(&client).await;
+    }
+
+    #[tokio::test]
     #[serial(BTC)]
-    async fn scan_mempool_relevant_output() {
+    async fn scan_mempool_stream() {
         let client = get_client();
         let tx = send_transaction(&client).await;
+        for _ in 0..10 {
+            send_transaction(&client).await;
+        }
 
         let mut outputs = HashSet::new();
         outputs.insert(tx.output_script_pubkeys()[0].clone());
 
-        let transactions = client
-            .scan_mempool(&HashSet::new(), &outputs)
+        let mut stream = client
+            .scan_mempool_stream(HashSet::new(), outputs)
             .await
             .unwrap();
-        assert_eq!(transactions.len(), 1);
-        assert_eq!(transactions[0], tx);
 
+        // The relevant transaction is yielded before the stream is exhausted
+        assert_eq!(stream.recv().await.unwrap(), tx);
+        assert!(stream.recv().await.is_none());
+
+        generate_block(&client).await;
+    }
+
+    #[tokio::test]
+    #[serial(BTC)]
+    async fn scan_mempool_stream_empty() {
+        let client = get_client();
         generate_block(&client).await;
+
+        let mut stream = client
+            .scan_mempool_stream(HashSet::new(), HashSet::new())
+            .await
+            .unwrap();
+        assert!(stream.recv().await.is_none());
     }
 
     #[tokio::test]
@@ -374,4 +2473,920 @@ pub mod test {
 
         generate_block(&client).await;
     }
+
+    mod transport {
+        use super::*;
+        use crate::chain::rpc_client::RpcTransport;
+        use crate::chain::types::RpcError;
+        use crate::chain::utils::{DescriptorMatch, Outpoint};
+        use crate::wallet::OutputDescriptor;
+        use alloy::hex;
+        use async_trait::async_trait;
+        use bitcoin::hashes::Hash;
+        use serde::de::DeserializeOwned;
+        use std::collections::HashMap;
+        use std::str::FromStr;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Debug, Clone, Default)]
+        struct MockTransport {
+            mempool: RawMempool,
+            // Hex of the transactions by id
+            transactions: HashMap<String, String>,
+            requests: Arc<Mutex<Vec<String>>>,
+            // Ids of the transactions in the order in which they were requested
+            fetched: Arc<Mutex<Vec<String>>>,
+            // Virtual size and fee in BTC of the verbose mempool entries
+            fees: HashMap<String, (u64, f64)>,
+            verbose_fails: bool,
+            batch_delay: Duration,
+            in_flight: Arc<AtomicUsize>,
+            max_in_flight: Arc<AtomicUsize>,
+            // The node refuses connections until then
+            down_until: Option<Instant>,
+        }
+
+        impl MockTransport {
+            fn new(transactions: &[bitcoin::Transaction], evicted: &[String]) -> Self {
+                let mut mempool = transactions
+                    .iter()
+                    .map(|tx| tx.compute_txid().to_string())
+                    .collect::<RawMempool>();
+                mempool.extend(evicted.iter().cloned());
+
+                Self {
+                    mempool,
+                    transactions: transactions
+                        .iter()
+                        .map(|tx| {
+                            (
+                                tx.compute_txid().to_string(),
+                                bitcoin::consensus::encode::serialize_hex(tx),
+                            )
+                        })
+                        .collect(),
+                    requests: Default::default(),
+                    fetched: Default::default(),
+                    fees: HashMap::new(),
+                    verbose_fails: false,
+                    batch_delay: Duration::ZERO,
+                    in_flight: Default::default(),
+                    max_in_flight: Default::default(),
+                    down_until: None,
+                }
+            }
+
+            fn requests(&self, method: &str) -> usize {
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|request| *request == method)
+                    .count()
+            }
+        }
+
+        #[async_trait]
+        impl RpcTransport for MockTransport {
+            fn symbol(&self) -> &str {
+                "BTC"
+            }
+
+            async fn request<T: DeserializeOwned + Send>(
+                &self,
+                method: &str,
+                params: Option<Vec<RpcParam>>,
+            ) -> anyhow::Result<T> {
+                self.requests.lock().unwrap().push(method.to_string());
+                if self
+                    .down_until
+                    .is_some_and(|down_until| Instant::now() < down_until)
+                {
+                    return Err(anyhow::anyhow!("Connection refused"));
+                }
+
+                let verbose = matches!(
+                    params.as_deref(),
+                    Some([RpcParam::Json(serde_json::Value::Bool(true))])
+                );
+                match method {
+                    "getrawmempool" if verbose => {
+                        if self.verbose_fails {
+                            return Err(anyhow::anyhow!("Work queue depth exceeded"));
+                        }
+
+                        let entries = self
+                            .mempool
+                            .iter()
+                            .map(|tx_id| {
+                                let (vsize, fee) =
+                                    self.fees.get(tx_id).copied().unwrap_or((100, 0.0));
+                                (
+                                    tx_id.clone(),
+                                    serde_json::json!({
+                                        "vsize": vsize,
+                                        "weight": vsize * 4,
+                                        "fees": { "base": fee, "ancestor": fee, "descendant": fee },
+                                        "depends": [],
+                                        "spentby": [],
+                                    }),
+                                )
+                            })
+                            .collect::<serde_json::Map<_, _>>();
+                        Ok(serde_json::from_value(serde_json::Value::Object(entries))?)
+                    }
+                    "getrawmempool" => Ok(serde_json::from_value(serde_json::json!(self.mempool))?),
+                    "getnetworkinfo" => Ok(serde_json::from_value(
+                        serde_json::json!({ "subversion": "/Satoshi:28.0.0/" }),
+                    )?),
+                    _ => Err(anyhow::anyhow!("unexpected method {method}")),
+                }
+            }
+
+            async fn request_null(
+                &self,
+                method: &str,
+                _params: Option<Vec<RpcParam>>,
+            ) -> anyhow::Result<()> {
+                Err(anyhow::anyhow!("unexpected method {method}"))
+            }
+
+            async fn request_optional<T: DeserializeOwned + Send>(
+                &self,
+                method: &str,
+                _params: Option<Vec<RpcParam>>,
+            ) -> anyhow::Result<Option<T>> {
+                Err(anyhow::anyhow!("unexpected method {method}"))
+            }
+
+            async fn request_batch<T: DeserializeOwned + Send>(
+                &self,
+                method: &str,
+                params: Vec<Vec<RpcParam>>,
+            ) -> anyhow::Result<Vec<anyhow::Result<T>>> {
+                self.requests.lock().unwrap().push(method.to_string());
+                if method != "getrawtransaction" {
+                    return Err(anyhow::anyhow!("unexpected method {method}"));
+                }
+
+                let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+                tokio::time::sleep(self.batch_delay).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                Ok(params
+                    .into_iter()
+                    .map(|params| match params.first() {
+                        Some(RpcParam::Str(tx_id)) => {
+                            self.fetched.lock().unwrap().push(tx_id.clone());
+                            match self.transactions.get(tx_id) {
+                                Some(tx_hex) => serde_json::from_value(serde_json::json!(tx_hex))
+                                    .map_err(anyhow::Error::from),
+                                None => Err(RpcError {
+                                    code: -5,
+                                    message: "No such mempool or blockchain transaction"
+                                        .to_string(),
+                                }
+                                .into()),
+                            }
+                        }
+                        _ => Err(anyhow::anyhow!("invalid params")),
+                    })
+                    .collect())
+            }
+        }
+
+        fn transaction(seed: u8) -> bitcoin::Transaction {
+            bitcoin::Transaction {
+                version: bitcoin::transaction::Version::TWO,
+                lock_time: bitcoin::absolute::LockTime::ZERO,
+                input: vec![bitcoin::TxIn {
+                    previous_output: bitcoin::OutPoint {
+                        txid: bitcoin::Txid::from_byte_array([seed; 32]),
+                        vout: seed as u32,
+                    },
+                    ..Default::default()
+                }],
+                output: vec![bitcoin::TxOut {
+                    value: bitcoin::Amount::from_sat(10_000),
+                    script_pubkey: bitcoin::ScriptBuf::from_bytes(
+                        [vec![0x00, 0x14], vec![seed; 20]].concat(),
+                    ),
+                }],
+            }
+        }
+
+        fn client(transport: MockTransport) -> ChainClient<MockTransport> {
+            ChainClient::with_transport(Type::Bitcoin, transport, Config::default()).unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_relevant() {
+            let transactions = (1..=4).map(transaction).collect::<Vec<_>>();
+            let client = client(MockTransport::new(&transactions, &[]));
+
+            let mut inputs = HashSet::new();
+            inputs.insert(Outpoint {
+                hash: vec![1; 32],
+                vout: 1,
+            });
+            let mut outputs = HashSet::new();
+            outputs.insert(transactions[2].output[0].script_pubkey.to_bytes());
+
+            let mut found = client
+                .scan_mempool(&inputs, &outputs)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|tx| tx.txid())
+                .collect::<Vec<_>>();
+            found.sort();
+
+            let mut expected = vec![
+                transactions[0].compute_txid().to_string(),
+                transactions[2].compute_txid().to_string(),
+            ];
+            expected.sort();
+            assert_eq!(found, expected);
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_evicted() {
+            let transactions = vec![transaction(1)];
+            let client = client(MockTransport::new(&transactions, &["00".repeat(32)]));
+
+            let mut outputs = HashSet::new();
+            outputs.insert(transactions[0].output[0].script_pubkey.to_bytes());
+
+            let found = client
+                .scan_mempool(&HashSet::new(), &outputs)
+                .await
+                .unwrap();
+            assert_eq!(found, vec![Transaction::Bitcoin(transactions[0].clone())]);
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_empty() {
+            let transport = MockTransport::default();
+            let client = client(transport.clone());
+
+            let found = client
+                .scan_mempool(&HashSet::new(), &HashSet::new())
+                .await
+                .unwrap();
+            assert!(found.is_empty());
+            assert_eq!(transport.requests("getrawmempool"), 1);
+            assert_eq!(transport.requests("getrawtransaction"), 0);
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_multi() {
+            let transactions = (1..=4).map(transaction).collect::<Vec<_>>();
+            let transport = MockTransport::new(&transactions, &[]);
+            let client = client(transport.clone());
+
+            let output = |index: usize| transactions[index].output[0].script_pubkey.to_bytes();
+            let groups = vec![
+                (
+                    "first".to_string(),
+                    HashSet::new(),
+                    HashSet::from([output(0), output(3)]),
+                ),
+                (
+                    "second".to_string(),
+                    HashSet::new(),
+                    HashSet::from([output(1), output(3)]),
+                ),
+                ("none".to_string(), HashSet::new(), HashSet::new()),
+            ];
+
+            let found = client.scan_mempool_multi(&groups).await.unwrap();
+            assert_eq!(transport.requests("getrawmempool"), 1);
+
+            let txids = |id: &str| {
+                let mut txids = found[id].iter().map(|tx| tx.txid()).collect::<Vec<_>>();
+                txids.sort();
+                txids
+            };
+            let expected = |indexes: &[usize]| {
+                let mut txids = indexes
+                    .iter()
+                    .map(|index| transactions[*index].compute_txid().to_string())
+                    .collect::<Vec<_>>();
+                txids.sort();
+                txids
+            };
+
+            assert_eq!(found.len(), 3);
+            assert_eq!(txids("first"), expected(&[0, 3]));
+            assert_eq!(txids("second"), expected(&[1, 3]));
+            assert!(found["none"].is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_multi_no_groups() {
+            let transport = MockTransport::new(&[transaction(1)], &[]);
+            let client = client(transport.clone());
+
+            assert!(client.scan_mempool_multi(&[]).await.unwrap().is_empty());
+            assert_eq!(transport.requests("getrawmempool"), 0);
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_with_progress() {
+            let transactions = (0..2_500)
+                .map(|i| {
+                    let mut tx = transaction(1);
+                    tx.input[0].previous_output.vout = i;
+                    tx
+                })
+                .collect::<Vec<_>>();
+            let client = client(MockTransport::new(&transactions, &[]));
+
+            let progress = Mutex::new(Vec::new());
+            client
+                .scan_mempool_with_progress(&HashSet::new(), &HashSet::new(), &|scanned, total| {
+                    progress.lock().unwrap().push((scanned, total));
+                })
+                .await
+                .unwrap();
+
+            assert_eq!(
+                progress.into_inner().unwrap(),
+                vec![(1_000, 2_500), (2_000, 2_500), (2_500, 2_500)]
+            );
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_stream_dropped() {
+            use tracing_subscriber::layer::SubscriberExt;
+
+            let recorder = SpanRecorder::default();
+            let _guard = tracing::subscriber::set_default(
+                tracing_subscriber::registry().with(recorder.clone()),
+            );
+
+            let transactions = (1..=64).map(transaction).collect::<Vec<_>>();
+            let mut transport = MockTransport::new(&transactions, &[]);
+            transport.batch_delay = Duration::from_millis(10);
+            let client = client(transport.clone()).with_scan_config(1, 2).unwrap();
+
+            let outputs = transactions
+                .iter()
+                .map(|tx| tx.output[0].script_pubkey.to_bytes())
+                .collect::<HashSet<_>>();
+            let mut rx = client
+                .scan_mempool_stream(HashSet::new(), outputs)
+                .await
+                .unwrap();
+            rx.recv().await.unwrap();
+            drop(rx);
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let fetched = transport.requests("getrawtransaction");
+            assert!(fetched < transactions.len() / 2);
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert_eq!(transport.requests("getrawtransaction"), fetched);
+
+            let events = recorder.events.lock().unwrap();
+            assert!(
+                !events
+                    .iter()
+                    .any(|(message, _, _)| message.starts_with("Could not"))
+            );
+            assert!(
+                events
+                    .iter()
+                    .any(|(message, _, _)| message.starts_with("Stopping BTC mempool fetcher"))
+            );
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_with_filter() {
+            let transactions = (1..=4)
+                .map(|seed| {
+                    let mut tx = transaction(seed);
+                    tx.output[0].value = bitcoin::Amount::from_sat(seed as u64 * 10_000);
+                    tx
+                })
+                .collect::<Vec<_>>();
+            let client = client(MockTransport::new(&transactions, &[]));
+
+            let mut found = client
+                .scan_mempool_with_filter(&|tx| {
+                    tx.outputs()
+                        .iter()
+                        .any(|output| output.value_sat.is_some_and(|value| value > 20_000))
+                })
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|tx| tx.txid())
+                .collect::<Vec<_>>();
+            found.sort();
+
+            let mut expected = vec![
+                transactions[2].compute_txid().to_string(),
+                transactions[3].compute_txid().to_string(),
+            ];
+            expected.sort();
+            assert_eq!(found, expected);
+
+            assert!(
+                client
+                    .scan_mempool_with_filter(&|_| false)
+                    .await
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn test_scan_mempool_max_concurrent_batches() {
+            let transactions = (1..=32).map(transaction).collect::<Vec<_>>();
+            let mut transport = MockTransport::new(&transactions, &[]);
+            transport.batch_delay = Duration::from_millis(20);
+
+            let client = ChainClient::with_transport(
+                Type::Bitcoin,
+                transport.clone(),
+                Config {
+                    max_concurrent_batches: Some(2),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .with_scan_config(16, 1)
+            .unwrap();
+
+            let outputs = transactions
+                .iter()
+                .map(|tx| tx.output[0].script_pubkey.to_bytes())
+                .collect::<HashSet<_>>();
+            let found = client
+                .scan_mempool(&HashSet::new(), &outputs)
+                .await
+                .unwrap();
+
+            assert_eq!(found.len(), transactions.len());
+            assert_eq!(transport.requests("getrawtransaction"), transactions.len());
+            assert!(transport.max_in_flight.load(Ordering::SeqCst) <= 2);
+        }
+
+        fn ordered_transport(transactions: &[bitcoin::Transaction]) -> MockTransport {
+            let mut transport = MockTransport::new(transactions, &[]);
+            // 10, 50, 20 and 1 sat/vbyte
+            transport.fees = transactions
+                .iter()
+                .zip([
+                    (100, 0.00001),
+                    (200, 0.0001),
+                    (100, 0.00002),
+                    (150, 0.0000015),
+                ])
+                .map(|(tx, fee)| (tx.compute_txid().to_string(), fee))
+                .collect();
+            transport
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_ordered() {
+            let transactions = (1..=4).map(transaction).collect::<Vec<_>>();
+            let transport = ordered_transport(&transactions);
+            let client = client(transport.clone()).with_scan_config(1, 1).unwrap();
+
+            let outputs = [&transactions[0], &transactions[1], &transactions[3]]
+                .iter()
+                .map(|tx| tx.output[0].script_pubkey.to_bytes())
+                .collect::<HashSet<_>>();
+            let mut rx = client
+                .scan_mempool_ordered(HashSet::new(), outputs)
+                .await
+                .unwrap();
+
+            let mut found = Vec::new();
+            while let Some(tx) = rx.recv().await {
+                found.push(tx.txid());
+            }
+
+            let txid = |index: usize| transactions[index].compute_txid().to_string();
+            assert_eq!(
+                *transport.fetched.lock().unwrap(),
+                vec![txid(1), txid(2), txid(0), txid(3)]
+            );
+            assert_eq!(found, vec![txid(1), txid(0), txid(3)]);
+            assert_eq!(transport.requests("getrawmempool"), 1);
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_ordered_batches() {
+            let transactions = (1..=4).map(transaction).collect::<Vec<_>>();
+            let transport = ordered_transport(&transactions);
+            let client = client(transport.clone()).with_scan_config(1, 2).unwrap();
+
+            let outputs = transactions
+                .iter()
+                .map(|tx| tx.output[0].script_pubkey.to_bytes())
+                .collect::<HashSet<_>>();
+            let mut rx = client
+                .scan_mempool_ordered(HashSet::new(), outputs)
+                .await
+                .unwrap();
+            while rx.recv().await.is_some() {}
+
+            let txid = |index: usize| transactions[index].compute_txid().to_string();
+            assert_eq!(
+                *transport.fetched.lock().unwrap(),
+                vec![txid(1), txid(2), txid(0), txid(3)]
+            );
+            assert_eq!(transport.requests("getrawtransaction"), 2);
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_ordered_verbose_failed() {
+            let transactions = (1..=4).map(transaction).collect::<Vec<_>>();
+            let mut transport = ordered_transport(&transactions);
+            transport.verbose_fails = true;
+            let client = client(transport.clone()).with_scan_config(1, 1).unwrap();
+
+            let outputs = transactions
+                .iter()
+                .map(|tx| tx.output[0].script_pubkey.to_bytes())
+                .collect::<HashSet<_>>();
+            let mut rx = client
+                .scan_mempool_ordered(HashSet::new(), outputs)
+                .await
+                .unwrap();
+
+            let mut found = 0;
+            while rx.recv().await.is_some() {
+                found += 1;
+            }
+
+            // Falls back to the order of the plain mempool
+            assert_eq!(found, transactions.len());
+            assert_eq!(
+                *transport.fetched.lock().unwrap(),
+                transactions
+                    .iter()
+                    .map(|tx| tx.compute_txid().to_string())
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(transport.requests("getrawmempool"), 2);
+        }
+
+        #[test]
+        fn test_max_concurrent_batches_invalid() {
+            let err = ChainClient::with_transport(
+                Type::Bitcoin,
+                MockTransport::default(),
+                Config {
+                    max_concurrent_batches: Some(0),
+                    ..Default::default()
+                },
+            )
+            .err()
+            .unwrap();
+            assert_eq!(
+                err.to_string(),
+                "max concurrent batches have to be at least 1"
+            );
+        }
+
+        #[tokio::test]
+        async fn test_wait_for_network_info() {
+            let transport = MockTransport {
+                down_until: Some(Instant::now() + Duration::from_millis(700)),
+                ..Default::default()
+            };
+            let client = client(transport.clone());
+
+            let started = Instant::now();
+            let info = client
+                .wait_for_network_info(Duration::from_secs(10))
+                .await
+                .unwrap();
+            assert_eq!(info.subversion, "/Satoshi:28.0.0/");
+            assert!(started.elapsed() >= Duration::from_millis(700));
+            assert!(transport.requests("getnetworkinfo") >= 2);
+        }
+
+        #[tokio::test]
+        async fn test_wait_for_network_info_timeout() {
+            let transport = MockTransport {
+                down_until: Some(Instant::now() + Duration::from_secs(60)),
+                ..Default::default()
+            };
+            let client = client(transport.clone());
+
+            let err = client
+                .wait_for_network_info(Duration::from_secs(1))
+                .await
+                .err()
+                .unwrap();
+            assert_eq!(
+                err.to_string(),
+                "could not connect to BTC chain client after 3 attempts: Connection refused"
+            );
+            assert_eq!(transport.requests("getnetworkinfo"), 3);
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_descriptors() {
+            let seed = crate::wallet::test::get_seed();
+            let xpub = bitcoin::bip32::Xpub::from_priv(
+                &bitcoin::key::Secp256k1::signing_only(),
+                &bitcoin::bip32::Xpriv::new_master(bitcoin::NetworkKind::Test, &seed).unwrap(),
+            );
+            let descriptors = vec![
+                OutputDescriptor::from_str(&format!("wpkh({xpub}/0/*)")).unwrap(),
+                OutputDescriptor::from_str(&format!("tr({xpub}/1/*)")).unwrap(),
+            ];
+
+            // Pays to index 3 of the second descriptor in its second output
+            let mut payment = transaction(1);
+            payment.output.push(bitcoin::TxOut {
+                value: bitcoin::Amount::from_sat(21_000),
+                script_pubkey: bitcoin::ScriptBuf::from_bytes(
+                    descriptors[1].script_pubkey(3).unwrap(),
+                ),
+            });
+            let transactions = vec![payment.clone(), transaction(2)];
+            let client = client(MockTransport::new(&transactions, &[]));
+
+            let found = client.scan_mempool_descriptors(&descriptors).await.unwrap();
+            assert_eq!(
+                found,
+                vec![DescriptorMatch {
+                    transaction: Transaction::Bitcoin(payment),
+                    vout: 1,
+                    descriptor: 1,
+                    index: 3,
+                }]
+            );
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_descriptors_beyond_gap_limit() {
+            let seed = crate::wallet::test::get_seed();
+            let xpub = bitcoin::bip32::Xpub::from_priv(
+                &bitcoin::key::Secp256k1::signing_only(),
+                &bitcoin::bip32::Xpriv::new_master(bitcoin::NetworkKind::Test, &seed).unwrap(),
+            );
+            let descriptor = OutputDescriptor::from_str(&format!("wpkh({xpub}/0/*)")).unwrap();
+
+            let mut payment = transaction(1);
+            payment.output[0].script_pubkey =
+                bitcoin::ScriptBuf::from_bytes(descriptor.script_pubkey(2).unwrap());
+            let client = ChainClient::with_transport(
+                Type::Bitcoin,
+                MockTransport::new(&[payment], &[]),
+                Config {
+                    descriptor_gap_limit: Some(2),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            assert!(
+                client
+                    .scan_mempool_descriptors(&[descriptor])
+                    .await
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_descriptors_empty() {
+            let transport = MockTransport::new(&[transaction(1)], &[]);
+            let client = client(transport.clone());
+
+            assert!(
+                client
+                    .scan_mempool_descriptors(&[])
+                    .await
+                    .unwrap()
+                    .is_empty()
+            );
+            assert_eq!(transport.requests("getrawmempool"), 0);
+        }
+
+        // Id, name and fields of a span; ids are reused once a span is closed
+        type RecordedSpan = (u64, String, HashMap<String, String>);
+
+        // Message, fields and the names of the spans an event was nested in
+        type RecordedEvent = (String, HashMap<String, String>, Vec<String>);
+
+        // Every span and event
+        #[derive(Clone, Default)]
+        struct SpanRecorder {
+            spans: Arc<Mutex<Vec<RecordedSpan>>>,
+            events: Arc<Mutex<Vec<RecordedEvent>>>,
+        }
+
+        struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+        impl tracing::field::Visit for FieldVisitor<'_> {
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .insert(field.name().to_string(), format!("{value:?}"));
+            }
+
+            fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+                self.0.insert(field.name().to_string(), value.to_string());
+            }
+        }
+
+        impl<S> tracing_subscriber::Layer<S> for SpanRecorder
+        where
+            S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+        {
+            fn on_new_span(
+                &self,
+                attrs: &tracing::span::Attributes<'_>,
+                id: &tracing::span::Id,
+                _ctx: tracing_subscriber::layer::Context<'_, S>,
+            ) {
+                let mut fields = HashMap::new();
+                attrs.record(&mut FieldVisitor(&mut fields));
+                self.spans.lock().unwrap().push((
+                    id.into_u64(),
+                    attrs.metadata().name().to_string(),
+                    fields,
+                ));
+            }
+
+            fn on_record(
+                &self,
+                id: &tracing::span::Id,
+                values: &tracing::span::Record<'_>,
+                _ctx: tracing_subscriber::layer::Context<'_, S>,
+            ) {
+                let mut spans = self.spans.lock().unwrap();
+                if let Some((_, _, fields)) = spans
+                    .iter_mut()
+                    .rev()
+                    .find(|(span_id, _, _)| *span_id == id.into_u64())
+                {
+                    values.record(&mut FieldVisitor(fields));
+                }
+            }
+
+            fn on_event(
+                &self,
+                event: &tracing::Event<'_>,
+                ctx: tracing_subscriber::layer::Context<'_, S>,
+            ) {
+                let mut fields = HashMap::new();
+                event.record(&mut FieldVisitor(&mut fields));
+                let spans = ctx
+                    .event_scope(event)
+                    .map(|scope| scope.map(|span| span.name().to_string()).collect())
+                    .unwrap_or_default();
+
+                let message = fields.remove("message").unwrap_or_default();
+                self.events.lock().unwrap().push((message, fields, spans));
+            }
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_span() {
+            use tracing_subscriber::layer::SubscriberExt;
+
+            let recorder = SpanRecorder::default();
+            let _guard = tracing::subscriber::set_default(
+                tracing_subscriber::registry().with(recorder.clone()),
+            );
+
+            let transactions = vec![transaction(1), transaction(2)];
+            let client = client(MockTransport::new(&transactions, &["00".repeat(32)]));
+            client
+                .scan_mempool(&HashSet::new(), &HashSet::new())
+                .await
+                .unwrap();
+
+            let spans = recorder.spans.lock().unwrap();
+            let scans = spans
+                .iter()
+                .filter(|(_, name, _)| name == "scan_mempool")
+                .collect::<Vec<_>>();
+            assert_eq!(scans.len(), 1);
+
+            let fields = &scans[0].2;
+            assert_eq!(fields.get("symbol"), Some(&"BTC".to_string()));
+            assert_eq!(fields.get("mempool_size"), Some(&"3".to_string()));
+            assert!(fields.get("scan_id").unwrap().parse::<u64>().is_ok());
+
+            // The evicted transaction is logged by a worker
+            let events = recorder.events.lock().unwrap();
+            let worker_event = events
+                .iter()
+                .find(|(message, _, _)| message.starts_with("Could not fetch single"))
+                .unwrap();
+            assert!(worker_event.2.contains(&"scan_mempool".to_string()));
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_match_reason() {
+            use tracing_subscriber::layer::SubscriberExt;
+
+            let recorder = SpanRecorder::default();
+            let _guard = tracing::subscriber::set_default(
+                tracing_subscriber::registry().with(recorder.clone()),
+            );
+
+            let transactions = (1..=3).map(transaction).collect::<Vec<_>>();
+            let client = client(MockTransport::new(&transactions, &[]));
+
+            let input = Outpoint {
+                hash: vec![1; 32],
+                vout: 1,
+            };
+            let output = transactions[1].output[0].script_pubkey.to_bytes();
+            client
+                .scan_mempool(
+                    &HashSet::from([input.clone()]),
+                    &HashSet::from([output.clone()]),
+                )
+                .await
+                .unwrap();
+
+            let events = recorder.events.lock().unwrap();
+            let matches = events
+                .iter()
+                .filter(|(message, _, _)| message.starts_with("Mempool transaction of BTC chain"))
+                .map(|(message, fields, _)| (message.clone(), fields.clone()))
+                .collect::<HashMap<_, _>>();
+            assert_eq!(matches.len(), 2);
+
+            let spending = matches
+                .get("Mempool transaction of BTC chain spends a relevant input")
+                .unwrap();
+            assert_eq!(
+                spending.get("tx_id"),
+                Some(&transactions[0].compute_txid().to_string())
+            );
+            assert_eq!(spending.get("input"), Some(&format!("{}:1", input.tx_id())));
+            assert_eq!(
+                spending.get("vsize"),
+                Some(&transactions[0].vsize().to_string())
+            );
+
+            let paying = matches
+                .get("Mempool transaction of BTC chain pays to a relevant output")
+                .unwrap();
+            assert_eq!(
+                paying.get("tx_id"),
+                Some(&transactions[1].compute_txid().to_string())
+            );
+            assert_eq!(paying.get("script_pubkey"), Some(&hex::encode(output)));
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_span_ids() {
+            use tracing_subscriber::layer::SubscriberExt;
+
+            let recorder = SpanRecorder::default();
+            let _guard = tracing::subscriber::set_default(
+                tracing_subscriber::registry().with(recorder.clone()),
+            );
+
+            let client = client(MockTransport::new(&[transaction(1)], &[]));
+            for _ in 0..2 {
+                client
+                    .scan_mempool(&HashSet::new(), &HashSet::new())
+                    .await
+                    .unwrap();
+            }
+
+            let spans = recorder.spans.lock().unwrap();
+            let scan_ids = spans
+                .iter()
+                .filter(|(_, name, _)| name == "scan_mempool")
+                .map(|(_, _, fields)| fields.get("scan_id").unwrap().clone())
+                .collect::<HashSet<_>>();
+            assert_eq!(scan_ids.len(), 2);
+        }
+
+        #[tokio::test]
+        async fn test_scan_mempool_chunks() {
+            let transactions = (1..=5).map(transaction).collect::<Vec<_>>();
+            let transport = MockTransport::new(&transactions, &[]);
+            let client = client(transport.clone()).with_scan_config(1, 2).unwrap();
+
+            let outputs = transactions
+                .iter()
+                .map(|tx| tx.output[0].script_pubkey.to_bytes())
+                .collect::<HashSet<_>>();
+            let found = client
+                .scan_mempool(&HashSet::new(), &outputs)
+                .await
+                .unwrap();
+
+            assert_eq!(found.len(), transactions.len());
+            assert_eq!(transport.requests("getrawtransaction"), 3);
+        }
+    }
 }
@@ -1,10 +1,18 @@
 use crate::chain::chain_client::ChainClient;
-use crate::chain::types::NetworkInfo;
-use crate::chain::utils::{Outpoint, Transaction};
-use crate::chain::{BaseClient, Client, LiquidConfig};
+use crate::chain::reorg::ReorgEvent;
+use crate::chain::rpc_metrics::MethodStats;
+use crate::chain::types::{
+    BlockHeader, FeeEstimate, MempoolAcceptResult, MempoolEntry, MempoolInfo, NetworkInfo,
+    SpentStatus,
+};
+use crate::chain::utils::{Block, DescriptorMatch, Outpoint, RelevantTransaction, Transaction};
+use crate::chain::{BaseClient, ChainError, Client, LiquidConfig, ScanGroup};
+use crate::wallet::OutputDescriptor;
 use async_trait::async_trait;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 use tokio::sync::broadcast::Receiver;
+use tokio::sync::mpsc;
 use tracing::{debug, info, instrument, warn};
 
 pub const SYMBOL: &str = "L-BTC";
@@ -15,11 +23,15 @@ const TYPE: crate::chain::types::Type = crate::chain::types::Type::Elements;
 pub struct ElementsClient {
     client: ChainClient,
     lowball_client: Option<ChainClient>,
+
+    // The lowball client is waited for as long as the main one
+    connect_max_wait: Option<Duration>,
 }
 
 impl ElementsClient {
     #[instrument(name = "ElementsClient::new", skip(config))]
     pub fn new(config: LiquidConfig) -> anyhow::Result<Self> {
+        let connect_max_wait = config.base.connect_max_wait();
         let client = ChainClient::new(TYPE, SYMBOL.to_string(), config.base)?;
         let lowball_client = match config.lowball {
             Some(lowball_config) => {
@@ -34,9 +46,20 @@ impl ElementsClient {
         Ok(Self {
             client,
             lowball_client,
+            connect_max_wait,
         })
     }
 
+    async fn connect_chain_client(
+        client: &mut ChainClient,
+        max_wait: Option<Duration>,
+    ) -> anyhow::Result<()> {
+        match max_wait {
+            Some(max_wait) => client.connect_with_retry(max_wait).await,
+            None => client.connect().await,
+        }
+    }
+
     fn wallet_client(&self) -> &ChainClient {
         match &self.lowball_client {
             Some(lowball) => lowball,
@@ -56,11 +79,11 @@ impl BaseClient for ElementsClient {
     }
 
     async fn connect(&mut self) -> anyhow::Result<()> {
-        self.client.connect().await?;
+        Self::connect_chain_client(&mut self.client, self.connect_max_wait).await?;
 
         if let Some(mut lowball_client) = self.lowball_client.clone() {
             info!("Connecting to {} lowball client", SYMBOL);
-            lowball_client.connect().await?;
+            Self::connect_chain_client(&mut lowball_client, self.connect_max_wait).await?;
             self.lowball_client = Some(lowball_client);
         }
 
@@ -74,19 +97,188 @@ impl Client for ElementsClient {
         &self,
         relevant_inputs: &HashSet<Outpoint>,
         relevant_outputs: &HashSet<Vec<u8>>,
-    ) -> anyhow::Result<Vec<Transaction>> {
+    ) -> Result<Vec<Transaction>, ChainError> {
         self.wallet_client()
             .scan_mempool(relevant_inputs, relevant_outputs)
             .await
     }
 
-    async fn network_info(&self) -> anyhow::Result<NetworkInfo> {
-        self.wallet_client().network_info().await
+    async fn scan_mempool_with_progress(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+        on_progress: &(dyn Fn(usize, usize) + Send + Sync),
+    ) -> Result<Vec<Transaction>, ChainError> {
+        self.wallet_client()
+            .scan_mempool_with_progress(relevant_inputs, relevant_outputs, on_progress)
+            .await
+    }
+
+    async fn scan_mempool_with_filter(
+        &self,
+        predicate: &(dyn Fn(&Transaction) -> bool + Send + Sync),
+    ) -> Result<Vec<Transaction>, ChainError> {
+        self.wallet_client()
+            .scan_mempool_with_filter(predicate)
+            .await
+    }
+
+    async fn scan_mempool_raw(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+    ) -> Result<Vec<(Transaction, String)>, ChainError> {
+        self.wallet_client()
+            .scan_mempool_raw(relevant_inputs, relevant_outputs)
+            .await
+    }
+
+    async fn scan_mempool_incremental(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+    ) -> Result<Vec<Transaction>, ChainError> {
+        self.wallet_client()
+            .scan_mempool_incremental(relevant_inputs, relevant_outputs)
+            .await
+    }
+
+    async fn scan_mempool_detailed(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+    ) -> Result<Vec<RelevantTransaction>, ChainError> {
+        self.wallet_client()
+            .scan_mempool_detailed(relevant_inputs, relevant_outputs)
+            .await
+    }
+
+    async fn scan_mempool_multi(
+        &self,
+        groups: &[ScanGroup],
+    ) -> Result<HashMap<String, Vec<Transaction>>, ChainError> {
+        self.wallet_client().scan_mempool_multi(groups).await
+    }
+
+    async fn scan_mempool_descriptors(
+        &self,
+        descriptors: &[OutputDescriptor],
+    ) -> Result<Vec<DescriptorMatch>, ChainError> {
+        self.wallet_client()
+            .scan_mempool_descriptors(descriptors)
+            .await
+    }
+
+    async fn scan_mempool_stream(
+        &self,
+        relevant_inputs: HashSet<Outpoint>,
+        relevant_outputs: HashSet<Vec<u8>>,
+    ) -> Result<mpsc::Receiver<Transaction>, ChainError> {
+        self.wallet_client()
+            .scan_mempool_stream(relevant_inputs, relevant_outputs)
+            .await
+    }
+
+    async fn scan_mempool_ordered(
+        &self,
+        relevant_inputs: HashSet<Outpoint>,
+        relevant_outputs: HashSet<Vec<u8>>,
+    ) -> Result<mpsc::Receiver<Transaction>, ChainError> {
+        self.wallet_client()
+            .scan_mempool_ordered(relevant_inputs, relevant_outputs)
+            .await
+    }
+
+    async fn network_info(&self, force_refresh: bool) -> Result<NetworkInfo, ChainError> {
+        self.wallet_client().network_info(force_refresh).await
+    }
+
+    async fn send_raw_transaction(&self, tx_hex: &str) -> Result<String, ChainError> {
+        self.wallet_client().send_raw_transaction(tx_hex).await
+    }
+
+    async fn test_mempool_accept(&self, tx_hex: &str) -> Result<MempoolAcceptResult, ChainError> {
+        self.wallet_client().test_mempool_accept(tx_hex).await
+    }
+
+    async fn estimate_fee(&self, conf_target: u16) -> Result<FeeEstimate, ChainError> {
+        self.wallet_client().estimate_fee(conf_target).await
+    }
+
+    async fn get_transaction(&self, tx_id: &str) -> Result<Transaction, ChainError> {
+        self.wallet_client().get_transaction(tx_id).await
+    }
+
+    async fn get_transactions(
+        &self,
+        tx_ids: &[String],
+    ) -> Result<Vec<Result<Transaction, ChainError>>, ChainError> {
+        self.wallet_client().get_transactions(tx_ids).await
+    }
+
+    async fn get_tx_confirmations(&self, tx_id: &str) -> Result<u32, ChainError> {
+        self.wallet_client().get_tx_confirmations(tx_id).await
+    }
+
+    async fn is_outpoint_spent(&self, outpoint: &Outpoint) -> Result<SpentStatus, ChainError> {
+        self.wallet_client().is_outpoint_spent(outpoint).await
+    }
+
+    async fn watch_address(&self, address: &str, rescan: bool) -> Result<(), ChainError> {
+        self.wallet_client().watch_address(address, rescan).await
+    }
+
+    async fn get_mempool_size(&self) -> Result<usize, ChainError> {
+        self.wallet_client().get_mempool_size().await
+    }
+
+    async fn get_mempool_info(&self) -> Result<MempoolInfo, ChainError> {
+        self.wallet_client().get_mempool_info().await
+    }
+
+    async fn get_mempool_entry(&self, tx_id: &str) -> Result<MempoolEntry, ChainError> {
+        self.wallet_client().get_mempool_entry(tx_id).await
+    }
+
+    async fn is_initial_block_download(&self) -> Result<bool, ChainError> {
+        self.wallet_client().is_initial_block_download().await
+    }
+
+    async fn sync_progress(&self) -> Result<f64, ChainError> {
+        self.wallet_client().sync_progress().await
+    }
+
+    async fn get_block_count(&self) -> Result<u64, ChainError> {
+        self.wallet_client().get_block_count().await
+    }
+
+    async fn get_best_block_hash(&self) -> Result<String, ChainError> {
+        self.wallet_client().get_best_block_hash().await
+    }
+
+    async fn get_block_header(&self, hash: &str) -> Result<BlockHeader, ChainError> {
+        self.wallet_client().get_block_header(hash).await
+    }
+
+    fn rpc_stats(&self) -> HashMap<String, MethodStats> {
+        self.wallet_client().rpc_stats()
     }
 
     fn tx_receiver(&self) -> Receiver<Transaction> {
         self.wallet_client().tx_receiver()
     }
+
+    async fn subscribe_raw_tx(&self) -> Result<mpsc::Receiver<Transaction>, ChainError> {
+        self.wallet_client().subscribe_raw_tx().await
+    }
+
+    async fn subscribe_raw_block(&self) -> Result<mpsc::Receiver<Block>, ChainError> {
+        self.wallet_client().subscribe_raw_block().await
+    }
+
+    async fn watch_reorgs(&self) -> Result<mpsc::Receiver<ReorgEvent>, ChainError> {
+        self.wallet_client().watch_reorgs().await
+    }
 }
 
 #[cfg(test)]
@@ -104,6 +296,7 @@ pub mod test {
             cookie: None,
             user: Some("boltz".to_string()),
             password: Some("anoVB0m1KvX0SmpPxvaLVADg0UQVLQTEx3jCD3qtuRI".to_string()),
+            ..Default::default()
         };
 
         static CLIENT: OnceLock<(ElementsClient, Config)> = OnceLock::new();
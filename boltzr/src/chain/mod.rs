@@ -1,18 +1,24 @@
-use crate::chain::utils::{Outpoint, Transaction};
+use crate::chain::utils::{Block, DescriptorMatch, Outpoint, RelevantTransaction, Transaction};
+use crate::wallet::OutputDescriptor;
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
 use tokio::sync::broadcast::Receiver;
+use tokio::sync::mpsc;
 
 pub mod chain_client;
 pub mod elements_client;
+pub mod reorg;
 mod rpc_client;
+pub mod rpc_metrics;
 pub mod types;
 pub mod utils;
 pub mod zmq_client;
 
-#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Default)]
 pub struct Config {
     host: String,
     port: u16,
@@ -21,6 +27,68 @@ pub struct Config {
 
     user: Option<String>,
     password: Option<String>,
+
+    // Used instead of the endpoints advertised by the node when set
+    #[serde(rename = "zmqRawTx")]
+    zmq_raw_tx: Option<String>,
+    #[serde(rename = "zmqRawBlock")]
+    zmq_raw_block: Option<String>,
+
+    #[serde(rename = "mempoolScanWorkers")]
+    mempool_scan_workers: Option<usize>,
+    #[serde(rename = "mempoolFetchChunkSize")]
+    mempool_fetch_chunk_size: Option<usize>,
+    // Bounds the RPC pressure of mempool scans regardless of the number of workers
+    #[serde(rename = "maxConcurrentBatches")]
+    max_concurrent_batches: Option<usize>,
+
+    retry: Option<rpc_client::RetryConfig>,
+    #[serde(rename = "maxConnections")]
+    max_connections: Option<usize>,
+    #[serde(rename = "requestTimeoutMs", default, with = "duration_millis")]
+    request_timeout: Option<Duration>,
+    #[serde(rename = "networkInfoTtlMs", default, with = "duration_millis")]
+    network_info_ttl: Option<Duration>,
+    // Callers retry connecting until the node is up for that long when set
+    #[serde(rename = "connectMaxWaitMs", default, with = "duration_millis")]
+    connect_max_wait: Option<Duration>,
+
+    #[serde(rename = "reorgPollIntervalMs", default, with = "duration_millis")]
+    reorg_poll_interval: Option<Duration>,
+    // Number of recent blocks compared against the chain of the node
+    #[serde(rename = "reorgDepth")]
+    reorg_depth: Option<usize>,
+
+    // Number of scripts per descriptor that mempool scans match against
+    #[serde(rename = "descriptorGapLimit")]
+    descriptor_gap_limit: Option<u32>,
+}
+
+impl Config {
+    pub fn connect_max_wait(&self) -> Option<Duration> {
+        self.connect_max_wait
+    }
+}
+
+mod duration_millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_some(&(duration.as_millis() as u64)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_millis))
+    }
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
@@ -31,6 +99,71 @@ pub struct LiquidConfig {
     lowball: Option<Config>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainError {
+    // The node could not be reached or answered with an invalid HTTP response
+    Transport(String),
+    // JSON-RPC error returned by the node
+    Rpc(types::RpcError),
+    Parse(String),
+    NotFound(String),
+    NotInMempool(String),
+    Rejected(String),
+    NoEstimate,
+    Timeout(Duration),
+    Other(String),
+}
+
+impl Display for ChainError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChainError::Transport(err) => write!(f, "{err}"),
+            ChainError::Rpc(err) => write!(f, "{err}"),
+            ChainError::Parse(err) => write!(f, "could not parse: {err}"),
+            ChainError::NotFound(tx_id) => write!(f, "transaction {tx_id} not found"),
+            ChainError::NotInMempool(tx_id) => {
+                write!(f, "transaction {tx_id} is not in the mempool")
+            }
+            ChainError::Rejected(reason) => write!(f, "transaction rejected: {reason}"),
+            ChainError::NoEstimate => write!(f, "no fee estimate available"),
+            ChainError::Timeout(timeout) => write!(f, "request timed out after {timeout:?}"),
+            ChainError::Other(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ChainError {}
+
+impl From<anyhow::Error> for ChainError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<ChainError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<types::RpcError>() {
+            Ok(err) => return ChainError::Rpc(err),
+            Err(err) => err,
+        };
+
+        if err.downcast_ref::<reqwest::Error>().is_some() {
+            ChainError::Transport(err.to_string())
+        } else if err.downcast_ref::<serde_json::Error>().is_some()
+            || err.downcast_ref::<alloy::hex::FromHexError>().is_some()
+            || err
+                .downcast_ref::<bitcoin::consensus::encode::Error>()
+                .is_some()
+            || err.downcast_ref::<elements::encode::Error>().is_some()
+        {
+            ChainError::Parse(err.to_string())
+        } else {
+            ChainError::Other(err.to_string())
+        }
+    }
+}
+
+// Id of the group and the inputs and outputs relevant to it
+pub type ScanGroup = (String, HashSet<Outpoint>, HashSet<Vec<u8>>);
+
 #[async_trait]
 pub trait BaseClient {
     fn kind(&self) -> String;
@@ -45,9 +178,169 @@ pub trait Client: BaseClient {
         &self,
         relevant_inputs: &HashSet<Outpoint>,
         relevant_outputs: &HashSet<Vec<u8>>,
-    ) -> Result<Vec<Transaction>>;
+    ) -> Result<Vec<Transaction>, ChainError>;
+
+    // Reports (scanned, total) every 1,000 transactions and once the scan is done
+    async fn scan_mempool_with_progress(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+        on_progress: &(dyn Fn(usize, usize) + Send + Sync),
+    ) -> Result<Vec<Transaction>, ChainError>;
+
+    // For relevance logic beyond spent inputs and paid outputs
+    async fn scan_mempool_with_filter(
+        &self,
+        predicate: &(dyn Fn(&Transaction) -> bool + Send + Sync),
+    ) -> Result<Vec<Transaction>, ChainError>;
+
+    // Pairs every relevant transaction with the hex returned by the node
+    async fn scan_mempool_raw(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+    ) -> Result<Vec<(Transaction, String)>, ChainError>;
+
+    // Only fetches transactions that were not seen by a previous incremental scan;
+    // seen transactions are returned again when they match filters that were
+    // added since the previous scan
+    async fn scan_mempool_incremental(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+    ) -> Result<Vec<Transaction>, ChainError>;
+
+    // Includes which inputs and outputs made a transaction relevant
+    async fn scan_mempool_detailed(
+        &self,
+        relevant_inputs: &HashSet<Outpoint>,
+        relevant_outputs: &HashSet<Vec<u8>>,
+    ) -> Result<Vec<RelevantTransaction>, ChainError>;
+
+    // Matches every transaction against all groups while fetching the mempool only once
+    async fn scan_mempool_multi(
+        &self,
+        groups: &[ScanGroup],
+    ) -> Result<HashMap<String, Vec<Transaction>>, ChainError>;
+
+    // Matches the outputs against the scripts derived up to the gap limit of every descriptor
+    async fn scan_mempool_descriptors(
+        &self,
+        descriptors: &[OutputDescriptor],
+    ) -> Result<Vec<DescriptorMatch>, ChainError>;
+
+    // Yields relevant transactions as soon as they are found
+    async fn scan_mempool_stream(
+        &self,
+        relevant_inputs: HashSet<Outpoint>,
+        relevant_outputs: HashSet<Vec<u8>>,
+    ) -> Result<mpsc::Receiver<Transaction>, ChainError>;
+    // Yields the relevant transactions that pay the most per vbyte first
+    async fn scan_mempool_ordered(
+        &self,
+        relevant_inputs: HashSet<Outpoint>,
+        relevant_outputs: HashSet<Vec<u8>>,
+    ) -> Result<mpsc::Receiver<Transaction>, ChainError>;
+
+    // Latencies and errors of the RPC calls per method since the client was created
+    fn rpc_stats(&self) -> HashMap<String, rpc_metrics::MethodStats>;
+
+    // Served from a short-lived cache unless a refresh is forced
+    async fn network_info(&self, force_refresh: bool) -> Result<types::NetworkInfo, ChainError>;
+
+    async fn send_raw_transaction(&self, tx_hex: &str) -> Result<String, ChainError>;
+    // Checks whether the node would accept a transaction without broadcasting it
+    async fn test_mempool_accept(
+        &self,
+        tx_hex: &str,
+    ) -> Result<types::MempoolAcceptResult, ChainError>;
+
+    async fn estimate_fee(&self, conf_target: u16) -> Result<types::FeeEstimate, ChainError>;
 
-    async fn network_info(&self) -> Result<types::NetworkInfo>;
+    async fn get_transaction(&self, tx_id: &str) -> Result<Transaction, ChainError>;
+    // A transaction that can't be fetched doesn't fail the others
+    async fn get_transactions(
+        &self,
+        tx_ids: &[String],
+    ) -> Result<Vec<Result<Transaction, ChainError>>, ChainError>;
+    // 0 for transactions that are still in the mempool
+    async fn get_tx_confirmations(&self, tx_id: &str) -> Result<u32, ChainError>;
+    // Outpoints that never existed are reported as spent on chain
+    async fn is_outpoint_spent(
+        &self,
+        outpoint: &Outpoint,
+    ) -> Result<types::SpentStatus, ChainError>;
+
+    // Rescans are slow, so they should only be requested when needed
+    async fn watch_address(&self, address: &str, rescan: bool) -> Result<(), ChainError>;
+
+    async fn get_mempool_size(&self) -> Result<usize, ChainError>;
+    async fn get_mempool_info(&self) -> Result<types::MempoolInfo, ChainError>;
+    async fn get_mempool_entry(&self, tx_id: &str) -> Result<types::MempoolEntry, ChainError>;
+
+    async fn is_initial_block_download(&self) -> Result<bool, ChainError>;
+    // Estimated share of the chain that was verified, between 0 and 1
+    async fn sync_progress(&self) -> Result<f64, ChainError>;
+
+    async fn get_block_count(&self) -> Result<u64, ChainError>;
+    async fn get_best_block_hash(&self) -> Result<String, ChainError>;
+    async fn get_block_header(&self, hash: &str) -> Result<types::BlockHeader, ChainError>;
 
     fn tx_receiver(&self) -> Receiver<Transaction>;
+
+    async fn subscribe_raw_tx(&self) -> Result<mpsc::Receiver<Transaction>, ChainError>;
+    async fn subscribe_raw_block(&self) -> Result<mpsc::Receiver<Block>, ChainError>;
+
+    // Polls the chain tip until the receiver is dropped
+    async fn watch_reorgs(&self) -> Result<mpsc::Receiver<reorg::ReorgEvent>, ChainError>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chain::types::{RpcError, Type};
+    use crate::chain::utils::parse_transaction_hex;
+
+    #[test]
+    fn test_chain_error_from_chain_error() {
+        let err: anyhow::Error = ChainError::NotFound("tx".to_string()).into();
+        assert_eq!(
+            ChainError::from(err),
+            ChainError::NotFound("tx".to_string())
+        );
+    }
+
+    #[test]
+    fn test_chain_error_from_rpc_error() {
+        let rpc_err = RpcError {
+            code: -5,
+            message: "No such mempool or blockchain transaction".to_string(),
+        };
+
+        let err = ChainError::from(anyhow::Error::from(rpc_err.clone()));
+        assert_eq!(err, ChainError::Rpc(rpc_err));
+        assert_eq!(err.to_string(), "No such mempool or blockchain transaction");
+    }
+
+    #[test]
+    fn test_chain_error_from_parse_error() {
+        let err = parse_transaction_hex(&Type::Bitcoin, "not hex")
+            .err()
+            .unwrap();
+        assert!(matches!(ChainError::from(err), ChainError::Parse(_)));
+
+        let err = parse_transaction_hex(&Type::Bitcoin, "00").err().unwrap();
+        assert!(matches!(ChainError::from(err), ChainError::Parse(_)));
+
+        let err = parse_transaction_hex(&Type::Elements, "00").err().unwrap();
+        assert!(matches!(ChainError::from(err), ChainError::Parse(_)));
+    }
+
+    #[test]
+    fn test_chain_error_from_other() {
+        assert_eq!(
+            ChainError::from(anyhow::anyhow!("something")),
+            ChainError::Other("something".to_string())
+        );
+    }
 }
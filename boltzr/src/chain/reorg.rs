@@ -0,0 +1,194 @@
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReorgEvent {
+    // Height of the tip that got orphaned
+    pub from_height: u64,
+    // Height of the last block both chains have in common
+    pub common_ancestor: u64,
+    // Set when the fork reaches below the recorded blocks; the actual common
+    // ancestor might be lower than the reported one in that case
+    pub deep: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockHistory {
+    depth: usize,
+    // Sorted by height, ascending
+    blocks: VecDeque<(u64, String)>,
+}
+
+impl BlockHistory {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            blocks: VecDeque::with_capacity(depth),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn tip(&self) -> Option<&(u64, String)> {
+        self.blocks.back()
+    }
+
+    pub fn lowest_height(&self) -> Option<u64> {
+        self.blocks.front().map(|(height, _)| *height)
+    }
+
+    pub fn blocks(&self) -> impl DoubleEndedIterator<Item = &(u64, String)> {
+        self.blocks.iter()
+    }
+
+    pub fn contains(&self, height: u64, hash: &str) -> bool {
+        self.blocks.iter().any(|(recorded_height, recorded_hash)| {
+            *recorded_height == height && recorded_hash == hash
+        })
+    }
+
+    // Connects the blocks, sorted by height ascending, on top of the recorded
+    // chain; a reorg is reported when recorded blocks have to be replaced
+    pub fn connect(&mut self, blocks: Vec<(u64, String)>) -> Option<ReorgEvent> {
+        let first_height = blocks.first()?.0;
+
+        let event = match self.tip() {
+            Some((tip_height, _)) if *tip_height >= first_height => {
+                let from_height = *tip_height;
+                let common_ancestor = first_height.saturating_sub(1);
+
+                // Only blocks below the fork point are still part of the chain
+                self.blocks.retain(|(height, _)| *height < first_height);
+
+                Some(ReorgEvent {
+                    from_height,
+                    common_ancestor,
+                    // Nothing is left when the fork is deeper than what was recorded
+                    deep: self.blocks.is_empty(),
+                })
+            }
+            _ => None,
+        };
+
+        self.blocks.extend(blocks);
+        while self.blocks.len() > self.depth {
+            self.blocks.pop_front();
+        }
+
+        event
+    }
+
+    pub fn reset(&mut self, blocks: Vec<(u64, String)>) {
+        self.blocks.clear();
+        self.blocks.extend(blocks);
+        while self.blocks.len() > self.depth {
+            self.blocks.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chain(heights: std::ops::RangeInclusive<u64>, fork: &str) -> Vec<(u64, String)> {
+        heights
+            .map(|height| (height, format!("{fork}{height}")))
+            .collect()
+    }
+
+    #[test]
+    fn test_connect_extends() {
+        let mut history = BlockHistory::new(3);
+        assert_eq!(history.tip(), None);
+
+        assert_eq!(history.connect(chain(1..=2, "a")), None);
+        assert_eq!(history.connect(chain(3..=5, "a")), None);
+
+        assert_eq!(history.tip(), Some(&(5, "a5".to_string())));
+        assert_eq!(history.lowest_height(), Some(3));
+        assert!(history.contains(4, "a4"));
+        assert!(!history.contains(2, "a2"));
+        assert!(!history.contains(4, "b4"));
+    }
+
+    #[test]
+    fn test_connect_reorg() {
+        let mut history = BlockHistory::new(10);
+        history.connect(chain(1..=5, "a"));
+
+        assert_eq!(
+            history.connect(chain(4..=6, "b")),
+            Some(ReorgEvent {
+                from_height: 5,
+                common_ancestor: 3,
+                deep: false,
+            })
+        );
+        assert_eq!(history.tip(), Some(&(6, "b6".to_string())));
+        assert!(history.contains(3, "a3"));
+        assert!(!history.contains(4, "a4"));
+        assert!(history.contains(4, "b4"));
+    }
+
+    #[test]
+    fn test_connect_reorg_same_height() {
+        let mut history = BlockHistory::new(10);
+        history.connect(chain(1..=5, "a"));
+
+        assert_eq!(
+            history.connect(chain(5..=5, "b")),
+            Some(ReorgEvent {
+                from_height: 5,
+                common_ancestor: 4,
+                deep: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_connect_deeper_than_history() {
+        let mut history = BlockHistory::new(2);
+        history.connect(chain(1..=5, "a"));
+
+        assert_eq!(
+            history.connect(chain(3..=6, "b")),
+            Some(ReorgEvent {
+                from_height: 5,
+                common_ancestor: 2,
+                deep: true,
+            })
+        );
+        assert_eq!(history.tip(), Some(&(6, "b6".to_string())));
+        assert_eq!(history.lowest_height(), Some(5));
+    }
+
+    #[test]
+    fn test_blocks() {
+        let mut history = BlockHistory::new(2);
+        history.connect(chain(1..=3, "a"));
+
+        assert_eq!(
+            history.blocks().rev().collect::<Vec<_>>(),
+            vec![&(3, "a3".to_string()), &(2, "a2".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_connect_empty() {
+        let mut history = BlockHistory::new(2);
+        assert_eq!(history.connect(Vec::new()), None);
+        assert_eq!(history.lowest_height(), None);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut history = BlockHistory::new(2);
+        history.connect(chain(1..=5, "a"));
+
+        history.reset(chain(10..=13, "b"));
+        assert_eq!(history.lowest_height(), Some(12));
+        assert_eq!(history.tip(), Some(&(13, "b13".to_string())));
+    }
+}
@@ -1,26 +1,163 @@
-use crate::chain::Config;
+use crate::chain::rpc_metrics::{MethodStats, RpcMetrics};
 use crate::chain::types::{RpcParam, RpcRequest, RpcResponse};
+use crate::chain::{ChainError, Config};
 use anyhow::anyhow;
+use async_trait::async_trait;
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Debug;
 use std::fs;
-use tracing::{debug, instrument};
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tracing::{debug, info, instrument};
 
-#[derive(PartialEq, Debug, Clone)]
+const DEFAULT_MAX_CONNECTIONS: usize = 32;
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+// Only calls that don't change the state of the node are safe to send more than once
+const RETRYABLE_METHODS: &[&str] = &[
+    "estimatesmartfee",
+    "getbestblockhash",
+    "getblockchaininfo",
+    "getblockcount",
+    "getblockhash",
+    "getblockheader",
+    "getdescriptorinfo",
+    "getmempoolentry",
+    "getmempoolinfo",
+    "getnetworkinfo",
+    "getrawmempool",
+    "getrawtransaction",
+    "gettransaction",
+    "gettxout",
+    "getzmqnotifications",
+    "testmempoolaccept",
+];
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct RetryConfig {
+    #[serde(rename = "maxAttempts")]
+    pub max_attempts: u32,
+    #[serde(rename = "initialDelayMs")]
+    pub initial_delay_ms: u64,
+    #[serde(rename = "maxDelayMs")]
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay_ms: 250,
+            max_delay_ms: 5_000,
+        }
+    }
+}
+
+impl RetryConfig {
+    fn delay(&self, attempt: u32) -> Duration {
+        Duration::from_millis(
+            self.initial_delay_ms
+                .saturating_mul(2u64.saturating_pow(attempt.saturating_sub(1)))
+                .min(self.max_delay_ms),
+        )
+    }
+}
+
+enum RequestError {
+    // Connection failures and HTTP server errors without a JSON-RPC response
+    Transient(anyhow::Error),
+    Permanent(anyhow::Error),
+    Unauthorized(anyhow::Error),
+}
+
+impl From<RequestError> for anyhow::Error {
+    fn from(err: RequestError) -> Self {
+        match err {
+            RequestError::Transient(err)
+            | RequestError::Permanent(err)
+            | RequestError::Unauthorized(err) => err,
+        }
+    }
+}
+
+// Abstracts the node away so that the chain client can be tested without one
+#[async_trait]
+pub trait RpcTransport: Debug + Clone + Send + Sync + 'static {
+    fn symbol(&self) -> &str;
+
+    async fn request<T: DeserializeOwned + Send>(
+        &self,
+        method: &str,
+        params: Option<Vec<RpcParam>>,
+    ) -> anyhow::Result<T>;
+
+    async fn request_null(&self, method: &str, params: Option<Vec<RpcParam>>)
+    -> anyhow::Result<()>;
+
+    async fn request_optional<T: DeserializeOwned + Send>(
+        &self,
+        method: &str,
+        params: Option<Vec<RpcParam>>,
+    ) -> anyhow::Result<Option<T>>;
+
+    async fn request_batch<T: DeserializeOwned + Send>(
+        &self,
+        method: &str,
+        params: Vec<Vec<RpcParam>>,
+    ) -> anyhow::Result<Vec<anyhow::Result<T>>>;
+
+    // Transports that don't record their calls have no stats to report
+    fn metrics(&self) -> HashMap<String, MethodStats> {
+        HashMap::new()
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct RpcClient {
     pub(crate) symbol: String,
 
     endpoint: String,
-    cookie: String,
+    // Reloaded when the node rotates the cookie file
+    cookie_file: Option<String>,
+    cookie: Arc<RwLock<String>>,
+    retry: RetryConfig,
+    // Includes all retries of a call
+    request_timeout: Duration,
+
+    // Shared by all clones so that connections are reused
+    client: reqwest::Client,
+    max_connections: usize,
+    connections: Arc<Semaphore>,
+
+    metrics: RpcMetrics,
+}
+
+impl PartialEq for RpcClient {
+    fn eq(&self, other: &Self) -> bool {
+        self.symbol == other.symbol
+            && self.endpoint == other.endpoint
+            && self.cookie_file == other.cookie_file
+            && self.auth() == other.auth()
+            && self.retry == other.retry
+            && self.request_timeout == other.request_timeout
+            && self.max_connections == other.max_connections
+    }
 }
 
 impl RpcClient {
     #[instrument(name = "RpcClient::new", skip(config))]
     pub fn new(symbol: String, config: Config) -> anyhow::Result<Self> {
-        let auth = match config.cookie {
+        let auth = match &config.cookie {
             Some(cookie) => {
                 let cookie = fs::read(cookie)?;
                 debug!("Using cookie file auth for {} chain client", symbol);
@@ -39,10 +176,33 @@ impl RpcClient {
             }
         };
 
+        let max_connections = config.max_connections.unwrap_or(DEFAULT_MAX_CONNECTIONS);
+        if max_connections < 1 {
+            return Err(anyhow!("max connections have to be at least 1"));
+        }
+
+        let request_timeout = config.request_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT);
+        if request_timeout.is_zero() {
+            return Err(anyhow!("request timeout has to be greater than 0"));
+        }
+
+        let client = reqwest::Client::builder()
+            .pool_max_idle_per_host(max_connections)
+            .pool_idle_timeout(KEEP_ALIVE)
+            .tcp_keepalive(KEEP_ALIVE)
+            .build()?;
+
         Ok(Self {
             symbol,
             endpoint: format!("http://{}:{}", config.host, config.port),
-            cookie: format!("Basic {}", BASE64_STANDARD.encode(auth)),
+            cookie_file: config.cookie,
+            cookie: Arc::new(RwLock::new(Self::encode_auth(auth))),
+            retry: config.retry.unwrap_or_default(),
+            request_timeout,
+            client,
+            max_connections,
+            connections: Arc::new(Semaphore::new(max_connections)),
+            metrics: RpcMetrics::default(),
         })
     }
 
@@ -52,27 +212,78 @@ impl RpcClient {
         method: &str,
         params: Option<Vec<RpcParam>>,
     ) -> anyhow::Result<T> {
-        let client = reqwest::Client::new();
+        self.timed(method, async {
+            let data = self
+                .post_with_timeout::<RpcResponse<T>>(
+                    method,
+                    &json!({
+                        "method": method,
+                        "params": params.unwrap_or_default(),
+                    }),
+                )
+                .await?;
+            if let Some(err) = data.error {
+                return Err(err.into());
+            }
 
-        let response = client
-            .post(&self.endpoint)
-            .headers(self.get_headers()?)
-            .json(&json!({
-                "method": method,
-                "params": params.unwrap_or_default(),
-            }))
-            .send()
-            .await?;
+            match data.result {
+                Some(res) => Ok(res),
+                None => Err(anyhow::anyhow!("no result")),
+            }
+        })
+        .await
+    }
 
-        let data = response.json::<RpcResponse<T>>().await?;
-        if let Some(err) = data.error {
-            return Err(anyhow!(err.message));
-        }
+    // For methods that return null on success
+    #[instrument(name = "RpcClient::request_null", skip(self), fields(symbol = self.symbol))]
+    pub async fn request_null(
+        &self,
+        method: &str,
+        params: Option<Vec<RpcParam>>,
+    ) -> anyhow::Result<()> {
+        self.timed(method, async {
+            let data = self
+                .post_with_timeout::<RpcResponse<serde_json::Value>>(
+                    method,
+                    &json!({
+                        "method": method,
+                        "params": params.unwrap_or_default(),
+                    }),
+                )
+                .await?;
 
-        match data.result {
-            Some(res) => Ok(res),
-            None => Err(anyhow::anyhow!("no result")),
-        }
+            match data.error {
+                Some(err) => Err(err.into()),
+                None => Ok(()),
+            }
+        })
+        .await
+    }
+
+    // For methods that return null when there is nothing to return
+    #[instrument(name = "RpcClient::request_optional", skip(self), fields(symbol = self.symbol))]
+    pub async fn request_optional<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Option<Vec<RpcParam>>,
+    ) -> anyhow::Result<Option<T>> {
+        self.timed(method, async {
+            let data = self
+                .post_with_timeout::<RpcResponse<T>>(
+                    method,
+                    &json!({
+                        "method": method,
+                        "params": params.unwrap_or_default(),
+                    }),
+                )
+                .await?;
+
+            match data.error {
+                Some(err) => Err(err.into()),
+                None => Ok(data.result),
+            }
+        })
+        .await
     }
 
     #[instrument(name = "RpcClient::request_batch", skip(self, params), fields(symbol = self.symbol))]
@@ -81,30 +292,28 @@ impl RpcClient {
         method: &str,
         params: Vec<Vec<RpcParam>>,
     ) -> anyhow::Result<Vec<anyhow::Result<T>>> {
-        let client = reqwest::Client::new();
-
-        let response = client
-            .post(&self.endpoint)
-            .headers(self.get_headers()?)
-            .json(
-                &params
-                    .into_iter()
-                    .map(|params| RpcRequest {
-                        params: Some(params),
-                        method: method.to_string(),
-                    })
-                    .collect::<Vec<RpcRequest>>(),
+        let body = serde_json::to_value(
+            params
+                .into_iter()
+                .map(|params| RpcRequest {
+                    params: Some(params),
+                    method: method.to_string(),
+                })
+                .collect::<Vec<RpcRequest>>(),
+        )?;
+        // Errors of single requests in the batch do not fail the call
+        let data = self
+            .timed(
+                method,
+                self.post_with_timeout::<Vec<RpcResponse<T>>>(method, &body),
             )
-            .send()
             .await?;
 
-        let data = response.json::<Vec<RpcResponse<T>>>().await?;
-
         Ok(data
             .into_iter()
             .map(|res| {
                 if let Some(err) = res.error {
-                    Err(anyhow!(err.message))
+                    Err(err.into())
                 } else if let Some(res) = res.result {
                     Ok(res)
                 } else {
@@ -114,11 +323,632 @@ impl RpcClient {
             .collect::<Vec<anyhow::Result<T>>>())
     }
 
+    async fn timed<T, F: Future<Output = anyhow::Result<T>>>(
+        &self,
+        method: &str,
+        call: F,
+    ) -> anyhow::Result<T> {
+        let start = Instant::now();
+        let res = call.await;
+        self.metrics.record(method, start.elapsed(), res.is_err());
+
+        res
+    }
+
+    async fn post_with_timeout<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        body: &serde_json::Value,
+    ) -> anyhow::Result<R> {
+        match tokio::time::timeout(self.request_timeout, self.post_with_retry(method, body)).await {
+            Ok(res) => res,
+            Err(_) => Err(ChainError::Timeout(self.request_timeout).into()),
+        }
+    }
+
+    async fn post_with_retry<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        body: &serde_json::Value,
+    ) -> anyhow::Result<R> {
+        let max_attempts = if RETRYABLE_METHODS.contains(&method) {
+            self.retry.max_attempts.max(1)
+        } else {
+            1
+        };
+
+        let mut attempt = 1;
+        let mut reloaded_cookie = false;
+        loop {
+            match self.post::<R>(body).await {
+                Ok(res) => return Ok(res),
+                Err(RequestError::Unauthorized(err))
+                    if !reloaded_cookie && self.cookie_file.is_some() =>
+                {
+                    reloaded_cookie = true;
+                    if !self.reload_cookie()? {
+                        return Err(err);
+                    }
+                }
+                Err(RequestError::Transient(err)) if attempt < max_attempts => {
+                    let delay = self.retry.delay(attempt);
+                    debug!(
+                        "{} RPC call {} failed (attempt {}/{}): {}; retrying in {:?}",
+                        self.symbol, method, attempt, max_attempts, err, delay
+                    );
+
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    async fn post<R: DeserializeOwned>(&self, body: &serde_json::Value) -> Result<R, RequestError> {
+        // Limits the number of open sockets to the node
+        let _permit = self
+            .connections
+            .acquire()
+            .await
+            .map_err(|err| RequestError::Permanent(err.into()))?;
+
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .headers(self.get_headers().map_err(RequestError::Permanent)?)
+            .json(body)
+            .send()
+            .await
+            .map_err(|err| {
+                if err.is_connect() || err.is_timeout() || err.is_request() {
+                    RequestError::Transient(err.into())
+                } else {
+                    RequestError::Permanent(err.into())
+                }
+            })?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(RequestError::Unauthorized(
+                ChainError::Transport(format!("server responded with {status}")).into(),
+            ));
+        }
+
+        let data = response
+            .bytes()
+            .await
+            .map_err(|err| RequestError::Transient(err.into()))?;
+
+        // The node answers JSON-RPC errors with a server error status too,
+        // so only responses that can't be parsed are worth a retry
+        match serde_json::from_slice::<R>(&data) {
+            Ok(res) => Ok(res),
+            Err(err) if status.is_server_error() => Err(RequestError::Transient(
+                ChainError::Transport(format!("server responded with {status}: {err}")).into(),
+            )),
+            Err(err) => Err(RequestError::Permanent(
+                ChainError::Parse(format!("invalid response with status {status}: {err}")).into(),
+            )),
+        }
+    }
+
+    // Returns whether the cookie changed
+    fn reload_cookie(&self) -> anyhow::Result<bool> {
+        let cookie_file = match &self.cookie_file {
+            Some(cookie_file) => cookie_file,
+            None => return Ok(false),
+        };
+
+        let auth = Self::encode_auth(fs::read(cookie_file)?);
+        let mut cookie = self
+            .cookie
+            .write()
+            .map_err(|_| anyhow!("cookie lock poisoned"))?;
+        if *cookie == auth {
+            return Ok(false);
+        }
+
+        info!("Reloaded cookie of {} chain client", self.symbol);
+        *cookie = auth;
+        Ok(true)
+    }
+
+    fn auth(&self) -> String {
+        match self.cookie.read() {
+            Ok(cookie) => cookie.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    fn encode_auth(auth: Vec<u8>) -> String {
+        format!("Basic {}", BASE64_STANDARD.encode(auth))
+    }
+
     fn get_headers(&self) -> anyhow::Result<HeaderMap> {
         let mut headers = HeaderMap::new();
-        headers.insert("Authorization", HeaderValue::from_str(&self.cookie)?);
+        headers.insert("Authorization", HeaderValue::from_str(&self.auth())?);
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
         Ok(headers)
     }
 }
+
+#[async_trait]
+impl RpcTransport for RpcClient {
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    // Latencies and errors of the calls per method since the client was created
+    fn metrics(&self) -> HashMap<String, MethodStats> {
+        self.metrics.snapshot()
+    }
+
+    async fn request<T: DeserializeOwned + Send>(
+        &self,
+        method: &str,
+        params: Option<Vec<RpcParam>>,
+    ) -> anyhow::Result<T> {
+        RpcClient::request(self, method, params).await
+    }
+
+    async fn request_null(
+        &self,
+        method: &str,
+        params: Option<Vec<RpcParam>>,
+    ) -> anyhow::Result<()> {
+        RpcClient::request_null(self, method, params).await
+    }
+
+    async fn request_optional<T: DeserializeOwned + Send>(
+        &self,
+        method: &str,
+        params: Option<Vec<RpcParam>>,
+    ) -> anyhow::Result<Option<T>> {
+        RpcClient::request_optional(self, method, params).await
+    }
+
+    async fn request_batch<T: DeserializeOwned + Send>(
+        &self,
+        method: &str,
+        params: Vec<Vec<RpcParam>>,
+    ) -> anyhow::Result<Vec<anyhow::Result<T>>> {
+        RpcClient::request_batch(self, method, params).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::Router;
+    use axum::extract::State;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::IntoResponse;
+    use axum::routing::post;
+    use futures::future::join_all;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Clone, Default)]
+    struct MockState {
+        calls: Arc<AtomicUsize>,
+        in_flight: Arc<AtomicUsize>,
+        max_in_flight: Arc<AtomicUsize>,
+        failures: usize,
+        rpc_error: bool,
+        auth: Arc<RwLock<Option<String>>>,
+    }
+
+    async fn handler(State(state): State<MockState>, headers: HeaderMap) -> impl IntoResponse {
+        let expected_auth = state.auth.read().unwrap().clone();
+        if let Some(expected_auth) = expected_auth {
+            let auth = headers
+                .get("Authorization")
+                .map(|auth| auth.to_str().unwrap().to_string());
+            if auth != Some(RpcClient::encode_auth(expected_auth.into_bytes())) {
+                return (StatusCode::UNAUTHORIZED, String::new());
+            }
+        }
+
+        let in_flight = state.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        state.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        state.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        let call = state.calls.fetch_add(1, Ordering::SeqCst);
+        if call < state.failures {
+            return (StatusCode::SERVICE_UNAVAILABLE, String::new());
+        }
+
+        if state.rpc_error {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                json!({
+                    "result": null,
+                    "error": { "code": -5, "message": "No such mempool or blockchain transaction" },
+                })
+                .to_string(),
+            );
+        }
+
+        (
+            StatusCode::OK,
+            json!({ "result": "ok", "error": null }).to_string(),
+        )
+    }
+
+    async fn start_server(
+        failures: usize,
+        rpc_error: bool,
+        max_connections: Option<usize>,
+    ) -> (RpcClient, MockState) {
+        let state = MockState {
+            failures,
+            rpc_error,
+            ..Default::default()
+        };
+        let router = Router::new()
+            .route("/", post(handler))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        let client = RpcClient::new(
+            "BTC".to_string(),
+            Config {
+                host: "127.0.0.1".to_string(),
+                port,
+                user: Some("boltz".to_string()),
+                password: Some("boltz".to_string()),
+                retry: Some(RetryConfig {
+                    max_attempts: 3,
+                    initial_delay_ms: 1,
+                    max_delay_ms: 10,
+                }),
+                max_connections,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        (client, state)
+    }
+
+    #[tokio::test]
+    async fn test_request_retry_transient() {
+        let (client, state) = start_server(2, false, None).await;
+
+        let res = client
+            .request::<String>("getblockchaininfo", None)
+            .await
+            .unwrap();
+        assert_eq!(res, "ok");
+        assert_eq!(state.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_request_retry_exhausted() {
+        let (client, state) = start_server(5, false, None).await;
+
+        let res = client.request::<String>("getblockchaininfo", None).await;
+        assert!(
+            res.err()
+                .unwrap()
+                .to_string()
+                .starts_with("server responded with 503 Service Unavailable")
+        );
+        assert_eq!(state.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_request_no_retry_rpc_error() {
+        let (client, state) = start_server(0, true, None).await;
+
+        let res = client.request::<String>("getrawtransaction", None).await;
+        assert_eq!(
+            res.err().unwrap().to_string(),
+            "No such mempool or blockchain transaction"
+        );
+        assert_eq!(state.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_no_retry_non_idempotent() {
+        let (client, state) = start_server(1, false, None).await;
+
+        assert!(
+            client
+                .request::<String>("sendrawtransaction", None)
+                .await
+                .is_err()
+        );
+        assert_eq!(state.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_no_retry_unlisted() {
+        let (client, state) = start_server(1, false, None).await;
+
+        assert!(
+            client
+                .request::<String>("importdescriptors", None)
+                .await
+                .is_err()
+        );
+        assert_eq!(state.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_batch_retry_transient() {
+        let (client, state) = start_server(1, false, None).await;
+
+        // The mock server answers with a single response instead of a list
+        let res = client
+            .request_batch::<String>("getrawtransaction", vec![vec![]])
+            .await;
+        assert!(res.is_err());
+        assert_eq!(state.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_request_concurrent() {
+        let max_connections = 4;
+        let (client, state) = start_server(0, false, Some(max_connections)).await;
+
+        let results =
+            join_all((0..500).map(|_| client.request::<String>("getblockchaininfo", None))).await;
+        assert!(results.iter().all(|res| res.as_ref().unwrap() == "ok"));
+
+        assert_eq!(state.calls.load(Ordering::SeqCst), 500);
+        assert!(state.max_in_flight.load(Ordering::SeqCst) <= max_connections);
+    }
+
+    #[tokio::test]
+    async fn test_request_shared_pool() {
+        let (client, _) = start_server(0, false, None).await;
+        let cloned = client.clone();
+
+        assert!(Arc::ptr_eq(&client.connections, &cloned.connections));
+        assert_eq!(client.max_connections, DEFAULT_MAX_CONNECTIONS);
+    }
+
+    #[tokio::test]
+    async fn test_request_metrics() {
+        let (client, _) = start_server(0, false, None).await;
+        assert!(client.metrics().is_empty());
+
+        for _ in 0..3 {
+            client
+                .request::<String>("getblockchaininfo", None)
+                .await
+                .unwrap();
+        }
+        client.request_null("ping", None).await.unwrap();
+        // The mock server answers with a single response instead of a list
+        assert!(
+            client
+                .clone()
+                .request_batch::<String>("getrawtransaction", vec![vec![]])
+                .await
+                .is_err()
+        );
+
+        let metrics = client.metrics();
+        assert_eq!(metrics.len(), 3);
+
+        let stats = metrics.get("getblockchaininfo").unwrap();
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.error_count, 0);
+        assert!(stats.p50 > Duration::ZERO);
+        assert!(stats.p99 >= stats.p50);
+
+        let stats = metrics.get("ping").unwrap();
+        assert_eq!(stats.count, 1);
+        assert_eq!(stats.error_count, 0);
+
+        let stats = metrics.get("getrawtransaction").unwrap();
+        assert_eq!(stats.count, 1);
+        assert_eq!(stats.error_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_metrics_rpc_error() {
+        let (client, _) = start_server(0, true, None).await;
+
+        assert!(
+            client
+                .request::<String>("getrawtransaction", None)
+                .await
+                .is_err()
+        );
+
+        let stats = client.metrics().remove("getrawtransaction").unwrap();
+        assert_eq!(stats.count, 1);
+        assert_eq!(stats.error_count, 1);
+    }
+
+    #[test]
+    fn test_max_connections_invalid() {
+        let res = RpcClient::new(
+            "BTC".to_string(),
+            Config {
+                user: Some("boltz".to_string()),
+                password: Some("boltz".to_string()),
+                max_connections: Some(0),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            res.err().unwrap().to_string(),
+            "max connections have to be at least 1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_cookie_reload() {
+        let cookie_file = std::env::temp_dir().join(format!(
+            "boltzr-rpc-cookie-{}-{}",
+            std::process::id(),
+            rand::random::<u64>()
+        ));
+        fs::write(&cookie_file, "__cookie__:first").unwrap();
+
+        let state = MockState {
+            auth: Arc::new(RwLock::new(Some("__cookie__:first".to_string()))),
+            ..Default::default()
+        };
+        let router = Router::new()
+            .route("/", post(handler))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        let client = RpcClient::new(
+            "BTC".to_string(),
+            Config {
+                host: "127.0.0.1".to_string(),
+                port,
+                cookie: Some(cookie_file.to_str().unwrap().to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            client
+                .request::<String>("getblockchaininfo", None)
+                .await
+                .unwrap(),
+            "ok"
+        );
+
+        // The node restarts and rotates its cookie
+        fs::write(&cookie_file, "__cookie__:second").unwrap();
+        *state.auth.write().unwrap() = Some("__cookie__:second".to_string());
+
+        assert_eq!(
+            client
+                .request::<String>("getblockchaininfo", None)
+                .await
+                .unwrap(),
+            "ok"
+        );
+        assert_eq!(state.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            client.auth(),
+            RpcClient::encode_auth(b"__cookie__:second".to_vec())
+        );
+
+        // Clones share the reloaded cookie
+        assert_eq!(client.clone().auth(), client.auth());
+
+        fs::remove_file(cookie_file).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_unauthorized_password() {
+        let (client, state) = start_server(0, false, None).await;
+        *state.auth.write().unwrap() = Some("other:password".to_string());
+
+        let res = client.request::<String>("getblockchaininfo", None).await;
+        assert_eq!(
+            res.err().unwrap().to_string(),
+            "server responded with 401 Unauthorized"
+        );
+        assert_eq!(state.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        // Accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                sockets.push(socket);
+            }
+        });
+
+        let timeout = Duration::from_millis(200);
+        let client = RpcClient::new(
+            "BTC".to_string(),
+            Config {
+                host: "127.0.0.1".to_string(),
+                port,
+                user: Some("boltz".to_string()),
+                password: Some("boltz".to_string()),
+                request_timeout: Some(timeout),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let start = std::time::Instant::now();
+        let err = client
+            .request::<String>("getblockchaininfo", None)
+            .await
+            .err()
+            .unwrap();
+        assert!(start.elapsed() < timeout * 2);
+        assert_eq!(
+            err.downcast_ref::<ChainError>(),
+            Some(&ChainError::Timeout(timeout))
+        );
+
+        let start = std::time::Instant::now();
+        let err = client
+            .request_batch::<String>("getrawtransaction", vec![vec![]])
+            .await
+            .err()
+            .unwrap();
+        assert!(start.elapsed() < timeout * 2);
+        assert_eq!(
+            err.downcast_ref::<ChainError>(),
+            Some(&ChainError::Timeout(timeout))
+        );
+    }
+
+    #[test]
+    fn test_request_timeout_invalid() {
+        assert_eq!(
+            RpcClient::new(
+                "BTC".to_string(),
+                Config {
+                    user: Some("boltz".to_string()),
+                    password: Some("boltz".to_string()),
+                    request_timeout: Some(Duration::ZERO),
+                    ..Default::default()
+                },
+            )
+            .err()
+            .unwrap()
+            .to_string(),
+            "request timeout has to be greater than 0"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let config = RetryConfig {
+            max_attempts: 10,
+            initial_delay_ms: 100,
+            max_delay_ms: 1_000,
+        };
+
+        assert_eq!(config.delay(1), Duration::from_millis(100));
+        assert_eq!(config.delay(2), Duration::from_millis(200));
+        assert_eq!(config.delay(4), Duration::from_millis(800));
+        assert_eq!(config.delay(5), Duration::from_millis(1_000));
+        assert_eq!(config.delay(u32::MAX), Duration::from_millis(1_000));
+    }
+}
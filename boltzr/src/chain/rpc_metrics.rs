@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+// Upper bounds of the buckets are powers of two in milliseconds, the last
+// bucket catches everything slower than 2^(BUCKET_COUNT - 2) ms
+const BUCKET_COUNT: usize = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MethodStats {
+    pub count: u64,
+    pub error_count: u64,
+    pub p50: Duration,
+    pub p99: Duration,
+}
+
+#[derive(Debug, Default)]
+struct LatencyHistogram {
+    buckets: [AtomicU64; BUCKET_COUNT],
+    count: AtomicU64,
+    errors: AtomicU64,
+}
+
+impl LatencyHistogram {
+    fn record(&self, latency: Duration, failed: bool) {
+        self.buckets[Self::bucket(latency)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn stats(&self) -> MethodStats {
+        let buckets = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect::<Vec<_>>();
+
+        MethodStats {
+            count: self.count.load(Ordering::Relaxed),
+            error_count: self.errors.load(Ordering::Relaxed),
+            p50: Self::percentile(&buckets, 0.5),
+            p99: Self::percentile(&buckets, 0.99),
+        }
+    }
+
+    fn bucket(latency: Duration) -> usize {
+        let millis = latency.as_millis();
+        if millis <= 1 {
+            return 0;
+        }
+
+        // Index of the smallest power of two that is at least the latency
+        ((u128::BITS - (millis - 1).leading_zeros()) as usize).min(BUCKET_COUNT - 1)
+    }
+
+    fn upper_bound(bucket: usize) -> Duration {
+        Duration::from_millis(1 << bucket.min(BUCKET_COUNT - 2))
+    }
+
+    // Reports the upper bound of the bucket the percentile falls into
+    fn percentile(buckets: &[u64], percentile: f64) -> Duration {
+        let total = buckets.iter().sum::<u64>();
+        if total == 0 {
+            return Duration::ZERO;
+        }
+
+        let rank = ((total as f64 * percentile).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, count) in buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Self::upper_bound(bucket);
+            }
+        }
+
+        Self::upper_bound(BUCKET_COUNT - 1)
+    }
+}
+
+// Shared by all clones of a client
+#[derive(Debug, Clone, Default)]
+pub struct RpcMetrics {
+    methods: Arc<RwLock<HashMap<String, Arc<LatencyHistogram>>>>,
+}
+
+impl RpcMetrics {
+    pub fn record(&self, method: &str, latency: Duration, failed: bool) {
+        let histogram = match self.histogram(method) {
+            Some(histogram) => histogram,
+            None => {
+                let mut methods = match self.methods.write() {
+                    Ok(methods) => methods,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                methods.entry(method.to_string()).or_default().clone()
+            }
+        };
+
+        histogram.record(latency, failed);
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, MethodStats> {
+        let methods = match self.methods.read() {
+            Ok(methods) => methods,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        methods
+            .iter()
+            .map(|(method, histogram)| (method.clone(), histogram.stats()))
+            .collect()
+    }
+
+    fn histogram(&self, method: &str) -> Option<Arc<LatencyHistogram>> {
+        let methods = match self.methods.read() {
+            Ok(methods) => methods,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        methods.get(method).cloned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(1, 0)]
+    #[case(2, 1)]
+    #[case(3, 2)]
+    #[case(4, 2)]
+    #[case(5, 3)]
+    #[case(1_000, 10)]
+    #[case(65_536, 16)]
+    #[case(65_537, 17)]
+    #[case(u64::MAX, 17)]
+    fn test_bucket(#[case] millis: u64, #[case] expected: usize) {
+        assert_eq!(
+            LatencyHistogram::bucket(Duration::from_millis(millis)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let histogram = LatencyHistogram::default();
+        assert_eq!(
+            histogram.stats(),
+            MethodStats {
+                count: 0,
+                error_count: 0,
+                p50: Duration::ZERO,
+                p99: Duration::ZERO,
+            }
+        );
+
+        for _ in 0..98 {
+            histogram.record(Duration::from_millis(3), false);
+        }
+        histogram.record(Duration::from_millis(100), true);
+        histogram.record(Duration::from_secs(120), true);
+
+        assert_eq!(
+            histogram.stats(),
+            MethodStats {
+                count: 100,
+                error_count: 2,
+                p50: Duration::from_millis(4),
+                p99: Duration::from_millis(128),
+            }
+        );
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let metrics = RpcMetrics::default();
+        metrics.record("getblock", Duration::from_millis(10), false);
+        metrics
+            .clone()
+            .record("getblock", Duration::from_millis(10), true);
+        metrics.record("getrawmempool", Duration::from_millis(1), false);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.len(), 2);
+
+        let stats = snapshot.get("getblock").unwrap();
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.error_count, 1);
+        assert_eq!(stats.p99, Duration::from_millis(16));
+
+        assert_eq!(snapshot.get("getrawmempool").unwrap().count, 1);
+    }
+}
@@ -1,5 +1,8 @@
+use serde::de::IgnoredAny;
 use serde::{Deserialize, Serialize, Serializer};
-use std::fmt::Display;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
 use std::str::FromStr;
 
 #[derive(PartialEq, Debug, Clone, Copy)]
@@ -31,6 +34,7 @@ pub enum RpcParam {
     Str(String),
     Int(i64),
     Float(f64),
+    Json(serde_json::Value),
 }
 
 impl Serialize for RpcParam {
@@ -42,6 +46,7 @@ impl Serialize for RpcParam {
             RpcParam::Str(ref s) => serializer.serialize_str(s),
             RpcParam::Int(num) => serializer.serialize_i64(num),
             RpcParam::Float(num) => serializer.serialize_f64(num),
+            RpcParam::Json(ref value) => value.serialize(serializer),
         }
     }
 }
@@ -52,11 +57,26 @@ pub struct RpcRequest {
     pub params: Option<Vec<RpcParam>>,
 }
 
-#[derive(Deserialize)]
+// https://github.com/bitcoin/bitcoin/blob/master/src/rpc/protocol.h
+pub const RPC_INVALID_ADDRESS_OR_KEY: i64 = -5;
+pub const RPC_VERIFY_ERROR: i64 = -25;
+pub const RPC_VERIFY_REJECTED: i64 = -26;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct RpcError {
+    #[serde(default)]
+    pub code: i64,
     pub message: String,
 }
 
+impl Display for RpcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
 #[derive(Deserialize)]
 pub struct RpcResponse<T> {
     pub result: Option<T>,
@@ -68,6 +88,80 @@ pub struct NetworkInfo {
     pub subversion: String,
 }
 
+impl NetworkInfo {
+    // Parses the major and minor version from a user agent like "/Satoshi:28.0.0/"
+    pub fn version(&self) -> Option<(u64, u64)> {
+        let version = self
+            .subversion
+            .trim_matches('/')
+            .split('/')
+            .next()?
+            .split(':')
+            .nth(1)?;
+
+        let mut parts = version.split('.');
+        Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+    }
+
+    // Descriptor imports were added in v0.21
+    pub fn supports_descriptors(&self) -> bool {
+        match self.version() {
+            Some((0, minor)) => minor >= 21,
+            Some(_) => true,
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DescriptorInfo {
+    // Includes the checksum
+    pub descriptor: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportDescriptorResult {
+    pub success: bool,
+    pub error: Option<RpcError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmartFeeEstimate {
+    // In BTC/kvB
+    pub feerate: Option<f64>,
+    pub errors: Option<Vec<String>>,
+    pub blocks: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeEstimate {
+    pub sat_per_vbyte: f64,
+    pub blocks: u16,
+}
+
+impl TryFrom<SmartFeeEstimate> for FeeEstimate {
+    type Error = crate::chain::ChainError;
+
+    fn try_from(estimate: SmartFeeEstimate) -> Result<Self, Self::Error> {
+        match estimate.feerate {
+            Some(feerate) if feerate > 0.0 => Ok(Self {
+                sat_per_vbyte: feerate * 100_000_000.0 / 1_000.0,
+                blocks: estimate.blocks,
+            }),
+            _ => Err(crate::chain::ChainError::NoEstimate),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BlockHeader {
+    pub hash: String,
+    pub height: u64,
+    // The genesis block has no previous block
+    #[serde(rename = "previousblockhash")]
+    pub previous_block_hash: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ZmqNotification {
     #[serde(rename = "type")]
@@ -75,4 +169,347 @@ pub struct ZmqNotification {
     pub address: String,
 }
 
-pub type RawMempool = Vec<String>;
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawMempoolResponse {
+    TxIds(Vec<String>),
+    // With "verbose" set, the entries are keyed by their transaction id
+    Verbose(BTreeMap<String, IgnoredAny>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RawMempoolResponse")]
+pub struct RawMempool(Vec<String>);
+
+impl From<RawMempoolResponse> for RawMempool {
+    fn from(response: RawMempoolResponse) -> Self {
+        match response {
+            RawMempoolResponse::TxIds(tx_ids) => Self(tx_ids),
+            RawMempoolResponse::Verbose(entries) => Self(entries.into_keys().collect()),
+        }
+    }
+}
+
+impl RawMempool {
+    // Highest fee rate first, ties are broken by the transaction id to keep the order stable
+    pub fn by_fee_rate(entries: HashMap<String, MempoolFeeEntry>) -> Self {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        entries.sort_by(|(a_id, a), (b_id, b)| {
+            b.fee_rate()
+                .total_cmp(&a.fee_rate())
+                .then_with(|| a_id.cmp(b_id))
+        });
+
+        Self(entries.into_iter().map(|(tx_id, _)| tx_id).collect())
+    }
+}
+
+impl From<RawMempool> for Vec<String> {
+    fn from(mempool: RawMempool) -> Self {
+        mempool.0
+    }
+}
+
+impl FromIterator<String> for RawMempool {
+    fn from_iter<T: IntoIterator<Item = String>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Deref for RawMempool {
+    type Target = Vec<String>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for RawMempool {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpentStatus {
+    Unspent,
+    // Id of the spending transaction
+    SpentInMempool(String),
+    SpentOnChain,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VerboseTransaction {
+    // Not set for transactions in the mempool
+    pub confirmations: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct MempoolInfo {
+    // Number of transactions
+    pub size: usize,
+    // Sum of the virtual sizes of all transactions
+    pub bytes: u64,
+}
+
+// All in BTC
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct MempoolEntryFees {
+    pub base: f64,
+    // Including the ancestors or descendants and the transaction itself
+    pub ancestor: f64,
+    pub descendant: f64,
+}
+
+// The verbose mempool is big, so only what is needed to order it is kept of its entries
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct MempoolFeeEntry {
+    pub vsize: u64,
+    pub fees: MempoolFeeEntryFees,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct MempoolFeeEntryFees {
+    // In BTC
+    pub base: f64,
+}
+
+impl MempoolFeeEntry {
+    // In sat/vbyte
+    pub fn fee_rate(&self) -> f64 {
+        if self.vsize == 0 {
+            return 0.0;
+        }
+
+        self.fees.base * 100_000_000.0 / self.vsize as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct MempoolEntry {
+    pub vsize: u64,
+    pub fees: MempoolEntryFees,
+    #[serde(rename = "ancestorcount")]
+    pub ancestor_count: u64,
+    #[serde(rename = "ancestorsize")]
+    pub ancestor_size: u64,
+    #[serde(rename = "descendantcount")]
+    pub descendant_count: u64,
+    #[serde(rename = "descendantsize")]
+    pub descendant_size: u64,
+    // Newer nodes with full RBF do not signal it anymore
+    #[serde(rename = "bip125-replaceable", default)]
+    pub bip125_replaceable: bool,
+}
+
+// In BTC
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct MempoolAcceptFees {
+    pub base: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MempoolAcceptResult {
+    pub allowed: bool,
+    #[serde(rename = "reject-reason")]
+    pub reject_reason: Option<String>,
+    // Only set for transactions that would be accepted
+    pub vsize: Option<u64>,
+    pub fees: Option<MempoolAcceptFees>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BlockchainInfo {
+    pub chain: String,
+    pub blocks: u64,
+    pub headers: u64,
+    #[serde(rename = "initialblockdownload")]
+    pub initial_block_download: bool,
+    // Estimate between 0 and 1
+    #[serde(rename = "verificationprogress")]
+    pub verification_progress: f64,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chain::ChainError;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("/Satoshi:28.0.0/", Some((28, 0)), true)]
+    #[case("/Satoshi:0.21.1/", Some((0, 21)), true)]
+    #[case("/Satoshi:0.20.1/", Some((0, 20)), false)]
+    #[case("/Elements Core:23.2.1/", Some((23, 2)), true)]
+    #[case("/Satoshi:27.1.0/Knots:20240801/", Some((27, 1)), true)]
+    #[case("/Satoshi/", None, false)]
+    #[case("", None, false)]
+    fn test_network_info_version(
+        #[case] subversion: &str,
+        #[case] version: Option<(u64, u64)>,
+        #[case] descriptors: bool,
+    ) {
+        let info = NetworkInfo {
+            subversion: subversion.to_string(),
+        };
+        assert_eq!(info.version(), version);
+        assert_eq!(info.supports_descriptors(), descriptors);
+    }
+
+    #[test]
+    fn test_fee_estimate_from_smart_fee() {
+        let estimate: SmartFeeEstimate =
+            serde_json::from_str("{\"feerate\":0.00002,\"blocks\":2}").unwrap();
+
+        assert_eq!(
+            FeeEstimate::try_from(estimate).unwrap(),
+            FeeEstimate {
+                sat_per_vbyte: 2.0,
+                blocks: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_fee_estimate_from_smart_fee_no_estimate() {
+        let estimate: SmartFeeEstimate = serde_json::from_str(
+            "{\"errors\":[\"Insufficient data or no feerate found\"],\"blocks\":0}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            FeeEstimate::try_from(estimate).err().unwrap(),
+            ChainError::NoEstimate
+        );
+    }
+
+    #[rstest]
+    #[case("[\"aa\",\"bb\"]")]
+    #[case("{\"bb\":{\"vsize\":141,\"depends\":[]},\"aa\":{\"vsize\":110,\"depends\":[\"bb\"]}}")]
+    fn test_raw_mempool_deserialize(#[case] json: &str) {
+        let mempool: RawMempool = serde_json::from_str(json).unwrap();
+        let mut tx_ids = Vec::from(mempool);
+        tx_ids.sort();
+
+        assert_eq!(tx_ids, vec!["aa".to_string(), "bb".to_string()]);
+    }
+
+    #[rstest]
+    #[case("[]")]
+    #[case("{}")]
+    fn test_raw_mempool_deserialize_empty(#[case] json: &str) {
+        let mempool: RawMempool = serde_json::from_str(json).unwrap();
+        assert!(mempool.is_empty());
+    }
+
+    #[test]
+    fn test_raw_mempool_by_fee_rate() {
+        let entries: HashMap<String, MempoolFeeEntry> = serde_json::from_str(
+            "{\"aa\":{\"vsize\":100,\"fees\":{\"base\":0.00001,\"ancestor\":0.00001},\"depends\":[]},\
+            \"bb\":{\"vsize\":200,\"fees\":{\"base\":0.0001},\"wtxid\":\"00\"},\
+            \"cc\":{\"vsize\":50,\"fees\":{\"base\":0.000005}},\
+            \"dd\":{\"vsize\":0,\"fees\":{\"base\":0.0001}}}",
+        )
+        .unwrap();
+        assert_eq!(entries.get("bb").unwrap().fee_rate(), 50.0);
+        assert_eq!(entries.get("dd").unwrap().fee_rate(), 0.0);
+
+        assert_eq!(
+            Vec::from(RawMempool::by_fee_rate(entries)),
+            vec![
+                "bb".to_string(),
+                "aa".to_string(),
+                "cc".to_string(),
+                "dd".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raw_mempool_deserialize_invalid() {
+        assert!(serde_json::from_str::<RawMempool>("\"aa\"").is_err());
+        assert!(serde_json::from_str::<RawMempool>("[1]").is_err());
+    }
+
+    #[test]
+    fn test_mempool_entry_deserialize() {
+        let entry: MempoolEntry = serde_json::from_str(
+            "{\"vsize\":141,\"weight\":561,\"time\":1700000000,\"height\":101,\
+            \"descendantcount\":2,\"descendantsize\":251,\"ancestorcount\":1,\
+            \"ancestorsize\":141,\"wtxid\":\"00\",\"fees\":{\"base\":0.00000282,\
+            \"modified\":0.00000282,\"ancestor\":0.00000282,\"descendant\":0.00000502},\
+            \"depends\":[],\"spentby\":[],\"bip125-replaceable\":true,\"unbroadcast\":false}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            entry,
+            MempoolEntry {
+                vsize: 141,
+                fees: MempoolEntryFees {
+                    base: 0.00000282,
+                    ancestor: 0.00000282,
+                    descendant: 0.00000502,
+                },
+                ancestor_count: 1,
+                ancestor_size: 141,
+                descendant_count: 2,
+                descendant_size: 251,
+                bip125_replaceable: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_mempool_accept_result_deserialize() {
+        let results: Vec<MempoolAcceptResult> = serde_json::from_str(
+            "[{\"txid\":\"00\",\"wtxid\":\"00\",\"allowed\":true,\"vsize\":141,\
+            \"fees\":{\"base\":0.00000282,\"effective-feerate\":0.00002,\
+            \"effective-includes\":[\"00\"]}},\
+            {\"txid\":\"01\",\"wtxid\":\"01\",\"allowed\":false,\
+            \"reject-reason\":\"missing-inputs\"}]",
+        )
+        .unwrap();
+
+        assert_eq!(
+            results,
+            vec![
+                MempoolAcceptResult {
+                    allowed: true,
+                    reject_reason: None,
+                    vsize: Some(141),
+                    fees: Some(MempoolAcceptFees { base: 0.00000282 }),
+                },
+                MempoolAcceptResult {
+                    allowed: false,
+                    reject_reason: Some("missing-inputs".to_string()),
+                    vsize: None,
+                    fees: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_blockchain_info_deserialize() {
+        let info: BlockchainInfo = serde_json::from_str(
+            "{\"chain\":\"main\",\"blocks\":800000,\"headers\":870000,\
+            \"bestblockhash\":\"00\",\"difficulty\":1,\"time\":1700000000,\
+            \"mediantime\":1700000000,\"verificationprogress\":0.42,\
+            \"initialblockdownload\":true,\"chainwork\":\"00\",\
+            \"size_on_disk\":1,\"pruned\":false,\"warnings\":\"\"}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            info,
+            BlockchainInfo {
+                chain: "main".to_string(),
+                blocks: 800000,
+                headers: 870000,
+                initial_block_download: true,
+                verification_progress: 0.42,
+            }
+        );
+    }
+}
@@ -1,12 +1,17 @@
 use crate::chain::{Config, types::ZmqNotification};
 use crate::chain::{
     types::Type,
-    utils::{Transaction, parse_transaction},
+    utils::{Block, Transaction, parse_block, parse_transaction},
 };
+use std::time::Duration;
 use tokio::sync::broadcast::{self, Sender};
-use tracing::{debug, error, warn};
+use tokio::sync::mpsc;
+use tracing::{debug, error, trace, warn};
 use zeromq::{Socket, SocketRecv, ZmqError, ZmqMessage};
 
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const SUBSCRIPTION_BUFFER: usize = 1024;
+
 #[derive(Debug, Clone)]
 pub struct ZmqClient {
     client_type: Type,
@@ -26,10 +31,8 @@ impl ZmqClient {
     }
 
     pub async fn connect(&self, notifications: Vec<ZmqNotification>) -> anyhow::Result<()> {
-        let raw_tx = match Self::find_notification("pubrawtx", notifications.clone()) {
-            Some(data) => data,
-            None => return Err(anyhow::anyhow!("pubrawtx ZMQ missing")),
-        };
+        let raw_tx =
+            self.subscription_address(self.config.zmq_raw_tx.clone(), "pubrawtx", notifications)?;
 
         let tx_sender = self.tx_sender.clone();
         let client_type = self.client_type;
@@ -58,16 +61,145 @@ impl ZmqClient {
         Ok(())
     }
 
+    pub fn subscribe_raw_tx(
+        &self,
+        notifications: Vec<ZmqNotification>,
+    ) -> anyhow::Result<mpsc::Receiver<Transaction>> {
+        let address =
+            self.subscription_address(self.config.zmq_raw_tx.clone(), "pubrawtx", notifications)?;
+
+        let client_type = self.client_type;
+        Ok(
+            self.subscribe_with_reconnect(address, "rawtx", move |data| {
+                parse_transaction(&client_type, data)
+            }),
+        )
+    }
+
+    pub fn subscribe_raw_block(
+        &self,
+        notifications: Vec<ZmqNotification>,
+    ) -> anyhow::Result<mpsc::Receiver<Block>> {
+        let address = self.subscription_address(
+            self.config.zmq_raw_block.clone(),
+            "pubrawblock",
+            notifications,
+        )?;
+
+        let client_type = self.client_type;
+        Ok(
+            self.subscribe_with_reconnect(address, "rawblock", move |data| {
+                let block = parse_block(&client_type, data)?;
+                trace!("Received {} block {}", client_type, block.hash());
+                Ok(block)
+            }),
+        )
+    }
+
+    fn subscription_address(
+        &self,
+        configured: Option<String>,
+        notification: &str,
+        notifications: Vec<ZmqNotification>,
+    ) -> anyhow::Result<String> {
+        let address = match configured {
+            Some(address) => address,
+            None => match Self::find_notification(notification, notifications) {
+                Some(notification) => notification.address,
+                None => return Err(anyhow::anyhow!("{} ZMQ missing", notification)),
+            },
+        };
+
+        Ok(self.replace_zmq_address_wildcard(&address))
+    }
+
+    // Keeps reconnecting to the socket until the receiver is dropped
+    fn subscribe_with_reconnect<T, F>(
+        &self,
+        address: String,
+        subscription: &'static str,
+        parse: F,
+    ) -> mpsc::Receiver<T>
+    where
+        T: Send + 'static,
+        F: Fn(&[u8]) -> anyhow::Result<T> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        let client_type = self.client_type;
+
+        tokio::spawn(async move {
+            while !sender.is_closed() {
+                debug!(
+                    "Connecting to {} {} ZMQ at {}",
+                    subscription, client_type, address
+                );
+
+                let mut socket = zeromq::SubSocket::new();
+                let connected = match socket.connect(&address).await {
+                    Ok(_) => socket.subscribe(subscription).await,
+                    Err(err) => Err(err),
+                };
+
+                match connected {
+                    Ok(_) => loop {
+                        let msg = tokio::select! {
+                            msg = socket.recv() => msg,
+                            _ = sender.closed() => return,
+                        };
+
+                        let msg = match msg {
+                            Ok(msg) => msg,
+                            Err(err) => {
+                                warn!(
+                                    "{} {} ZMQ subscription disconnected: {}",
+                                    client_type, subscription, err
+                                );
+                                break;
+                            }
+                        };
+
+                        let data = match msg.get(1) {
+                            Some(data) => data,
+                            None => continue,
+                        };
+                        match parse(&data[..]) {
+                            Ok(parsed) => {
+                                if sender.send(parsed).await.is_err() {
+                                    return;
+                                }
+                            }
+                            Err(err) => {
+                                warn!(
+                                    "{} ZMQ client could not parse {}: {}",
+                                    client_type, subscription, err
+                                );
+                            }
+                        }
+                    },
+                    Err(err) => {
+                        warn!(
+                            "Could not connect to {} {} ZMQ: {}",
+                            client_type, subscription, err
+                        );
+                    }
+                };
+
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+
+        receiver
+    }
+
     async fn subscribe<F>(
         &self,
-        notification: ZmqNotification,
+        address: String,
         subscription: &str,
         handler: F,
     ) -> Result<(), ZmqError>
     where
         F: Fn(ZmqMessage) + Send + 'static,
     {
-        let address = self.replace_zmq_address_wildcard(&notification.address);
         debug!(
             "Connecting to {} {} ZMQ at {}",
             subscription, self.client_type, address
@@ -108,3 +240,54 @@ impl ZmqClient {
             .find(|elem| elem.notification_type == to_find)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::*;
+
+    fn notifications() -> Vec<ZmqNotification> {
+        vec![ZmqNotification {
+            notification_type: "pubrawtx".to_string(),
+            address: "tcp://0.0.0.0:29000".to_string(),
+        }]
+    }
+
+    #[rstest]
+    #[case(None, "tcp://127.0.0.1:29000")]
+    #[case(Some("tcp://0.0.0.0:29001"), "tcp://127.0.0.1:29001")]
+    fn test_subscription_address(#[case] configured: Option<&str>, #[case] expected: &str) {
+        let client = ZmqClient::new(
+            Type::Bitcoin,
+            Config {
+                host: "127.0.0.1".to_string(),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            client
+                .subscription_address(
+                    configured.map(|address| address.to_string()),
+                    "pubrawtx",
+                    notifications(),
+                )
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_subscription_address_missing() {
+        let client = ZmqClient::new(Type::Bitcoin, Config::default());
+
+        assert_eq!(
+            client
+                .subscription_address(None, "pubrawblock", notifications())
+                .err()
+                .unwrap()
+                .to_string(),
+            "pubrawblock ZMQ missing"
+        );
+    }
+}
@@ -10,6 +10,7 @@ pub struct CurrencyConfig {
     pub chain: Option<crate::chain::Config>,
     pub cln: Option<crate::lightning::cln::Config>,
     pub lnd: Option<crate::lightning::lnd::Config>,
+    pub eclair: Option<crate::lightning::eclair::Config>,
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
@@ -62,7 +63,11 @@ pub struct GlobalConfig {
     pub marking: Option<crate::service::MarkingsConfig>,
 
     pub cache: Option<crate::cache::CacheConfig>,
+    // Bounds the in memory cache that is used when no Redis is configured
+    #[serde(rename = "memoryCacheMaxEntries")]
+    pub memory_cache_max_entries: Option<usize>,
     pub historical: Option<crate::service::HistoricalConfig>,
+    pub gossip: Option<crate::service::LightningInfoConfig>,
 
     pub backup: Option<crate::backup::Config>,
     pub notification: Option<crate::notifications::Config>,
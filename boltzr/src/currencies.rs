@@ -8,6 +8,7 @@ use crate::db::helpers::keys::KeysHelper;
 use crate::db::helpers::offer::OfferHelperDatabase;
 use crate::db::helpers::reverse_swap::ReverseSwapHelperDatabase;
 use crate::lightning::cln::Cln;
+use crate::lightning::eclair::Eclair;
 use crate::lightning::lnd::Lnd;
 use crate::wallet;
 use crate::wallet::Wallet;
@@ -17,6 +18,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, warn};
 
@@ -29,6 +31,7 @@ pub struct Currency {
     pub chain: Option<Arc<Box<dyn crate::chain::Client + Send + Sync>>>,
     pub cln: Option<Cln>,
     pub lnd: Option<Lnd>,
+    pub eclair: Option<Eclair>,
 }
 
 pub type Currencies = Arc<HashMap<String, Currency>>;
@@ -74,12 +77,17 @@ pub async fn connect_nodes<K: KeysHelper>(
                         )?),
                         chain: match currency.chain {
                             Some(config) => {
+                                let connect_max_wait = config.connect_max_wait();
+
                                 #[allow(clippy::manual_map)]
-                                match connect_client(ChainClient::new(
-                                    crate::chain::types::Type::Bitcoin,
-                                    currency.symbol.clone(),
-                                    config,
-                                ))
+                                match connect_chain_client(
+                                    ChainClient::new(
+                                        crate::chain::types::Type::Bitcoin,
+                                        currency.symbol.clone(),
+                                        config,
+                                    ),
+                                    connect_max_wait,
+                                )
                                 .await
                                 {
                                     Some(client) => Some(Arc::new(Box::new(client))),
@@ -116,6 +124,12 @@ pub async fn connect_nodes<K: KeysHelper>(
                             }
                             None => None,
                         },
+                        eclair: match currency.eclair {
+                            Some(config) => {
+                                connect_client(Eclair::new(&currency.symbol, config)).await
+                            }
+                            None => None,
+                        },
                     },
                 );
             }
@@ -139,6 +153,7 @@ pub async fn connect_nodes<K: KeysHelper>(
                 network,
                 cln: None,
                 lnd: None,
+                eclair: None,
                 wallet: Arc::new(wallet::Elements::new(
                     network,
                     &seed,
@@ -156,6 +171,37 @@ pub async fn connect_nodes<K: KeysHelper>(
     Ok((network, Arc::new(curs), offer_subscriptions))
 }
 
+// Chain clients can wait for their node to come up, which can take a while when
+// it is started at the same time as the backend
+async fn connect_chain_client(
+    client: anyhow::Result<ChainClient>,
+    max_wait: Option<Duration>,
+) -> Option<ChainClient> {
+    let max_wait = match max_wait {
+        Some(max_wait) => max_wait,
+        None => return connect_client(client).await,
+    };
+
+    match client {
+        Ok(mut client) => match client.connect_with_retry(max_wait).await {
+            Ok(_) => Some(client),
+            Err(err) => {
+                error!(
+                    "Could not connect to {} {}: {}",
+                    client.symbol(),
+                    client.kind(),
+                    err
+                );
+                None
+            }
+        },
+        Err(err) => {
+            error!("Could not create client: {}", err);
+            None
+        }
+    }
+}
+
 async fn connect_client<T: BaseClient>(client: anyhow::Result<T>) -> Option<T> {
     match client {
         Ok(mut client) => match client.connect().await {
@@ -177,11 +223,24 @@ async fn connect_client<T: BaseClient>(client: anyhow::Result<T>) -> Option<T> {
     }
 }
 
+// Addresses have to be for the network of the currency
+pub fn address_to_script_pubkey(
+    currencies: &Currencies,
+    symbol: &str,
+    address: &str,
+) -> anyhow::Result<Vec<u8>> {
+    match currencies.get(symbol) {
+        Some(currency) => currency.wallet.decode_address(address),
+        None => Err(anyhow!("unknown currency: {}", symbol)),
+    }
+}
+
 fn parse_network(network: Option<String>) -> anyhow::Result<wallet::Network> {
     match network {
         Some(network) => match network.to_lowercase().as_str() {
             "mainnet" => Ok(wallet::Network::Mainnet),
             "testnet" => Ok(wallet::Network::Testnet),
+            "testnet4" => Ok(wallet::Network::Testnet4),
             "signet" => Ok(wallet::Network::Signet),
             "regtest" => Ok(wallet::Network::Regtest),
             &_ => Err(anyhow::anyhow!("invalid network: {}", network)),
@@ -195,15 +254,47 @@ fn parse_network(network: Option<String>) -> anyhow::Result<wallet::Network> {
 
 #[cfg(test)]
 mod test {
-    use crate::currencies::parse_network;
-    use crate::wallet::Network;
+    use crate::currencies::{Currencies, Currency, address_to_script_pubkey, parse_network};
+    use crate::wallet::{Bitcoin, Network};
+    use alloy::hex;
+    use bip39::Mnemonic;
     use rstest::*;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    fn currencies() -> Currencies {
+        Arc::new(HashMap::from([(
+            "BTC".to_string(),
+            Currency {
+                network: Network::Regtest,
+                wallet: Arc::new(
+                    Bitcoin::new(
+                        Network::Regtest,
+                        &Mnemonic::from_str(
+                            "test test test test test test test test test test test junk",
+                        )
+                        .unwrap()
+                        .to_seed(""),
+                        "m/0/0".to_string(),
+                    )
+                    .unwrap(),
+                ),
+                chain: None,
+                cln: None,
+                lnd: None,
+                eclair: None,
+            },
+        )]))
+    }
 
     #[rstest]
     #[case(Some(String::from("mainnet")), Network::Mainnet)]
     #[case(Some(String::from("MAINNET")), Network::Mainnet)]
     #[case(Some(String::from("mAiNnEt")), Network::Mainnet)]
     #[case(Some(String::from("testnet")), Network::Testnet)]
+    #[case(Some(String::from("testnet4")), Network::Testnet4)]
+    #[case(Some(String::from("signet")), Network::Signet)]
     #[case(Some(String::from("regtest")), Network::Regtest)]
     #[case(None, Network::Regtest)]
     fn test_parse_network(#[case] network: Option<String>, #[case] expected: Network) {
@@ -220,4 +311,39 @@ mod test {
             format!("invalid network: {network}")
         );
     }
+
+    #[test]
+    fn test_address_to_script_pubkey() {
+        assert_eq!(
+            hex::encode(
+                address_to_script_pubkey(
+                    &currencies(),
+                    "BTC",
+                    "bcrt1qe8tncvy59ea8yzccjlgdcdgy0susy6rvsyfvth"
+                )
+                .unwrap()
+            ),
+            "0014c9d73c30942e7a720b1897d0dc35047c3902686c"
+        );
+    }
+
+    #[rstest]
+    #[case("BTC", "bc1qe8tncvy59ea8yzccjlgdcdgy0susy6rvcttj8d", "invalid network")]
+    #[case(
+        "L-BTC",
+        "bcrt1qe8tncvy59ea8yzccjlgdcdgy0susy6rvsyfvth",
+        "unknown currency: L-BTC"
+    )]
+    fn test_address_to_script_pubkey_invalid(
+        #[case] symbol: &str,
+        #[case] address: &str,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            address_to_script_pubkey(&currencies(), symbol, address)
+                .unwrap_err()
+                .to_string(),
+            expected
+        );
+    }
 }
@@ -10,16 +10,17 @@ use crate::grpc::service::boltzr::{
     Bolt11Invoice, Bolt12Invoice, Bolt12Offer, CreateWebHookRequest, CreateWebHookResponse,
     DecodeInvoiceOrOfferRequest, DecodeInvoiceOrOfferResponse, Feature, GetInfoRequest,
     GetInfoResponse, GetMessagesRequest, GetMessagesResponse, IsMarkedRequest, IsMarkedResponse,
-    LogLevel, ScanMempoolRequest, ScanMempoolResponse, SendMessageRequest, SendMessageResponse,
-    SendSwapUpdateRequest, SendSwapUpdateResponse, SendWebHookRequest, SendWebHookResponse,
-    SetLogLevelRequest, SetLogLevelResponse, SignEvmRefundRequest, SignEvmRefundResponse,
-    StartWebHookRetriesRequest, StartWebHookRetriesResponse, SwapUpdate, SwapUpdateRequest,
-    SwapUpdateResponse, bolt11_invoice, bolt12_invoice, decode_invoice_or_offer_response,
+    LogLevel, RefreshLightningGossipRequest, RefreshLightningGossipResponse, ScanMempoolRequest,
+    ScanMempoolResponse, SendMessageRequest, SendMessageResponse, SendSwapUpdateRequest,
+    SendSwapUpdateResponse, SendWebHookRequest, SendWebHookResponse, SetLogLevelRequest,
+    SetLogLevelResponse, SignEvmRefundRequest, SignEvmRefundResponse, StartWebHookRetriesRequest,
+    StartWebHookRetriesResponse, SwapUpdate, SwapUpdateRequest, SwapUpdateResponse, bolt11_invoice,
+    bolt12_invoice, decode_invoice_or_offer_response,
 };
 use crate::grpc::status_fetcher::StatusFetcher;
 use crate::lightning::invoice::Invoice;
 use crate::notifications::NotificationClient;
-use crate::service::Service;
+use crate::service::{LookupError, Service};
 use crate::swap::manager::SwapManager;
 use crate::tracing_setup::ReloadHandler;
 use crate::webhook::status_caller::StatusCaller;
@@ -711,6 +712,27 @@ where
             transactions: transaction_serialized,
         }))
     }
+
+    #[instrument(name = "grpc::refresh_lightning_gossip", skip_all)]
+    async fn refresh_lightning_gossip(
+        &self,
+        request: Request<RefreshLightningGossipRequest>,
+    ) -> Result<Response<RefreshLightningGossipResponse>, Status> {
+        extract_parent_context(&request);
+
+        let symbol = request.into_inner().symbol;
+        match self.service.lightning_info.refresh_now(&symbol).await {
+            Ok(_) => Ok(Response::new(RefreshLightningGossipResponse {})),
+            Err(err) => Err(Status::new(
+                match err.downcast_ref::<LookupError>() {
+                    Some(LookupError::UnknownSymbol(_)) => Code::NotFound,
+                    Some(LookupError::RefreshInProgress(_)) => Code::Aborted,
+                    _ => Code::Internal,
+                },
+                err.to_string(),
+            )),
+        }
+    }
 }
 
 fn extract_parent_context<T>(request: &Request<T>) {
@@ -742,8 +764,8 @@ mod test {
     use crate::grpc::service::boltzr::sign_evm_refund_request::Contract;
     use crate::grpc::service::boltzr::{
         CreateWebHookRequest, CreateWebHookResponse, GetInfoRequest, GetInfoResponse,
-        SendWebHookRequest, SendWebHookResponse, SignEvmRefundRequest, StartWebHookRetriesRequest,
-        StartWebHookRetriesResponse,
+        RefreshLightningGossipRequest, SendWebHookRequest, SendWebHookResponse,
+        SignEvmRefundRequest, StartWebHookRetriesRequest, StartWebHookRetriesResponse,
     };
     use crate::grpc::status_fetcher::StatusFetcher;
     use crate::notifications::commands::Commands;
@@ -1109,6 +1131,21 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_refresh_lightning_gossip_unknown_symbol() {
+        let (_, svc) = make_service();
+
+        let err = svc
+            .refresh_lightning_gossip(Request::new(RefreshLightningGossipRequest {
+                symbol: "BTC".to_string(),
+            }))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), Code::NotFound);
+        assert_eq!(err.message(), "no gossip source for BTC");
+    }
+
     fn make_service() -> (
         CancellationToken,
         BoltzService<MockManager, crate::notifications::mattermost::Client<Commands>>,
@@ -1122,14 +1159,18 @@ mod test {
             token.clone(),
             BoltzService::new(
                 ReloadHandler::new(),
-                Arc::new(Service::new(
-                    Arc::new(MockSwapHelper::new()),
-                    Arc::new(MockChainSwapHelper::new()),
-                    Arc::new(HashMap::new()),
-                    None,
-                    None,
-                    Cache::Memory(MemCache::new()),
-                )),
+                Arc::new(
+                    Service::new(
+                        Arc::new(MockSwapHelper::new()),
+                        Arc::new(MockChainSwapHelper::new()),
+                        Arc::new(HashMap::new()),
+                        None,
+                        None,
+                        None,
+                        Cache::Memory(MemCache::new()),
+                    )
+                    .unwrap(),
+                ),
                 Arc::new(make_mock_manager()),
                 StatusFetcher::new(),
                 status_tx,
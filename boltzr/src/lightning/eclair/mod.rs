@@ -0,0 +1,410 @@
+use crate::chain::BaseClient;
+use crate::lightning::eclair::types::{
+    ChannelDesc, ChannelUpdate, GetInfoResponse, NodeAnnouncement, NodeInfo,
+};
+use alloy::hex;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use bytes::Bytes;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{info, instrument};
+
+pub mod types;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+pub struct Config {
+    pub host: String,
+    pub port: u16,
+
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Eclair {
+    symbol: String,
+    endpoint: String,
+    password: String,
+    client: reqwest::Client,
+}
+
+impl Eclair {
+    #[instrument(name = "Eclair::new", skip(config))]
+    pub fn new(symbol: &str, config: Config) -> anyhow::Result<Self> {
+        Ok(Self {
+            symbol: symbol.to_string(),
+            endpoint: format!("http://{}:{}", config.host, config.port),
+            password: config.password,
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()?,
+        })
+    }
+
+    pub async fn get_info(&self) -> anyhow::Result<GetInfoResponse> {
+        self.request("getinfo", &[]).await
+    }
+
+    pub async fn nodes(&self) -> anyhow::Result<Vec<NodeAnnouncement>> {
+        self.request("nodes", &[]).await
+    }
+
+    pub async fn node(&self, node_id: &[u8]) -> anyhow::Result<NodeAnnouncement> {
+        let info: NodeInfo = self
+            .request("node", &[("nodeId", hex::encode(node_id))])
+            .await?;
+        Ok(info.announcement)
+    }
+
+    // Eclair responds with "not found" for nodes it does not know about,
+    // every other failure is an error
+    pub async fn find_node(&self, node_id: &[u8]) -> anyhow::Result<Option<NodeAnnouncement>> {
+        let (status, data) = self
+            .send("node", &[("nodeId", hex::encode(node_id))])
+            .await?;
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(Self::parse_error(status, &data));
+        }
+
+        Ok(Some(
+            serde_json::from_slice::<NodeInfo>(&data)?.announcement,
+        ))
+    }
+
+    // The "channels" endpoint only lists the channels of the node itself,
+    // the public ones of the whole graph are in "allchannels"
+    pub async fn channels(&self) -> anyhow::Result<Vec<ChannelDesc>> {
+        self.request("allchannels", &[]).await
+    }
+
+    // Both directions of a channel have their own update
+    pub async fn channel_updates(&self) -> anyhow::Result<Vec<ChannelUpdate>> {
+        self.request("allupdates", &[]).await
+    }
+
+    #[instrument(name = "Eclair::request", skip(self, params), fields(symbol = self.symbol))]
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[(&str, String)],
+    ) -> anyhow::Result<T> {
+        let (status, data) = self.send(method, params).await?;
+        if !status.is_success() {
+            return Err(Self::parse_error(status, &data));
+        }
+
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    async fn send(
+        &self,
+        method: &str,
+        params: &[(&str, String)],
+    ) -> anyhow::Result<(StatusCode, Bytes)> {
+        let response = self
+            .client
+            .post(format!("{}/{}", self.endpoint, method))
+            .basic_auth("", Some(&self.password))
+            .form(params)
+            .send()
+            .await?;
+
+        let status = response.status();
+        Ok((status, response.bytes().await?))
+    }
+
+    fn parse_error(status: StatusCode, data: &[u8]) -> anyhow::Error {
+        match serde_json::from_slice::<ErrorResponse>(data) {
+            Ok(res) => anyhow!("{}", res.error),
+            Err(_) => anyhow!("Eclair responded with {}", status),
+        }
+    }
+}
+
+#[async_trait]
+impl BaseClient for Eclair {
+    fn kind(&self) -> String {
+        "Eclair".to_string()
+    }
+
+    fn symbol(&self) -> String {
+        self.symbol.clone()
+    }
+
+    async fn connect(&mut self) -> anyhow::Result<()> {
+        let info = self.get_info().await?;
+        info!(
+            "Connected to {} Eclair {} ({})",
+            self.symbol,
+            info.version,
+            if !info.alias.is_empty() {
+                info.alias
+            } else {
+                info.node_id
+            }
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use axum::extract::{Form, Path, State};
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::IntoResponse;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use base64::Engine;
+    use base64::prelude::BASE64_STANDARD;
+    use serde_json::{Value, json};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    const PASSWORD: &str = "eclair";
+
+    // Canned responses per method of the API
+    #[derive(Clone, Default)]
+    pub struct MockEclair {
+        pub responses: Arc<HashMap<String, Value>>,
+    }
+
+    async fn handler(
+        State(state): State<MockEclair>,
+        Path(method): Path<String>,
+        headers: HeaderMap,
+        Form(params): Form<HashMap<String, String>>,
+    ) -> impl IntoResponse {
+        let auth = headers
+            .get("Authorization")
+            .map(|auth| auth.to_str().unwrap().to_string());
+        if auth
+            != Some(format!(
+                "Basic {}",
+                BASE64_STANDARD.encode(format!(":{PASSWORD}"))
+            ))
+        {
+            return (StatusCode::UNAUTHORIZED, Json(json!({})));
+        }
+
+        let response = match params.get("nodeId") {
+            Some(node_id) => match state.responses.get(&format!("{method}/{node_id}")) {
+                Some(response) => Some(response),
+                None => {
+                    return (
+                        StatusCode::NOT_FOUND,
+                        Json(json!({ "error": format!("node {node_id} not found") })),
+                    );
+                }
+            },
+            None => state.responses.get(&method),
+        };
+
+        match response {
+            Some(response) => (StatusCode::OK, Json(response.clone())),
+            None => (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": format!("no response for {method}") })),
+            ),
+        }
+    }
+
+    pub async fn start_server(responses: HashMap<String, Value>) -> Eclair {
+        let router = Router::new()
+            .route("/{method}", post(handler))
+            .with_state(MockEclair {
+                responses: Arc::new(responses),
+            });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        Eclair::new(
+            "BTC",
+            Config {
+                host: "127.0.0.1".to_string(),
+                port,
+                password: PASSWORD.to_string(),
+            },
+        )
+        .unwrap()
+    }
+
+    pub fn node_announcement(node_id: &[u8], alias: &str, rgb_color: &str) -> Value {
+        json!({
+            "signature": "3045",
+            "features": {},
+            "timestamp": { "iso": "2024-01-01T00:00:00Z", "unix": 1704067200 },
+            "nodeId": hex::encode(node_id),
+            "rgbColor": rgb_color,
+            "alias": alias,
+            "addresses": [],
+        })
+    }
+
+    pub fn channel_update(short_channel_id: &str, is_node1: bool, is_enabled: bool) -> Value {
+        json!({
+            "signature": "3045",
+            "chainHash": "06226e46111a0b59caaf126043eb5bbf28c34f3a5e332a1fc7b2b73cf188910f",
+            "shortChannelId": short_channel_id,
+            "timestamp": { "iso": "2024-01-01T00:00:00Z", "unix": 1704067200 },
+            "messageFlags": { "dontForward": false },
+            "channelFlags": { "isEnabled": is_enabled, "isNode1": is_node1 },
+            "cltvExpiryDelta": 144,
+            "htlcMinimumMsat": 1,
+            "feeBaseMsat": 1000,
+            "feeProportionalMillionths": 100,
+            "htlcMaximumMsat": 990000000,
+            "tlvStream": {},
+        })
+    }
+
+    #[tokio::test]
+    async fn test_connect() {
+        let mut eclair = start_server(HashMap::from([(
+            "getinfo".to_string(),
+            json!({
+                "version": "0.11.0",
+                "nodeId": hex::encode([2; 33]),
+                "alias": "Boltz",
+            }),
+        )]))
+        .await;
+
+        assert_eq!(eclair.kind(), "Eclair");
+        assert_eq!(eclair.symbol(), "BTC");
+        eclair.connect().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_nodes() {
+        let eclair = start_server(HashMap::from([(
+            "nodes".to_string(),
+            json!([
+                node_announcement(&[2; 33], "Boltz\u{0}\u{0}", "#49daaa"),
+                node_announcement(&[3; 33], "", "#000000"),
+            ]),
+        )]))
+        .await;
+
+        let nodes = eclair.nodes().await.unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].node_id, hex::encode([2; 33]));
+        assert_eq!(nodes[0].alias(), Some("Boltz".to_string()));
+        assert_eq!(nodes[0].color(), Some(vec![0x49, 0xda, 0xaa]));
+        assert_eq!(nodes[0].timestamp.unix, 1704067200);
+        assert_eq!(nodes[1].alias(), None);
+    }
+
+    #[tokio::test]
+    async fn test_node() {
+        let eclair = start_server(HashMap::from([(
+            format!("node/{}", hex::encode([2; 33])),
+            json!({
+                "announcement": node_announcement(&[2; 33], "Boltz", "#49daaa"),
+                "activeChannels": 1,
+                "totalCapacity": 100000,
+            }),
+        )]))
+        .await;
+
+        let node = eclair.node(&[2; 33]).await.unwrap();
+        assert_eq!(node.alias(), Some("Boltz".to_string()));
+
+        assert_eq!(
+            eclair.node(&[3; 33]).await.err().unwrap().to_string(),
+            format!("node {} not found", hex::encode([3; 33]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_node() {
+        let mut eclair = start_server(HashMap::from([(
+            format!("node/{}", hex::encode([2; 33])),
+            json!({
+                "announcement": node_announcement(&[2; 33], "Boltz", "#49daaa"),
+                "activeChannels": 1,
+                "totalCapacity": 100000,
+            }),
+        )]))
+        .await;
+
+        let node = eclair.find_node(&[2; 33]).await.unwrap().unwrap();
+        assert_eq!(node.alias(), Some("Boltz".to_string()));
+        assert!(eclair.find_node(&[3; 33]).await.unwrap().is_none());
+
+        eclair.password = "invalid".to_string();
+        assert_eq!(
+            eclair.find_node(&[2; 33]).await.err().unwrap().to_string(),
+            "Eclair responded with 401 Unauthorized"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_channels() {
+        let eclair = start_server(HashMap::from([
+            (
+                "allchannels".to_string(),
+                json!([{
+                    "shortChannelId": "1x1x1",
+                    "a": hex::encode([2; 33]),
+                    "b": hex::encode([3; 33]),
+                }]),
+            ),
+            (
+                "allupdates".to_string(),
+                json!([
+                    channel_update("1x1x1", true, true),
+                    channel_update("1x1x1", false, false),
+                ]),
+            ),
+        ]))
+        .await;
+
+        assert_eq!(
+            eclair.channels().await.unwrap(),
+            vec![ChannelDesc {
+                short_channel_id: "1x1x1".to_string(),
+                a: hex::encode([2; 33]),
+                b: hex::encode([3; 33]),
+            }]
+        );
+
+        let updates = eclair.channel_updates().await.unwrap();
+        assert_eq!(updates.len(), 2);
+        assert!(updates[0].channel_flags.is_node1);
+        assert!(updates[0].channel_flags.is_enabled);
+        assert!(!updates[1].channel_flags.is_node1);
+        assert_eq!(updates[1].fee_base_msat, 1000);
+        assert_eq!(updates[1].fee_proportional_millionths, 100);
+        assert_eq!(updates[1].cltv_expiry_delta, 144);
+        assert_eq!(updates[1].htlc_maximum_msat, Some(990000000));
+    }
+
+    #[tokio::test]
+    async fn test_request_unauthorized() {
+        let mut eclair = start_server(HashMap::new()).await;
+        eclair.password = "wrong".to_string();
+
+        assert_eq!(
+            eclair.nodes().await.err().unwrap().to_string(),
+            "Eclair responded with 401 Unauthorized"
+        );
+    }
+}
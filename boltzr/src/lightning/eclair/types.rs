@@ -0,0 +1,153 @@
+use alloy::hex;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GetInfoResponse {
+    pub version: String,
+    #[serde(rename = "nodeId")]
+    pub node_id: String,
+    #[serde(default)]
+    pub alias: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Timestamp {
+    pub unix: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct NodeAnnouncement {
+    #[serde(rename = "nodeId")]
+    pub node_id: String,
+    #[serde(default)]
+    pub alias: String,
+    #[serde(rename = "rgbColor", default)]
+    pub rgb_color: String,
+    #[serde(default)]
+    pub timestamp: Timestamp,
+}
+
+impl NodeAnnouncement {
+    // The alias field of announcements is 32 bytes long and Eclair keeps the NUL padding
+    pub fn alias(&self) -> Option<String> {
+        let alias = self.alias.trim_end_matches('\0');
+        if alias.is_empty() {
+            return None;
+        }
+
+        Some(alias.to_string())
+    }
+
+    // Eclair formats colors as "#rrggbb" instead of returning the raw bytes
+    pub fn color(&self) -> Option<Vec<u8>> {
+        let color = self.rgb_color.strip_prefix('#')?;
+        hex::decode(color).ok().filter(|color| color.len() == 3)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct NodeInfo {
+    pub announcement: NodeAnnouncement,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ChannelDesc {
+    #[serde(rename = "shortChannelId")]
+    pub short_channel_id: String,
+    pub a: String,
+    pub b: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ChannelFlags {
+    #[serde(rename = "isEnabled")]
+    pub is_enabled: bool,
+    // Node 1 is the one with the lexicographically smaller id, which is "a" of the channel
+    #[serde(rename = "isNode1")]
+    pub is_node1: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ChannelUpdate {
+    #[serde(rename = "shortChannelId")]
+    pub short_channel_id: String,
+    #[serde(default)]
+    pub timestamp: Timestamp,
+    #[serde(rename = "channelFlags")]
+    pub channel_flags: ChannelFlags,
+    #[serde(rename = "cltvExpiryDelta")]
+    pub cltv_expiry_delta: u32,
+    #[serde(rename = "htlcMinimumMsat")]
+    pub htlc_minimum_msat: u64,
+    #[serde(rename = "htlcMaximumMsat")]
+    pub htlc_maximum_msat: Option<u64>,
+    #[serde(rename = "feeBaseMsat")]
+    pub fee_base_msat: u32,
+    #[serde(rename = "feeProportionalMillionths")]
+    pub fee_proportional_millionths: u32,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    fn announcement(alias: &str, rgb_color: &str) -> NodeAnnouncement {
+        NodeAnnouncement {
+            node_id: "02".to_string(),
+            alias: alias.to_string(),
+            rgb_color: rgb_color.to_string(),
+            timestamp: Timestamp::default(),
+        }
+    }
+
+    #[rstest]
+    #[case("Boltz", Some("Boltz"))]
+    #[case("Boltz\0\0\0", Some("Boltz"))]
+    #[case("\0\0", None)]
+    #[case("", None)]
+    fn test_alias(#[case] alias: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            announcement(alias, "").alias(),
+            expected.map(|alias| alias.to_string())
+        );
+    }
+
+    #[rstest]
+    #[case("#49daaa", Some(vec![0x49, 0xda, 0xaa]))]
+    #[case("#FF0000", Some(vec![0xff, 0x00, 0x00]))]
+    #[case("49daaa", None)]
+    #[case("#49da", None)]
+    #[case("#zzzzzz", None)]
+    #[case("", None)]
+    fn test_color(#[case] rgb_color: &str, #[case] expected: Option<Vec<u8>>) {
+        assert_eq!(announcement("", rgb_color).color(), expected);
+    }
+
+    #[test]
+    fn test_deserialize_node_announcement() {
+        let node: NodeAnnouncement = serde_json::from_str(
+            r##"{
+                "signature": "3045",
+                "features": {},
+                "timestamp": { "iso": "2024-01-01T00:00:00Z", "unix": 1704067200 },
+                "nodeId": "02d96eadea3d780104449aca5c93461ce67c1564e2e1d73225fa67dd3b997a6018",
+                "rgbColor": "#49daaa",
+                "alias": "Boltz",
+                "addresses": []
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            node,
+            NodeAnnouncement {
+                node_id: "02d96eadea3d780104449aca5c93461ce67c1564e2e1d73225fa67dd3b997a6018"
+                    .to_string(),
+                alias: "Boltz".to_string(),
+                rgb_color: "#49daaa".to_string(),
+                timestamp: Timestamp { unix: 1704067200 },
+            }
+        );
+    }
+}
@@ -50,7 +50,13 @@ impl Invoice {
         let chain_hash = Self::network_to_chain_hash(network);
 
         match self {
-            Invoice::Bolt11(invoice) => invoice.network().chain_hash() == chain_hash,
+            Invoice::Bolt11(invoice) => match network {
+                // BOLT11 has no currency of its own for testnet4
+                wallet::Network::Testnet4 => {
+                    invoice.currency() == lightning_invoice::Currency::BitcoinTestnet
+                }
+                _ => invoice.network().chain_hash() == chain_hash,
+            },
             Invoice::Offer(offer) => offer.supports_chain(chain_hash),
             Invoice::Bolt12(invoice) => invoice.chain() == chain_hash,
         }
@@ -60,6 +66,7 @@ impl Invoice {
         match network {
             wallet::Network::Mainnet => ChainHash::BITCOIN,
             wallet::Network::Testnet => ChainHash::TESTNET3,
+            wallet::Network::Testnet4 => ChainHash::TESTNET4,
             wallet::Network::Signet => ChainHash::SIGNET,
             wallet::Network::Regtest => ChainHash::REGTEST,
         }
@@ -1,6 +1,7 @@
 use std::fmt::{Display, Formatter};
 
 pub mod cln;
+pub mod eclair;
 pub mod invoice;
 pub mod lnd;
 
@@ -110,7 +110,13 @@ async fn main() {
         }
     } else {
         warn!("No cache was configured");
-        Cache::Memory(MemCache::new())
+        Cache::Memory(match config.memory_cache_max_entries {
+            Some(max_entries) => MemCache::with_capacity(max_entries).unwrap_or_else(|err| {
+                error!("Could not create memory cache: {}", err);
+                std::process::exit(1);
+            }),
+            None => MemCache::new(),
+        })
     };
 
     // TODO: move to currencies
@@ -163,14 +169,21 @@ async fn main() {
         }
     };
 
-    let service = Arc::new(Service::new(
+    let service = match Service::new(
         Arc::new(SwapHelperDatabase::new(db_pool.clone())),
         Arc::new(ChainSwapHelperDatabase::new(db_pool.clone())),
         currencies.clone(),
         config.marking,
         config.historical,
+        config.gossip,
         cache,
-    ));
+    ) {
+        Ok(service) => Arc::new(service),
+        Err(err) => {
+            error!("Could not initialize service: {}", err);
+            std::process::exit(1);
+        }
+    };
     {
         let service = service.clone();
         let cancellation_token = cancellation_token.clone();
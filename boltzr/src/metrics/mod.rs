@@ -2,5 +2,10 @@ pub mod server;
 
 pub const SSE_OPEN_COUNT: &str = "sse_open_count";
 pub const GRPC_REQUEST_COUNT: &str = "grpc_request_count";
+pub const GOSSIP_NODE_COUNT: &str = "gossip_node_count";
+pub const GOSSIP_CHANNEL_COUNT: &str = "gossip_channel_count";
+pub const GOSSIP_LAST_REFRESH: &str = "gossip_last_refresh";
+pub const GOSSIP_REFRESH_DURATION: &str = "gossip_refresh_duration";
+pub const GOSSIP_REFRESH_PHASE_DURATION: &str = "gossip_refresh_phase_duration";
 pub const WEBHOOK_CALL_COUNT: &str = "webhook_call_count";
 pub const WEBSOCKET_OPEN_COUNT: &str = "websocket_open_count";
@@ -2,7 +2,7 @@ use axum::Router;
 use axum::routing::get;
 use axum_prometheus::GenericMetricLayer;
 use axum_prometheus::metrics_exporter_prometheus::PrometheusHandle;
-use metrics::{Unit, describe_counter, describe_gauge};
+use metrics::{Unit, describe_counter, describe_gauge, describe_histogram};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use tokio_util::sync::CancellationToken;
@@ -114,7 +114,22 @@ impl Server {
     }
 
     fn setup_prometheus_collector() -> metrics_exporter_prometheus::PrometheusHandle {
-        let prom_collector = metrics_exporter_prometheus::PrometheusBuilder::new().build_recorder();
+        // Phases of gossip refreshes take anywhere from milliseconds to minutes
+        let phase_buckets = (0..18)
+            .map(|exponent| 0.001 * 2f64.powi(exponent))
+            .collect::<Vec<_>>();
+        let prom_collector = metrics_exporter_prometheus::PrometheusBuilder::new()
+            .set_buckets_for_metric(
+                metrics_exporter_prometheus::Matcher::Full(
+                    crate::metrics::GOSSIP_REFRESH_PHASE_DURATION.to_string(),
+                ),
+                &phase_buckets,
+            )
+            .unwrap_or_else(|err| {
+                error!("Could not set gossip refresh phase buckets: {}", err);
+                metrics_exporter_prometheus::PrometheusBuilder::new()
+            })
+            .build_recorder();
         let handle = prom_collector.handle();
 
         metrics::set_global_recorder(prom_collector).unwrap_or_else(|err| {
@@ -145,6 +160,36 @@ impl Server {
             "number of open SSE streams",
         );
 
+        describe_gauge!(
+            crate::metrics::GOSSIP_NODE_COUNT,
+            Unit::Count,
+            "number of nodes in the lightning gossip"
+        );
+
+        describe_gauge!(
+            crate::metrics::GOSSIP_CHANNEL_COUNT,
+            Unit::Count,
+            "number of channels in the lightning gossip"
+        );
+
+        describe_gauge!(
+            crate::metrics::GOSSIP_REFRESH_DURATION,
+            Unit::Seconds,
+            "duration of the last lightning gossip refresh"
+        );
+
+        describe_histogram!(
+            crate::metrics::GOSSIP_REFRESH_PHASE_DURATION,
+            Unit::Seconds,
+            "duration of the phases of lightning gossip refreshes"
+        );
+
+        describe_gauge!(
+            crate::metrics::GOSSIP_LAST_REFRESH,
+            Unit::Seconds,
+            "UNIX timestamp of the last lightning gossip refresh"
+        );
+
         handle
     }
 }
@@ -0,0 +1,499 @@
+use rapidfuzz::distance::{jaro, jaro_winkler, levenshtein};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use unicode_normalization::UnicodeNormalization;
+
+const NGRAM_SIZE: usize = 3;
+
+// The Winkler prefix bonus considers at most 4 characters with a weight of 0.1,
+// which caps the similarity at 0.6 + 0.4 * jaro
+const MAX_PREFIX_BONUS: f64 = 0.4;
+
+// Jaro distances lack the prefix bonus and Levenshtein ones grow by a whole
+// edit relative to the length, so both need more leeway than Jaro-Winkler
+const JARO_WINKLER_MAX_DISTANCE: f64 = 0.1;
+const JARO_MAX_DISTANCE: f64 = 0.15;
+const LEVENSHTEIN_MAX_DISTANCE: f64 = 0.3;
+
+// Characters that separate the words of aliases like "bfx-lnd0" or "Boltz|CLN"
+const WORD_SEPARATORS: [char; 4] = [' ', '|', '-', '_'];
+
+// Where in an alias a query may match in addition to the fuzzy matching
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SubstringMatch {
+    #[default]
+    Anywhere,
+    // Only at the start of a word, so that "ln" does not match "Blink"
+    WordBoundary,
+}
+
+impl SubstringMatch {
+    fn matches(&self, alias: &str, query: &str) -> bool {
+        match self {
+            SubstringMatch::Anywhere => alias.contains(query),
+            SubstringMatch::WordBoundary => alias.match_indices(query).any(|(position, _)| {
+                alias[..position]
+                    .chars()
+                    .next_back()
+                    .is_none_or(|previous| WORD_SEPARATORS.contains(&previous))
+            }),
+        }
+    }
+}
+
+// All metrics are normalized to distances between 0 and 1
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchMetric {
+    #[default]
+    JaroWinkler,
+    Jaro,
+    Levenshtein,
+}
+
+impl SearchMetric {
+    pub fn default_max_distance(&self) -> f64 {
+        match self {
+            SearchMetric::JaroWinkler => JARO_WINKLER_MAX_DISTANCE,
+            SearchMetric::Jaro => JARO_MAX_DISTANCE,
+            SearchMetric::Levenshtein => LEVENSHTEIN_MAX_DISTANCE,
+        }
+    }
+
+    fn comparator(&self, query: &[char]) -> Comparator {
+        let query = query.iter().copied();
+        match self {
+            SearchMetric::JaroWinkler => {
+                Comparator::JaroWinkler(jaro_winkler::BatchComparator::new(query))
+            }
+            SearchMetric::Jaro => Comparator::Jaro(jaro::BatchComparator::new(query)),
+            SearchMetric::Levenshtein => {
+                Comparator::Levenshtein(levenshtein::BatchComparator::new(query))
+            }
+        }
+    }
+
+    // Smallest ratio of the shorter to the longer length that can still be within the
+    // max distance: Jaro similarity is at most (2 + shorter / longer) / 3 and at least
+    // longer - shorter edits are needed to equalize the lengths
+    fn min_length_ratio(&self, max_distance: f64) -> f64 {
+        match self {
+            SearchMetric::JaroWinkler => 1.0 - 3.0 * max_distance / MAX_PREFIX_BONUS,
+            SearchMetric::Jaro => 1.0 - 3.0 * max_distance,
+            SearchMetric::Levenshtein => 1.0 - max_distance,
+        }
+    }
+}
+
+enum Comparator {
+    JaroWinkler(jaro_winkler::BatchComparator<char>),
+    Jaro(jaro::BatchComparator<char>),
+    Levenshtein(levenshtein::BatchComparator<char>),
+}
+
+impl Comparator {
+    fn distance(&self, alias: &str) -> f64 {
+        match self {
+            Comparator::JaroWinkler(comparator) => comparator.distance(alias.chars()),
+            Comparator::Jaro(comparator) => comparator.distance(alias.chars()),
+            Comparator::Levenshtein(comparator) => comparator.normalized_distance(alias.chars()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AliasIndex {
+    // Normalized alias and id of the node
+    aliases: Vec<(String, String)>,
+    ngrams: HashMap<String, Vec<usize>>,
+    exact: HashMap<String, Vec<usize>>,
+    lengths: BTreeMap<usize, Vec<usize>>,
+}
+
+impl AliasIndex {
+    pub fn new<'a, I>(nodes: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut index = Self::default();
+
+        for (id, alias) in nodes {
+            let alias = Self::normalize(alias);
+            let position = index.aliases.len();
+
+            let chars = alias.chars().collect::<Vec<_>>();
+            index.lengths.entry(chars.len()).or_default().push(position);
+
+            let ngrams = chars
+                .windows(NGRAM_SIZE)
+                .map(|window| window.iter().collect::<String>())
+                .collect::<HashSet<_>>();
+            for ngram in ngrams {
+                index.ngrams.entry(ngram).or_default().push(position);
+            }
+
+            index.exact.entry(alias.clone()).or_default().push(position);
+            index.aliases.push((alias, id.to_string()));
+        }
+
+        index
+    }
+
+    pub fn search(
+        &self,
+        query: &str,
+        metric: SearchMetric,
+        max_distance: f64,
+        substring_match: Option<SubstringMatch>,
+    ) -> Vec<(&str, f64)> {
+        let query = Self::normalize(query);
+        let query_chars = query.chars().collect::<Vec<_>>();
+
+        let mut candidates = HashSet::new();
+
+        let (min_length, max_length) = Self::length_bounds(query_chars.len(), metric, max_distance);
+        for (_, positions) in self.lengths.range(min_length..=max_length) {
+            candidates.extend(positions.iter().copied());
+        }
+
+        if substring_match.is_some() {
+            candidates.extend(self.substring_candidates(&query_chars));
+        }
+
+        let comparator = metric.comparator(&query_chars);
+        candidates
+            .into_iter()
+            .filter_map(|position| {
+                let (alias, id) = &self.aliases[position];
+                let distance = comparator.distance(alias);
+                if distance <= max_distance
+                    || substring_match.is_some_and(|mode| mode.matches(alias, &query))
+                {
+                    Some((id.as_str(), distance))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    // Ids of the nodes whose alias equals the query, ignoring case
+    pub fn exact(&self, query: &str) -> Vec<&str> {
+        match self.exact.get(&Self::normalize(query)) {
+            Some(positions) => positions
+                .iter()
+                .map(|position| self.aliases[*position].1.as_str())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Compatibility forms like full-width letters or ligatures compare equal to
+    // their plain equivalents and case is folded after NFKC
+    fn normalize(alias: &str) -> String {
+        alias.nfkc().collect::<String>().to_lowercase()
+    }
+
+    pub(super) fn substring_candidates(&self, query: &[char]) -> Vec<usize> {
+        if query.len() < NGRAM_SIZE {
+            return (0..self.aliases.len()).collect();
+        }
+
+        let mut postings = Vec::new();
+        for window in query.windows(NGRAM_SIZE) {
+            match self.ngrams.get(&window.iter().collect::<String>()) {
+                Some(positions) => postings.push(positions),
+                // An alias containing the query has to contain all its n-grams
+                None => return Vec::new(),
+            }
+        }
+        postings.sort_by_key(|positions| positions.len());
+
+        let mut candidates = postings[0].clone();
+        for positions in &postings[1..] {
+            let positions = positions.iter().collect::<HashSet<_>>();
+            candidates.retain(|position| positions.contains(position));
+        }
+
+        candidates
+    }
+
+    // Aliases whose length differs too much from the query can never be
+    // within the max distance
+    fn length_bounds(
+        query_length: usize,
+        metric: SearchMetric,
+        max_distance: f64,
+    ) -> (usize, usize) {
+        let min_ratio = metric.min_length_ratio(max_distance);
+        if min_ratio <= 0.0 {
+            return (0, usize::MAX);
+        }
+
+        (
+            (query_length as f64 * min_ratio).floor() as usize,
+            (query_length as f64 / min_ratio).ceil() as usize,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    fn index() -> AliasIndex {
+        AliasIndex::new(vec![
+            ("1", "Boltz"),
+            ("2", "Boltz|CLN"),
+            ("3", "bfx-lnd0"),
+            ("4", "bfx-lnd1"),
+            ("5", "Some very long alias that is not similar"),
+        ])
+    }
+
+    fn ids(mut res: Vec<(&str, f64)>) -> Vec<String> {
+        res.sort_by(|a, b| a.0.cmp(b.0));
+        res.into_iter().map(|(id, _)| id.to_string()).collect()
+    }
+
+    #[rstest]
+    #[case("boltz", true, vec!["1", "2"])]
+    #[case("BOLTZ", true, vec!["1", "2"])]
+    #[case("cln", true, vec!["2"])]
+    #[case("cln", false, vec![])]
+    #[case("lnd", true, vec!["3", "4"])]
+    #[case("bfx-lnd0", false, vec!["3", "4"])]
+    #[case("not", true, vec!["5"])]
+    #[case("test", true, vec![])]
+    fn test_search(#[case] query: &str, #[case] substring: bool, #[case] expected: Vec<&str>) {
+        assert_eq!(
+            ids(index().search(
+                query,
+                SearchMetric::JaroWinkler,
+                0.1,
+                substring.then_some(SubstringMatch::Anywhere)
+            )),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("bfx", vec!["3", "4"], vec!["3", "4"])]
+    #[case("ln", vec!["3", "4"], vec!["3", "4"])]
+    #[case("cln", vec!["2"], vec!["2"])]
+    #[case("not", vec!["5"], vec!["5"])]
+    #[case("fx", vec!["3", "4"], vec![])]
+    #[case("nd", vec!["3", "4"], vec![])]
+    #[case("ot", vec!["5"], vec![])]
+    fn test_search_word_boundary(
+        #[case] query: &str,
+        #[case] anywhere: Vec<&str>,
+        #[case] word_boundary: Vec<&str>,
+    ) {
+        assert_eq!(
+            ids(index().search(
+                query,
+                SearchMetric::JaroWinkler,
+                0.1,
+                Some(SubstringMatch::Anywhere)
+            )),
+            anywhere
+        );
+        assert_eq!(
+            ids(index().search(
+                query,
+                SearchMetric::JaroWinkler,
+                0.1,
+                Some(SubstringMatch::WordBoundary)
+            )),
+            word_boundary
+        );
+    }
+
+    #[rstest]
+    #[case("blink", "ln", false)]
+    #[case("bfx-lnd0", "lnd", true)]
+    #[case("boltz|cln", "cln", true)]
+    #[case("my_node", "node", true)]
+    #[case("ln ln", "ln", true)]
+    #[case("xln ln", "ln", true)]
+    #[case("boltz", "boltz", true)]
+    fn test_word_boundary_matches(
+        #[case] alias: &str,
+        #[case] query: &str,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(SubstringMatch::WordBoundary.matches(alias, query), expected);
+    }
+
+    #[rstest]
+    #[case("boltz", vec!["1"])]
+    #[case("BOLTZ|cln", vec!["2"])]
+    #[case("bfx-lnd", vec![])]
+    #[case("", vec![])]
+    fn test_exact(#[case] query: &str, #[case] expected: Vec<&str>) {
+        assert_eq!(index().exact(query), expected);
+    }
+
+    #[rstest]
+    #[case("Ｂｏｌｔｚ", "boltz")]
+    #[case("ＢＯＬＴＺ", "Boltz")]
+    #[case("boltz", "Ｂｏｌｔｚ")]
+    #[case("ﬁnance", "FINANCE")]
+    #[case("Cafe\u{301}", "CAFÉ")]
+    fn test_normalized(#[case] alias: &str, #[case] query: &str) {
+        let index = AliasIndex::new(vec![("1", alias), ("2", "bfx-lnd0")]);
+        assert_eq!(index.exact(query), vec!["1"]);
+        assert_eq!(
+            ids(index.search(query, SearchMetric::JaroWinkler, 0.1, None)),
+            vec!["1"]
+        );
+    }
+
+    #[test]
+    fn test_search_full_width_substring() {
+        let index = AliasIndex::new(vec![("1", "Ｂｏｌｔｚ｜ＣＬＮ"), ("2", "bfx-lnd0")]);
+        assert_eq!(
+            ids(index.search(
+                "boltz",
+                SearchMetric::JaroWinkler,
+                0.1,
+                Some(SubstringMatch::Anywhere)
+            )),
+            vec!["1"]
+        );
+    }
+
+    #[test]
+    fn test_exact_duplicate_aliases() {
+        let index = AliasIndex::new(vec![("1", "Boltz"), ("2", "boltz"), ("3", "Boltz|CLN")]);
+        assert_eq!(index.exact("Boltz"), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_search_short_query() {
+        assert_eq!(
+            ids(index().search(
+                "b",
+                SearchMetric::JaroWinkler,
+                0.1,
+                Some(SubstringMatch::Anywhere)
+            )),
+            vec!["1", "2", "3", "4"]
+        );
+    }
+
+    #[test]
+    fn test_search_matches_full_scan() {
+        let aliases = (0..1_000)
+            .map(|i| {
+                (
+                    i.to_string(),
+                    format!("node{}-{}", i % 7, "x".repeat(i % 13)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let index = AliasIndex::new(aliases.iter().map(|(id, a)| (id.as_str(), a.as_str())));
+
+        for query in ["node3", "node3-xx", "xxxx", "de", "node1-xxxxxxxxxxxx"] {
+            let comparator = jaro_winkler::BatchComparator::new(query.chars());
+            let mut expected = aliases
+                .iter()
+                .filter(|(_, alias)| {
+                    comparator.distance(alias.chars()) <= 0.1 || alias.contains(query)
+                })
+                .map(|(id, _)| id.clone())
+                .collect::<Vec<_>>();
+            expected.sort();
+
+            assert_eq!(
+                ids(index.search(
+                    query,
+                    SearchMetric::JaroWinkler,
+                    0.1,
+                    Some(SubstringMatch::Anywhere)
+                )),
+                expected
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(SearchMetric::JaroWinkler, vec!["3", "2", "1"])]
+    #[case(SearchMetric::Jaro, vec!["2", "3", "1"])]
+    #[case(SearchMetric::Levenshtein, vec!["2", "1", "3"])]
+    fn test_search_metric_ranking(#[case] metric: SearchMetric, #[case] expected: Vec<&str>) {
+        let index = AliasIndex::new(vec![("1", "boltzxx"), ("2", "xboltz"), ("3", "botlz")]);
+
+        let mut res = index.search("boltz", metric, 0.5, None);
+        res.sort_by(|a, b| a.1.total_cmp(&b.1));
+        assert_eq!(
+            res.into_iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(SearchMetric::JaroWinkler, 0.1)]
+    #[case(SearchMetric::Jaro, 0.15)]
+    #[case(SearchMetric::Levenshtein, 0.3)]
+    fn test_search_metric_default_max_distance(
+        #[case] metric: SearchMetric,
+        #[case] expected: f64,
+    ) {
+        assert_eq!(metric.default_max_distance(), expected);
+    }
+
+    #[rstest]
+    #[case(SearchMetric::Jaro)]
+    #[case(SearchMetric::Levenshtein)]
+    fn test_search_metric_matches_full_scan(#[case] metric: SearchMetric) {
+        let aliases = (0..1_000)
+            .map(|i| {
+                (
+                    i.to_string(),
+                    format!("node{}-{}", i % 7, "x".repeat(i % 13)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let index = AliasIndex::new(aliases.iter().map(|(id, a)| (id.as_str(), a.as_str())));
+        let max_distance = metric.default_max_distance();
+
+        for query in ["node3", "node3-xx", "xxxx", "node1-xxxxxxxxxxxx"] {
+            let comparator = metric.comparator(&query.chars().collect::<Vec<_>>());
+            let mut expected = aliases
+                .iter()
+                .filter(|(_, alias)| comparator.distance(alias) <= max_distance)
+                .map(|(id, _)| id.clone())
+                .collect::<Vec<_>>();
+            expected.sort();
+
+            assert_eq!(
+                ids(index.search(query, metric, max_distance, None)),
+                expected
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(10, SearchMetric::JaroWinkler, 0.1, (2, 40))]
+    #[case(10, SearchMetric::JaroWinkler, 0.0, (10, 10))]
+    #[case(10, SearchMetric::JaroWinkler, 0.2, (0, usize::MAX))]
+    #[case(10, SearchMetric::Jaro, 0.0, (10, 10))]
+    #[case(10, SearchMetric::Jaro, 0.4, (0, usize::MAX))]
+    #[case(10, SearchMetric::Levenshtein, 0.5, (5, 20))]
+    #[case(10, SearchMetric::Levenshtein, 1.0, (0, usize::MAX))]
+    fn test_length_bounds(
+        #[case] length: usize,
+        #[case] metric: SearchMetric,
+        #[case] max_distance: f64,
+        #[case] expected: (usize, usize),
+    ) {
+        assert_eq!(
+            AliasIndex::length_bounds(length, metric, max_distance),
+            expected
+        );
+    }
+}
@@ -0,0 +1,505 @@
+use crate::cache::Cache;
+use crate::currencies::Currencies;
+use crate::lightning::eclair::Eclair;
+use crate::service::lightning_info::{
+    Channel, ChannelInfo, ClnLightningInfo, DEFAULT_CACHE_PREFIX, GossipDiagnostics, GossipStats,
+    GraphCursor, GraphExport, LightningInfo, LightningInfoConfig, LookupError, Node, NodeSymbols,
+    PolicySummary, RankedNode, normalize_color,
+};
+use alloy::hex;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, info};
+
+const CACHE_PREFIX: &str = "eclair";
+
+// Keeps the gossip of Eclair nodes in cache entries of its own instead of the keys of
+// ClnLightningInfo, since the ones of CLN may be refreshed for the same symbols and
+// would remove each other's entries; both are merged by MultiLightningInfo
+#[derive(Clone)]
+pub struct EclairLightningInfo {
+    info: ClnLightningInfo,
+    clients: HashMap<String, Eclair>,
+}
+
+impl EclairLightningInfo {
+    pub fn with_config(
+        cache: Cache,
+        currencies: Currencies,
+        config: LightningInfoConfig,
+    ) -> Result<Self> {
+        config.validate()?;
+        let refresh_interval = config.refresh_interval;
+        // The default prefix names the backend, custom ones namespace an instance
+        let cache_prefix = match config.cache_prefix == DEFAULT_CACHE_PREFIX {
+            true => CACHE_PREFIX.to_string(),
+            false => format!("{}:{CACHE_PREFIX}", config.cache_prefix),
+        };
+        let config = LightningInfoConfig {
+            cache_prefix,
+            ..config
+        };
+        let info = ClnLightningInfo::build(cache, currencies.clone(), config);
+
+        info!(
+            "Updating Eclair lightning gossip every: {:?}",
+            refresh_interval
+        );
+
+        let mut clients = HashMap::new();
+        for (symbol, currency) in currencies.iter() {
+            if let Some(eclair) = &currency.eclair {
+                info.spawn_refresh(eclair.clone());
+                clients.insert(symbol.clone(), eclair.clone());
+            }
+        }
+
+        Ok(Self { info, clients })
+    }
+}
+
+#[async_trait]
+impl LightningInfo for EclairLightningInfo {
+    async fn find_node_by_alias(&self, symbol: &str, alias: &str) -> Result<Vec<Node>> {
+        self.info.find_node_by_alias(symbol, alias).await
+    }
+
+    async fn find_node_by_alias_paged(
+        &self,
+        symbol: &str,
+        alias: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Node>> {
+        self.info
+            .find_node_by_alias_paged(symbol, alias, limit, offset)
+            .await
+    }
+
+    async fn find_node_by_alias_scored(
+        &self,
+        symbol: &str,
+        alias: &str,
+    ) -> Result<Vec<(Node, f64)>> {
+        self.info.find_node_by_alias_scored(symbol, alias).await
+    }
+
+    async fn find_node_by_alias_all(&self, alias: &str) -> Result<Vec<NodeSymbols>> {
+        self.info.find_node_by_alias_all(alias).await
+    }
+
+    async fn find_node_by_id_prefix(&self, symbol: &str, prefix: &str) -> Result<Vec<Node>> {
+        self.info.find_node_by_id_prefix(symbol, prefix).await
+    }
+
+    async fn get_channels(&self, symbol: &str, destination: &[u8]) -> Result<Vec<Channel>> {
+        self.info.get_channels(symbol, destination).await
+    }
+
+    async fn get_active_channels(&self, symbol: &str, destination: &[u8]) -> Result<Vec<Channel>> {
+        self.info.get_active_channels(symbol, destination).await
+    }
+
+    async fn get_channels_from(&self, symbol: &str, source: &[u8]) -> Result<Vec<Channel>> {
+        self.info.get_channels_from(symbol, source).await
+    }
+
+    async fn get_channel(&self, symbol: &str, short_channel_id: String) -> Result<ChannelInfo> {
+        self.info.get_channel(symbol, short_channel_id).await
+    }
+
+    async fn get_channel_by_scid(&self, symbol: &str, short_channel_id: String) -> Result<Channel> {
+        self.info
+            .get_channel_by_scid(symbol, short_channel_id)
+            .await
+    }
+
+    async fn get_node_info(&self, symbol: &str, node: &[u8]) -> Result<Node> {
+        let err = match self.info.get_node_info(symbol, node).await {
+            Ok(node) => return Ok(node),
+            Err(err) => err,
+        };
+
+        let eclair = match self.clients.get(symbol) {
+            Some(eclair) => eclair,
+            None => return Err(err),
+        };
+
+        // Nodes that announced themselves after the last refresh are not cached yet
+        match eclair.node(node).await {
+            Ok(announcement) => Ok(Node {
+                id: hex::encode(node),
+                alias: announcement.alias(),
+                color: announcement.color().as_deref().and_then(normalize_color),
+                last_update: Some(announcement.timestamp.unix),
+            }),
+            Err(lookup_err) => {
+                debug!(
+                    "Could not look up {} node {} with Eclair: {}",
+                    symbol,
+                    hex::encode(node),
+                    lookup_err
+                );
+                Err(err)
+            }
+        }
+    }
+
+    async fn node_exists(&self, symbol: &str, node: &[u8]) -> Result<bool> {
+        if self.info.node_exists(symbol, node).await? {
+            return Ok(true);
+        }
+
+        match self.clients.get(symbol) {
+            Some(eclair) => Ok(eclair.find_node(node).await?.is_some()),
+            None => Ok(false),
+        }
+    }
+
+    // Nodes that are only known to Eclair itself are not cached
+    async fn node_ttl(&self, symbol: &str, node: &[u8]) -> Result<Option<Duration>> {
+        self.info.node_ttl(symbol, node).await
+    }
+
+    async fn get_node_infos(&self, symbol: &str, nodes: &[Vec<u8>]) -> Result<Vec<Option<Node>>> {
+        let mut infos = self.info.get_node_infos(symbol, nodes).await?;
+        if !self.clients.contains_key(symbol) {
+            return Ok(infos);
+        }
+
+        for (info, node) in infos.iter_mut().zip(nodes) {
+            if info.is_none() {
+                *info = self.get_node_info(symbol, node).await.ok();
+            }
+        }
+
+        Ok(infos)
+    }
+
+    async fn get_node_policy_summary(
+        &self,
+        symbol: &str,
+        node: &[u8],
+    ) -> Result<Option<PolicySummary>> {
+        self.info.get_node_policy_summary(symbol, node).await
+    }
+
+    async fn gossip_stats(&self, symbol: &str) -> Option<GossipStats> {
+        self.info.gossip_stats(symbol).await
+    }
+
+    async fn gossip_diagnostics(&self, symbol: &str) -> Option<GossipDiagnostics> {
+        self.info.gossip_diagnostics(symbol).await
+    }
+
+    async fn gossip_age(&self, symbol: &str) -> Option<Duration> {
+        self.info.gossip_age(symbol).await
+    }
+
+    async fn is_gossip_fresh(&self, symbol: &str) -> bool {
+        self.info.is_gossip_fresh(symbol).await
+    }
+
+    async fn loaded_symbols(&self) -> Vec<String> {
+        self.info.loaded_symbols().await
+    }
+
+    async fn node_count(&self, symbol: &str) -> Option<usize> {
+        self.info.node_count(symbol).await
+    }
+
+    async fn refresh_now(&self, symbol: &str) -> Result<()> {
+        match self.clients.get(symbol) {
+            Some(eclair) => self.info.refresh_source_now(&mut eclair.clone()).await,
+            None => Err(LookupError::UnknownSymbol(symbol.to_string()).into()),
+        }
+    }
+
+    async fn export_graph(
+        &self,
+        symbol: &str,
+        cursor: &GraphCursor,
+        limit: usize,
+    ) -> Result<GraphExport> {
+        self.info.export_graph(symbol, cursor, limit).await
+    }
+
+    async fn top_nodes_by_capacity(&self, symbol: &str, n: usize) -> Result<Vec<RankedNode>> {
+        self.info.top_nodes_by_capacity(symbol, n).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cache::MemCache;
+    use crate::currencies::Currency;
+    use crate::lightning::eclair::test::{channel_update, node_announcement, start_server};
+    use crate::service::multi_info::MultiLightningInfo;
+    use crate::wallet::{Bitcoin, Network};
+    use bip39::Mnemonic;
+    use serde_json::json;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    async fn eclair() -> Eclair {
+        start_server(HashMap::from([
+            (
+                "nodes".to_string(),
+                json!([
+                    node_announcement(&[2; 33], "Boltz\u{0}\u{0}\u{0}", "#49daaa"),
+                    node_announcement(&[3; 33], "bfx-lnd0", "invalid"),
+                ]),
+            ),
+            (
+                "allchannels".to_string(),
+                json!([{
+                    "shortChannelId": "1x1x1",
+                    "a": hex::encode([2; 33]),
+                    "b": hex::encode([3; 33]),
+                }]),
+            ),
+            (
+                "allupdates".to_string(),
+                json!([
+                    channel_update("1x1x1", true, true),
+                    channel_update("1x1x1", false, false),
+                    channel_update("9x9x9", true, true),
+                ]),
+            ),
+            (
+                format!("node/{}", hex::encode([4; 33])),
+                json!({
+                    "announcement": node_announcement(&[4; 33], "New\u{0}", "#000000"),
+                    "activeChannels": 0,
+                    "totalCapacity": 0,
+                }),
+            ),
+        ]))
+        .await
+    }
+
+    fn currencies(eclair: Eclair) -> Currencies {
+        Arc::new(HashMap::from([(
+            "BTC".to_string(),
+            Currency {
+                network: Network::Regtest,
+                wallet: Arc::new(
+                    Bitcoin::new(
+                        Network::Regtest,
+                        &Mnemonic::from_str(
+                            "test test test test test test test test test test test junk",
+                        )
+                        .unwrap()
+                        .to_seed(""),
+                        "m/0/0".to_string(),
+                    )
+                    .unwrap(),
+                ),
+                chain: None,
+                cln: None,
+                lnd: None,
+                eclair: Some(eclair),
+            },
+        )]))
+    }
+
+    async fn refreshed_info(cache: Cache) -> EclairLightningInfo {
+        let info = EclairLightningInfo::with_config(
+            cache,
+            currencies(eclair().await),
+            LightningInfoConfig::default(),
+        )
+        .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while info.gossip_stats("BTC").await.is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        info
+    }
+
+    #[tokio::test]
+    async fn test_refresh() {
+        let cache = Cache::Memory(MemCache::new());
+        let info = refreshed_info(cache.clone()).await;
+
+        let stats = info.gossip_stats("BTC").await.unwrap();
+        assert_eq!(stats.node_count, 2);
+        assert_eq!(stats.channel_count, 1);
+        assert!(info.is_gossip_fresh("BTC").await);
+
+        let nodes = info.find_node_by_alias("BTC", "Boltz").await.unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].id, hex::encode([2; 33]));
+        assert_eq!(nodes[0].alias, Some("Boltz".to_string()));
+        assert_eq!(nodes[0].color, Some("#49daaa".to_string()));
+        assert_eq!(nodes[0].last_update, Some(1704067200));
+
+        let node = info.get_node_info("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(node.alias, Some("bfx-lnd0".to_string()));
+        assert_eq!(node.color, None);
+
+        let cached: Option<Node> = cache
+            .get("eclair:BTC:node", &hex::encode([2; 33]))
+            .await
+            .unwrap();
+        assert_eq!(cached.unwrap().alias, Some("Boltz".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_alongside_cln() {
+        let cache = Cache::Memory(MemCache::new());
+        let info = refreshed_info(cache.clone()).await;
+
+        // Refreshes the same symbol with a different graph, like CLN would
+        let cln = ClnLightningInfo::build(
+            cache.clone(),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig::default(),
+        );
+        cln.spawn_refresh(
+            start_server(HashMap::from([
+                (
+                    "nodes".to_string(),
+                    json!([node_announcement(&[5; 33], "cln", "#000000")]),
+                ),
+                ("allchannels".to_string(), json!([])),
+                ("allupdates".to_string(), json!([])),
+            ]))
+            .await,
+        );
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while cln.gossip_stats("BTC").await.is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // Neither refresh removed the entries of the other
+        for (key, node) in [("cln:BTC:node", [5; 33]), ("eclair:BTC:node", [2; 33])] {
+            assert!(cache.exists(key, &hex::encode(node)).await.unwrap());
+        }
+        assert!(
+            !cache
+                .exists("cln:BTC:node", &hex::encode([2; 33]))
+                .await
+                .unwrap()
+        );
+        assert_eq!(info.get_channels("BTC", &[3; 33]).await.unwrap().len(), 1);
+
+        let multi = MultiLightningInfo::new(vec![Arc::new(cln), Arc::new(info)]);
+        assert_eq!(
+            multi.find_node_by_alias("BTC", "Boltz").await.unwrap()[0].id,
+            hex::encode([2; 33])
+        );
+        assert_eq!(
+            multi.find_node_by_alias("BTC", "cln").await.unwrap()[0].id,
+            hex::encode([5; 33])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_channels() {
+        let info = refreshed_info(Cache::Memory(MemCache::new())).await;
+
+        let channels = info.get_channels("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].source.id, hex::encode([2; 33]));
+        assert_eq!(channels[0].short_channel_id, "1x1x1");
+        assert_eq!(channels[0].capacity_sat, None);
+        assert!(channels[0].active);
+        assert_eq!(channels[0].info.base_fee_millisatoshi, 1000);
+        assert_eq!(channels[0].info.fee_ppm, 100);
+        assert_eq!(channels[0].info.delay, 144);
+        assert_eq!(channels[0].info.htlc_minimum_millisatoshi, Some(1));
+        assert_eq!(channels[0].info.htlc_maximum_millisatoshi, Some(990000000));
+        assert_eq!(channels[0].info.last_update, Some(1704067200));
+
+        let channels = info.get_channels_from("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(
+            channels[0].destination.as_ref().unwrap().id,
+            hex::encode([2; 33])
+        );
+        assert!(!channels[0].active);
+
+        // Updates of channels without announcement are ignored
+        assert!(info.get_channel("BTC", "9x9x9".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_node_exists() {
+        let info = refreshed_info(Cache::Memory(MemCache::new())).await;
+
+        assert!(info.node_exists("BTC", &[2; 33]).await.unwrap());
+        // Not cached yet, but known to Eclair
+        assert!(info.node_exists("BTC", &[4; 33]).await.unwrap());
+        assert!(!info.node_exists("BTC", &[5; 33]).await.unwrap());
+        assert!(!info.node_exists("L-BTC", &[2; 33]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_node_exists_eclair_error() {
+        let mut info = refreshed_info(Cache::Memory(MemCache::new())).await;
+        info.clients.get_mut("BTC").unwrap().password = "invalid".to_string();
+
+        // Cached nodes do not need Eclair
+        assert!(info.node_exists("BTC", &[2; 33]).await.unwrap());
+        assert_eq!(
+            info.node_exists("BTC", &[5; 33])
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "Eclair responded with 401 Unauthorized"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_node_infos_fallback() {
+        let info = refreshed_info(Cache::Memory(MemCache::new())).await;
+
+        let nodes = info
+            .get_node_infos("BTC", &[vec![5; 33], vec![4; 33], vec![2; 33]])
+            .await
+            .unwrap();
+        assert!(nodes[0].is_none());
+        assert_eq!(nodes[1].as_ref().unwrap().alias, Some("New".to_string()));
+        assert_eq!(nodes[2].as_ref().unwrap().alias, Some("Boltz".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_get_node_info_fallback() {
+        let info = refreshed_info(Cache::Memory(MemCache::new())).await;
+
+        let node = info.get_node_info("BTC", &[4; 33]).await.unwrap();
+        assert_eq!(node.id, hex::encode([4; 33]));
+        assert_eq!(node.alias, Some("New".to_string()));
+        assert_eq!(node.color, Some("#000000".to_string()));
+
+        assert_eq!(
+            info.get_node_info("BTC", &[5; 33])
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "node not found"
+        );
+        assert_eq!(
+            info.get_node_info("L-BTC", &[4; 33])
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "node not found"
+        );
+    }
+}
@@ -2,22 +2,574 @@ use crate::cache::Cache;
 use crate::chain::BaseClient;
 use crate::currencies::Currencies;
 use crate::lightning::cln::Cln;
-use crate::lightning::cln::cln_rpc::ListchannelsChannels;
+use crate::lightning::cln::cln_rpc::{Amount, ListchannelsChannels, ListnodesNodes};
+use crate::lightning::eclair::Eclair;
+use crate::lightning::eclair::types::{ChannelDesc, ChannelUpdate};
+use crate::service::alias_index::{AliasIndex, SearchMetric, SubstringMatch};
 use alloy::hex;
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
-use rapidfuzz::distance::jaro_winkler;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::{Duration, Instant};
+use futures::{StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::hash::{DefaultHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio_util::sync::{CancellationToken, DropGuard};
 use tracing::{debug, info, instrument, warn};
 
-const MAX_DISTANCE: f64 = 0.1;
-const CACHE_TTL_SECS: u64 = 3_600;
+const DEFAULT_CACHE_TTL_SECS: u64 = 3_600;
+const CACHE_WRITE_CONCURRENCY: usize = 32;
+const NODE_WRITE_BATCH_SIZE: usize = 256;
+const MAX_NEGATIVE_CACHE_ENTRIES: usize = 10_000;
+const BACKOFF_INITIAL_DELAY: Duration = Duration::from_secs(5);
+const STARTUP_JITTER: Duration = Duration::from_secs(2);
+const DEFAULT_GOSSIP_TIMEOUT_SECS: u64 = 120;
+pub(super) const DEFAULT_CACHE_PREFIX: &str = "cln";
+// Hex encoded compressed public key
+const MAX_NODE_ID_PREFIX_LENGTH: usize = 66;
 
-struct SearchResult<T> {
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct LightningInfoConfig {
+    #[serde(rename = "searchMetric")]
+    pub search_metric: SearchMetric,
+    // Defaults to the threshold of the search metric
+    #[serde(rename = "maxDistance")]
+    pub max_distance: Option<f64>,
+    #[serde(rename = "substringMatch")]
+    pub substring_match: bool,
+    // Only used when substring matching is enabled
+    #[serde(rename = "substringMatchMode")]
+    pub substring_match_mode: SubstringMatch,
+    // How much the channel capacity of nodes matters when ranking search results; 0 ranks
+    // by the alias distance only
+    #[serde(rename = "capacityWeight")]
+    pub capacity_weight: f64,
+    // Never returned by alias searches, like the nodes of the operator
+    #[serde(rename = "excludeNodeIds")]
+    pub exclude_node_ids: Vec<String>,
+    // Private channels to these nodes are cached too and returned with their public ones
+    #[serde(rename = "ownNodeIds")]
+    pub own_node_ids: Vec<String>,
+    // Only the channels with the highest capacity are cached for destinations with more
+    #[serde(rename = "maxChannelsPerDestination")]
+    pub max_channels_per_destination: Option<usize>,
+    // Smaller channels are not cached; 0 keeps all of them
+    #[serde(rename = "minChannelCapacitySat")]
+    pub min_channel_capacity_sat: u64,
+
+    #[serde(rename = "refreshInterval", with = "duration_secs")]
+    pub refresh_interval: Duration,
+    #[serde(rename = "cacheTtl", with = "duration_secs")]
+    pub cache_ttl: Duration,
+    // How long lookups of missing entries are answered without querying the cache; 0 disables it
+    #[serde(rename = "negativeCacheTtl", with = "duration_secs")]
+    pub negative_cache_ttl: Duration,
+    // How long fetching the nodes or channels from the gossip source may take
+    #[serde(rename = "gossipTimeout", with = "duration_secs")]
+    pub gossip_timeout: Duration,
+    // Namespace of the cache keys, so that instances sharing a cache do not collide
+    #[serde(rename = "cachePrefix")]
+    pub cache_prefix: String,
+}
+
+impl Default for LightningInfoConfig {
+    fn default() -> Self {
+        Self {
+            search_metric: SearchMetric::JaroWinkler,
+            max_distance: None,
+            substring_match: true,
+            substring_match_mode: SubstringMatch::Anywhere,
+            capacity_weight: 0.0,
+            exclude_node_ids: Vec::new(),
+            own_node_ids: Vec::new(),
+            max_channels_per_destination: None,
+            min_channel_capacity_sat: 0,
+            refresh_interval: Duration::from_secs(DEFAULT_CACHE_TTL_SECS - 60),
+            cache_ttl: Duration::from_secs(DEFAULT_CACHE_TTL_SECS),
+            negative_cache_ttl: Duration::ZERO,
+            gossip_timeout: Duration::from_secs(DEFAULT_GOSSIP_TIMEOUT_SECS),
+            cache_prefix: DEFAULT_CACHE_PREFIX.to_string(),
+        }
+    }
+}
+
+impl LightningInfoConfig {
+    pub fn max_distance(&self) -> f64 {
+        self.max_distance
+            .unwrap_or_else(|| self.search_metric.default_max_distance())
+    }
+
+    pub(super) fn validate(&self) -> Result<()> {
+        if self.refresh_interval.is_zero() {
+            return Err(anyhow!("gossip refresh interval has to be greater than 0"));
+        }
+
+        // Refreshing less often than the cache expires would serve expired data
+        if self.refresh_interval >= self.cache_ttl {
+            return Err(anyhow!(
+                "gossip refresh interval {:?} has to be shorter than the cache TTL {:?}",
+                self.refresh_interval,
+                self.cache_ttl
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.capacity_weight) {
+            return Err(anyhow!(
+                "capacity weight {} has to be between 0 and 1",
+                self.capacity_weight
+            ));
+        }
+
+        if self.cache_prefix.is_empty() {
+            return Err(anyhow!("cache prefix must not be empty"));
+        }
+
+        if self.gossip_timeout.is_zero() {
+            return Err(anyhow!("gossip timeout has to be greater than 0"));
+        }
+
+        if self.max_channels_per_destination == Some(0) {
+            return Err(anyhow!(
+                "max channels per destination has to be greater than 0"
+            ));
+        }
+
+        if self.negative_cache_ttl >= self.cache_ttl {
+            return Err(anyhow!(
+                "negative cache TTL {:?} has to be shorter than the cache TTL {:?}",
+                self.negative_cache_ttl,
+                self.cache_ttl
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+mod duration_secs {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_secs())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_secs(u64::deserialize(deserializer)?))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct GossipStats {
+    #[serde(rename = "nodeCount")]
+    pub node_count: usize,
+    #[serde(rename = "channelCount")]
+    pub channel_count: usize,
+    #[serde(rename = "lastRefreshDurationMs", serialize_with = "serialize_millis")]
+    pub last_refresh_duration: Duration,
+    #[serde(rename = "lastRefresh")]
+    pub last_refresh_ts: u64,
+    #[serde(rename = "lastRefreshPhases")]
+    pub last_refresh_phases: RefreshPhases,
+}
+
+// Shows whether the gossip source or the cache dominates a refresh
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct RefreshPhases {
+    #[serde(rename = "nodeFetchMs", serialize_with = "serialize_millis")]
+    pub node_fetch: Duration,
+    #[serde(rename = "channelFetchMs", serialize_with = "serialize_millis")]
+    pub channel_fetch: Duration,
+    #[serde(rename = "cacheWriteMs", serialize_with = "serialize_millis")]
+    pub cache_write: Duration,
+}
+
+// A recent refresh can still serve gossip that lags behind the network
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct GossipDiagnostics {
+    #[serde(rename = "nodeCount")]
+    pub node_count: usize,
+    #[serde(rename = "channelCount")]
+    pub channel_count: usize,
+    #[serde(rename = "lastRefresh")]
+    pub last_refresh_ts: u64,
+    // Newest announcement timestamp among the loaded nodes
+    #[serde(rename = "newestNodeUpdate", skip_serializing_if = "Option::is_none")]
+    pub newest_node_update: Option<u64>,
+}
+
+// Value of the channels to a destination in the cache
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(from = "CachedDestinationChannels<C>")]
+struct DestinationChannels<C> {
+    channels: Vec<C>,
+    // Whether channels were dropped because of the configured maximum
+    truncated: bool,
+}
+
+// Entries written before truncation was recorded are a bare list of channels
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CachedDestinationChannels<C> {
+    Legacy(Vec<C>),
+    Current { channels: Vec<C>, truncated: bool },
+}
+
+impl<C> From<CachedDestinationChannels<C>> for DestinationChannels<C> {
+    fn from(cached: CachedDestinationChannels<C>) -> Self {
+        match cached {
+            CachedDestinationChannels::Legacy(channels) => Self {
+                channels,
+                truncated: false,
+            },
+            CachedDestinationChannels::Current {
+                channels,
+                truncated,
+            } => Self {
+                channels,
+                truncated,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct NodeCapacity {
+    #[serde(rename = "inboundSat")]
+    pub inbound_sat: u64,
+    #[serde(rename = "outboundSat")]
+    pub outbound_sat: u64,
+    #[serde(rename = "channelCount")]
+    pub channel_count: usize,
+    // Channels without a known capacity are not part of the sums
+    #[serde(rename = "channelsMissingCapacity")]
+    pub channels_missing_capacity: usize,
+}
+
+impl NodeCapacity {
+    fn from_channels(inbound: &[Channel], outbound: &[Channel]) -> Self {
+        let sum = |channels: &[Channel]| {
+            channels
+                .iter()
+                .filter_map(|channel| channel.capacity_sat)
+                .sum::<u64>()
+        };
+
+        // Both directions of a channel count as one
+        let mut capacities = HashMap::<&str, Option<u64>>::new();
+        for channel in inbound.iter().chain(outbound) {
+            capacities
+                .entry(channel.short_channel_id.as_str())
+                .or_insert(channel.capacity_sat);
+        }
+
+        Self {
+            inbound_sat: sum(inbound),
+            outbound_sat: sum(outbound),
+            channel_count: capacities.len(),
+            channels_missing_capacity: capacities
+                .values()
+                .filter(|capacity| capacity.is_none())
+                .count(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct FeeRange {
+    pub min: u32,
+    pub median: f64,
+    pub max: u32,
+}
+
+impl FeeRange {
+    fn from_values(mut values: Vec<u32>) -> Option<Self> {
+        values.sort_unstable();
+        let (min, max) = (*values.first()?, *values.last()?);
+
+        // The two middle values are the same one when the count is odd
+        let lower = values[(values.len() - 1) / 2];
+        let upper = values[values.len() / 2];
+
+        Some(Self {
+            min,
+            median: (f64::from(lower) + f64::from(upper)) / 2.0,
+            max,
+        })
+    }
+}
+
+// Fees charged by the peers of a node for forwarding to it
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct PolicySummary {
+    #[serde(rename = "channelCount")]
+    pub channel_count: usize,
+    #[serde(rename = "baseFeeMillisatoshi")]
+    pub base_fee_millisatoshi: FeeRange,
+    #[serde(rename = "feePpm")]
+    pub fee_ppm: FeeRange,
+}
+
+impl PolicySummary {
+    pub(super) fn from_channels(channels: &[Channel]) -> Option<Self> {
+        let fees = |fee: fn(&ChannelPolicy) -> u32| {
+            FeeRange::from_values(channels.iter().map(|channel| fee(&channel.info)).collect())
+        };
+
+        Some(Self {
+            channel_count: channels.len(),
+            base_fee_millisatoshi: fees(|policy| policy.base_fee_millisatoshi)?,
+            fee_ppm: fees(|policy| policy.fee_ppm)?,
+        })
+    }
+}
+
+// Both directions of a channel; node 1 is the one with the lexicographically smaller id
+#[derive(Debug, Serialize)]
+pub struct ChannelPair {
+    #[serde(rename = "shortChannelId")]
+    pub short_channel_id: String,
+    #[serde(rename = "capacity", skip_serializing_if = "Option::is_none")]
+    pub capacity_sat: Option<u64>,
+    pub node1: String,
+    // Not known when only one direction was announced and the other node is not in the gossip
+    pub node2: Option<String>,
+    #[serde(rename = "node1Policy")]
+    pub node1_policy: Option<ChannelPolicy>,
+    #[serde(rename = "node2Policy")]
+    pub node2_policy: Option<ChannelPolicy>,
+}
+
+impl ChannelPair {
+    fn from_sides(
+        short_channel_id: String,
+        capacity_sat: Option<u64>,
+        mut sides: Vec<(String, Option<ChannelPolicy>)>,
+    ) -> Result<Self> {
+        sides.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut sides = sides.into_iter();
+
+        let (node1, node1_policy) = sides.next().ok_or_else(|| anyhow!("channel not found"))?;
+        let (node2, node2_policy) = match sides.next() {
+            Some((node2, policy)) => (Some(node2), policy),
+            None => (None, None),
+        };
+
+        Ok(Self {
+            short_channel_id,
+            capacity_sat,
+            node1,
+            node2,
+            node1_policy,
+            node2_policy,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RankedNode {
+    #[serde(flatten)]
+    pub node: Node,
+    #[serde(rename = "capacity")]
+    pub capacity_sat: u64,
+    #[serde(rename = "channelCount")]
+    pub channel_count: usize,
+}
+
+// Nodes are paged by id and channels by short channel id,
+// each page continues after the cursor returned with the previous one
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GraphCursor {
+    pub after_node: Option<String>,
+    pub after_channel: Option<String>,
+}
+
+// A page of everything that is cached for a symbol
+#[derive(Debug, Serialize)]
+pub struct GraphExport {
+    pub nodes: Vec<Node>,
+    pub channels: Vec<ChannelInfo>,
+    #[serde(rename = "nextNode", skip_serializing_if = "Option::is_none")]
+    pub next_node: Option<String>,
+    #[serde(rename = "nextChannel", skip_serializing_if = "Option::is_none")]
+    pub next_channel: Option<String>,
+    // Set when one of the lists filled the page and might continue
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+}
+
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u128(duration.as_millis())
+}
+
+// Colors are RGB, so anything that is not exactly 3 bytes is malformed
+pub(super) fn normalize_color(raw: &[u8]) -> Option<String> {
+    if raw.len() != 3 {
+        return None;
+    }
+
+    Some(format!("#{}", hex::encode(raw)))
+}
+
+#[derive(Debug)]
+pub enum UpdateError {
+    Nodes(anyhow::Error),
+    Channels(anyhow::Error),
+}
+
+impl Display for UpdateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateError::Nodes(err) => write!(f, "updating nodes failed: {err}"),
+            UpdateError::Channels(err) => write!(f, "updating channels failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+#[derive(Debug, PartialEq)]
+pub enum LookupError {
+    UnknownSymbol(String),
+    // The symbol has a gossip source that has not been loaded yet
+    GossipNotReady(String),
+    RefreshInProgress(String),
+}
+
+impl Display for LookupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LookupError::UnknownSymbol(symbol) => write!(f, "no gossip source for {symbol}"),
+            LookupError::GossipNotReady(symbol) => write!(f, "gossip of {symbol} is not ready"),
+            LookupError::RefreshInProgress(symbol) => {
+                write!(
+                    f,
+                    "refresh of {symbol} lightning gossip is already in progress"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LookupError {}
+
+#[async_trait]
+pub trait GossipSource: BaseClient {
+    async fn gossip_nodes(&mut self) -> Result<Vec<ListnodesNodes>>;
+    async fn gossip_channels(&mut self) -> Result<Vec<ListchannelsChannels>>;
+}
+
+#[async_trait]
+impl GossipSource for Cln {
+    async fn gossip_nodes(&mut self) -> Result<Vec<ListnodesNodes>> {
+        self.list_nodes(None).await
+    }
+
+    async fn gossip_channels(&mut self) -> Result<Vec<ListchannelsChannels>> {
+        self.list_channels(None).await
+    }
+}
+
+#[async_trait]
+impl GossipSource for Eclair {
+    async fn gossip_nodes(&mut self) -> Result<Vec<ListnodesNodes>> {
+        self.nodes()
+            .await?
+            .into_iter()
+            .map(|node| {
+                Ok(ListnodesNodes {
+                    nodeid: hex::decode(&node.node_id)?,
+                    last_timestamp: u32::try_from(node.timestamp.unix).ok(),
+                    alias: node.alias(),
+                    color: node.color(),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    async fn gossip_channels(&mut self) -> Result<Vec<ListchannelsChannels>> {
+        let (channels, updates) = tokio::try_join!(self.channels(), self.channel_updates())?;
+        let channels = channels
+            .into_iter()
+            .map(|channel| (channel.short_channel_id.clone(), channel))
+            .collect::<HashMap<_, _>>();
+
+        // Updates of channels that are not announced are not part of the public graph
+        updates
+            .into_iter()
+            .filter_map(|update| {
+                channels
+                    .get(&update.short_channel_id)
+                    .map(|channel| eclair_channel_direction(channel, update))
+            })
+            .collect()
+    }
+}
+
+fn eclair_channel_direction(
+    channel: &ChannelDesc,
+    update: ChannelUpdate,
+) -> Result<ListchannelsChannels> {
+    let (source, destination) = if update.channel_flags.is_node1 {
+        (&channel.a, &channel.b)
+    } else {
+        (&channel.b, &channel.a)
+    };
+
+    Ok(ListchannelsChannels {
+        source: hex::decode(source)?,
+        destination: hex::decode(destination)?,
+        short_channel_id: update.short_channel_id,
+        public: true,
+        // Eclair does not include the capacity in its gossip
+        amount_msat: None,
+        active: update.channel_flags.is_enabled,
+        last_update: u32::try_from(update.timestamp.unix).unwrap_or_default(),
+        base_fee_millisatoshi: update.fee_base_msat,
+        fee_per_millionth: update.fee_proportional_millionths,
+        delay: update.cltv_expiry_delta,
+        htlc_minimum_msat: Some(Amount {
+            msat: update.htlc_minimum_msat,
+        }),
+        htlc_maximum_msat: update.htlc_maximum_msat.map(|msat| Amount { msat }),
+        direction: u32::from(!update.channel_flags.is_node1),
+        ..Default::default()
+    })
+}
+
+struct GossipNodes {
+    nodes: HashMap<String, Node>,
+    aliases: AliasIndex,
+    // Total capacity of the public channels of the nodes in sat
+    capacities: HashMap<String, u64>,
+    channel_counts: HashMap<String, usize>,
+}
+
+impl From<HashMap<String, Node>> for GossipNodes {
+    fn from(nodes: HashMap<String, Node>) -> Self {
+        let aliases = AliasIndex::new(
+            nodes
+                .values()
+                .filter_map(|node| node.alias.as_deref().map(|alias| (node.id.as_str(), alias))),
+        );
+
+        Self {
+            nodes,
+            aliases,
+            capacities: HashMap::new(),
+            channel_counts: HashMap::new(),
+        }
+    }
+}
+
+pub(super) struct SearchResult<T> {
     pub distance: f64,
     pub node: T,
 }
@@ -29,6 +581,15 @@ pub struct Node {
     pub alias: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<String>,
+    #[serde(rename = "lastUpdate", skip_serializing_if = "Option::is_none")]
+    pub last_update: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeSymbols {
+    #[serde(flatten)]
+    pub node: Node,
+    pub symbols: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -49,6 +610,21 @@ pub struct ChannelPolicy {
         skip_serializing_if = "Option::is_none"
     )]
     pub htlc_maximum_millisatoshi: Option<u64>,
+    #[serde(rename = "lastUpdate", skip_serializing_if = "Option::is_none")]
+    pub last_update: Option<u64>,
+}
+
+impl ChannelPolicy {
+    // The proportional part is rounded down like the nodes do when forwarding
+    pub fn total_fee_msat(&self, amount_msat: u64) -> u64 {
+        let proportional = amount_msat as u128 * self.fee_ppm as u128 / 1_000_000;
+        (self.base_fee_millisatoshi as u128 + proportional).min(u64::MAX as u128) as u64
+    }
+
+    // Rounded down to whole satoshis
+    pub fn base_fee_sat(&self) -> u32 {
+        self.base_fee_millisatoshi / 1_000
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -60,6 +636,24 @@ pub struct Channel {
     pub capacity_sat: Option<u64>,
     pub active: bool,
     pub info: ChannelPolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination: Option<Node>,
+}
+
+impl Channel {
+    // Policies without a gossip timestamp cannot be shown to be recent
+    pub fn is_stale(&self, max_age: Duration, now: SystemTime) -> bool {
+        let last_update = match self.info.last_update {
+            Some(last_update) => UNIX_EPOCH + Duration::from_secs(last_update),
+            None => return true,
+        };
+
+        match now.duration_since(last_update) {
+            Ok(age) => age > max_age,
+            // Updates that are ahead of the clock are as fresh as it gets
+            Err(_) => false,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -78,13 +672,14 @@ pub struct ChannelInfo {
     pub policies: Vec<ChannelInfoSide>,
 }
 
-impl From<(ListchannelsChannels, Node)> for Channel {
-    fn from(v: (ListchannelsChannels, Node)) -> Self {
+impl From<(ListchannelsChannels, Node, Option<Node>)> for Channel {
+    fn from(v: (ListchannelsChannels, Node, Option<Node>)) -> Self {
         Self {
             source: Node {
                 id: hex::encode(v.0.source),
                 alias: v.1.alias,
                 color: v.1.color,
+                last_update: v.1.last_update,
             },
             short_channel_id: v.0.short_channel_id,
             capacity_sat: v.0.amount_msat.map(|a| a.msat / 1_000),
@@ -96,7 +691,10 @@ impl From<(ListchannelsChannels, Node)> for Channel {
                 delay: v.0.delay,
                 htlc_minimum_millisatoshi: v.0.htlc_minimum_msat.map(|a| a.msat),
                 htlc_maximum_millisatoshi: v.0.htlc_maximum_msat.map(|a| a.msat),
+                // Zero is what CLN reports when it has not seen an update yet
+                last_update: Some(u64::from(v.0.last_update)).filter(|ts| *ts > 0),
             },
+            destination: v.2,
         }
     }
 }
@@ -104,416 +702,4655 @@ impl From<(ListchannelsChannels, Node)> for Channel {
 #[async_trait]
 pub trait LightningInfo {
     async fn find_node_by_alias(&self, symbol: &str, alias: &str) -> Result<Vec<Node>>;
+    async fn find_node_by_alias_paged(
+        &self,
+        symbol: &str,
+        alias: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Node>>;
+    // Lower scores are better matches
+    async fn find_node_by_alias_scored(
+        &self,
+        symbol: &str,
+        alias: &str,
+    ) -> Result<Vec<(Node, f64)>>;
+    async fn find_node_by_alias_all(&self, alias: &str) -> Result<Vec<NodeSymbols>>;
+    async fn find_node_by_id_prefix(&self, symbol: &str, prefix: &str) -> Result<Vec<Node>>;
     async fn get_channels(&self, symbol: &str, destination: &[u8]) -> Result<Vec<Channel>>;
+    async fn get_active_channels(&self, symbol: &str, destination: &[u8]) -> Result<Vec<Channel>>;
+    async fn get_channels_from(&self, symbol: &str, source: &[u8]) -> Result<Vec<Channel>>;
     async fn get_channel(&self, symbol: &str, short_channel_id: String) -> Result<ChannelInfo>;
+    async fn get_channel_by_scid(&self, symbol: &str, short_channel_id: String) -> Result<Channel>;
     async fn get_node_info(&self, symbol: &str, node: &[u8]) -> Result<Node>;
-}
-
-#[derive(Clone)]
-pub struct ClnLightningInfo {
-    cache: Cache,
-    currencies: Currencies,
+    async fn node_exists(&self, symbol: &str, node: &[u8]) -> Result<bool>;
+    // In the order of the requested nodes, with None for the ones that are not known
+    async fn get_node_infos(&self, symbol: &str, nodes: &[Vec<u8>]) -> Result<Vec<Option<Node>>>;
+    async fn get_channel_pair(
+        &self,
+        symbol: &str,
+        short_channel_id: String,
+    ) -> Result<ChannelPair> {
+        let info = self.get_channel(symbol, short_channel_id).await?;
+        let mut sides = info
+            .policies
+            .into_iter()
+            .map(|side| (side.node.id, Some(side.policy)))
+            .collect::<Vec<_>>();
 
-    nodes: Arc<RwLock<HashMap<String, HashMap<String, Node>>>>,
-}
+        // Without its policy, the other node is only known as destination of the channel
+        if sides.len() == 1 {
+            let destination = match self
+                .get_channels_from(symbol, &hex::decode(&sides[0].0)?)
+                .await
+            {
+                Ok(channels) => channels
+                    .into_iter()
+                    .find(|channel| channel.short_channel_id == info.short_channel_id)
+                    .and_then(|channel| channel.destination),
+                Err(_) => None,
+            };
+            if let Some(destination) = destination {
+                sides.push((destination.id, None));
+            }
+        }
 
-impl ClnLightningInfo {
-    pub fn new(cache: Cache, currencies: Currencies) -> Self {
-        let info = Self {
-            cache,
-            currencies,
-            nodes: Arc::new(RwLock::new(HashMap::new())),
-        };
+        ChannelPair::from_sides(info.short_channel_id, info.capacity_sat, sides)
+    }
+    // Inbound channels are capped by the max channels per destination that get cached
+    async fn get_node_capacity(&self, symbol: &str, node: &[u8]) -> Result<NodeCapacity> {
+        let inbound = self.get_channels(symbol, node).await;
+        let outbound = self.get_channels_from(symbol, node).await;
 
-        {
-            let interval_duration = Duration::from_secs(CACHE_TTL_SECS - 60);
-            info!("Updating lightning gossip every: {:?}", interval_duration);
-            let mut interval = tokio::time::interval(interval_duration);
-
-            let info = info.clone();
-            tokio::spawn(async move {
-                loop {
-                    interval.tick().await;
-
-                    for currency in info.currencies.values() {
-                        let mut cln = match &currency.cln {
-                            Some(cln) => cln.clone(),
-                            None => continue,
-                        };
-
-                        let start = Instant::now();
-                        match info.update_cache(&mut cln).await {
-                            Ok(_) => {
-                                debug!(
-                                    "Updated {} lighting gossip in: {:?}",
-                                    cln.symbol(),
-                                    start.elapsed()
-                                );
-                            }
-                            Err(err) => {
-                                warn!("Updating {} lightning gossip failed: {}", cln.symbol(), err);
-                            }
-                        }
-                    }
-                }
-            });
+        match (inbound, outbound) {
+            (Err(err), Err(_)) => Err(err),
+            (inbound, outbound) => Ok(NodeCapacity::from_channels(
+                &inbound.unwrap_or_default(),
+                &outbound.unwrap_or_default(),
+            )),
         }
-
-        info
     }
+    // Both directions of the channels that directly connect the nodes, in the
+    // same order no matter which node is passed first
+    async fn get_channels_between(&self, symbol: &str, a: &[u8], b: &[u8]) -> Result<Vec<Channel>> {
+        let (a_id, b_id) = (hex::encode(a), hex::encode(b));
+        let to_a = self.get_channels(symbol, a).await;
+        let to_b = self.get_channels(symbol, b).await;
 
-    #[instrument(name = "ClnLightningInfo::update_cache", skip_all, fields(symbol = cln.symbol()))]
-    async fn update_cache(&self, cln: &mut Cln) -> Result<()> {
-        let symbol = cln.symbol();
-        info!("Updating {} lightning gossip", symbol);
+        let mut channels = match (to_a, to_b) {
+            (Err(err), Err(_)) => return Err(err),
+            (to_a, to_b) => to_a
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|channel| channel.source.id == b_id)
+                .chain(
+                    to_b.unwrap_or_default()
+                        .into_iter()
+                        .filter(|channel| channel.source.id == a_id),
+                )
+                .collect::<Vec<_>>(),
+        };
+        channels.sort_by(|x, y| {
+            x.short_channel_id
+                .cmp(&y.short_channel_id)
+                .then_with(|| x.source.id.cmp(&y.source.id))
+        });
 
-        let node_infos = self.update_nodes(&symbol, cln).await?;
+        Ok(channels)
+    }
+    // None when no channels to the node are cached
+    async fn get_node_policy_summary(
+        &self,
+        symbol: &str,
+        node: &[u8],
+    ) -> Result<Option<PolicySummary>>;
+    async fn gossip_stats(&self, symbol: &str) -> Option<GossipStats>;
+    async fn gossip_diagnostics(&self, symbol: &str) -> Option<GossipDiagnostics>;
+    async fn gossip_age(&self, symbol: &str) -> Option<Duration>;
+    async fn is_gossip_fresh(&self, symbol: &str) -> bool;
 
-        let mut channel_infos = HashMap::<String, ChannelInfo>::new();
-        let mut channels_to_nodes = HashMap::<Vec<u8>, Vec<Channel>>::new();
+    // Symbols for which gossip has been loaded, sorted alphabetically
+    async fn loaded_symbols(&self) -> Vec<String>;
+    async fn node_count(&self, symbol: &str) -> Option<usize>;
 
-        for channel_raw in cln.list_channels(None).await? {
-            if !channel_raw.public {
-                continue;
-            }
+    // Refreshes the gossip of a currency right away instead of waiting for the interval
+    async fn refresh_now(&self, symbol: &str) -> Result<()>;
 
-            let source_info = match node_infos.get(&hex::encode(&channel_raw.source)) {
-                Some(info) => info,
-                None => continue,
-            };
+    // At most limit nodes and limit channels after the cursor; channels are read from
+    // the cache in chunks of the ones written in the last refresh
+    async fn export_graph(
+        &self,
+        symbol: &str,
+        cursor: &GraphCursor,
+        limit: usize,
+    ) -> Result<GraphExport>;
 
-            let destination = channel_raw.destination.clone();
-            let channel: Channel = (channel_raw, source_info.clone()).into();
+    // Ranked by the summed capacity of their public channels from the last refresh
+    async fn top_nodes_by_capacity(&self, symbol: &str, n: usize) -> Result<Vec<RankedNode>>;
 
-            channel_infos
-                .entry(channel.short_channel_id.clone())
-                .or_insert_with(|| ChannelInfo {
-                    short_channel_id: channel.short_channel_id.clone(),
-                    capacity_sat: channel.capacity_sat,
-                    policies: Vec::new(),
-                })
-                .policies
-                .push(ChannelInfoSide {
-                    node: source_info.clone(),
-                    policy: channel.info.clone(),
-                });
+    // How long the cached info of a node has left before it expires
+    async fn node_ttl(&self, symbol: &str, node: &[u8]) -> Result<Option<Duration>>;
+}
 
-            channels_to_nodes
-                .entry(destination)
-                .or_default()
-                .push(channel);
-        }
+#[derive(Clone)]
+pub struct ClnLightningInfo {
+    cache: Cache,
+    currencies: Currencies,
+    config: LightningInfoConfig,
 
-        for (destination, channels) in channels_to_nodes {
-            let (key, field) = Self::cache_key_channels(&symbol, hex::encode(destination));
-            self.cache
-                .set(&key, &field, &channels, Some(CACHE_TTL_SECS))
-                .await?;
-        }
+    nodes: Arc<RwLock<HashMap<String, GossipNodes>>>,
+    stats: Arc<RwLock<HashMap<String, GossipStats>>>,
+    refreshed: Arc<RwLock<HashMap<String, Instant>>>,
+    // Content hashes of the last values written to the cache
+    written: Arc<RwLock<HashMap<String, WrittenEntry>>>,
+    // Fields written per cache key in the last refresh
+    fields: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+    // When lookups of entries missing from the cache happened
+    misses: Arc<RwLock<HashMap<String, Instant>>>,
+    // Symbols for which gossip is being fetched
+    sources: Arc<Mutex<HashSet<String>>>,
+    // Held while the gossip of a symbol is refreshed
+    refresh_locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
 
-        for (short_channel_id, channel_info) in channel_infos {
-            let (key, field) = Self::cache_key_channel(&symbol, short_channel_id);
-            self.cache
-                .set(&key, &field, &channel_info, Some(CACHE_TTL_SECS))
-                .await?;
-        }
+    // Stops the refresh tasks once the last handle outside of them is dropped
+    cancellation_token: CancellationToken,
+    _shutdown: Option<Arc<DropGuard>>,
+}
 
-        self.nodes.write().await.insert(symbol, node_infos);
+#[derive(Debug)]
+struct Backoff {
+    failures: u32,
+    max: Duration,
+}
 
-        Ok(())
+impl Backoff {
+    fn new(max: Duration) -> Self {
+        Self { failures: 0, max }
     }
 
-    #[instrument(name = "ClnLightningInfo::update_nodes", skip(self, cln))]
-    async fn update_nodes(&self, symbol: &str, cln: &mut Cln) -> Result<HashMap<String, Node>> {
-        let mut infos = HashMap::new();
-        for node in cln.list_nodes(None).await? {
-            let id_hex = hex::encode(&node.nodeid);
-            let node_info = Node {
-                id: id_hex.clone(),
-                alias: node.alias,
-                color: node.color.map(hex::encode),
-            };
-            let (key, field) = Self::cache_key_node(symbol, id_hex.clone());
-            self.cache
-                .set(&key, &field, &node_info, Some(CACHE_TTL_SECS))
-                .await?;
-            infos.insert(id_hex, node_info);
-        }
-
-        Ok(infos)
+    fn reset(&mut self) {
+        self.failures = 0;
     }
 
-    fn scid_lnd_to_cln(s: &str) -> Result<String> {
-        let big: u128 = s.parse::<u128>()?;
-
-        let block: u128 = big >> 40;
-        let tx: u128 = (big >> 16) & 0x00FF_FFFF;
-        let output: u128 = big & 0xFFFF;
+    fn next_delay(&mut self) -> Duration {
+        let base = self.base_delay();
+        self.failures = self.failures.saturating_add(1);
 
-        Ok(format!("{block}x{tx}x{output}"))
+        // Jitter in the upper half of the delay so that currencies failing
+        // at the same time do not retry in lockstep
+        let half = base / 2;
+        half + half.mul_f64(rand::random::<f64>())
     }
 
-    fn cache_key_node(symbol: &str, id: String) -> (String, String) {
-        (format!("cln:{symbol}:node"), id)
+    fn base_delay(&self) -> Duration {
+        BACKOFF_INITIAL_DELAY
+            .saturating_mul(2u32.saturating_pow(self.failures))
+            .min(self.max)
     }
+}
 
-    fn cache_key_channel(symbol: &str, short_channel_id: String) -> (String, String) {
-        (format!("cln:{symbol}:channel"), short_channel_id)
-    }
+#[derive(Clone, Copy, Debug)]
+struct WrittenEntry {
+    hash: u64,
+    written_at: Instant,
+}
 
-    fn cache_key_channels(symbol: &str, destination: String) -> (String, String) {
-        (format!("cln:{symbol}:channels"), destination)
+impl ClnLightningInfo {
+    pub fn new(cache: Cache, currencies: Currencies) -> Self {
+        Self::start(cache, currencies, LightningInfoConfig::default())
     }
-}
 
-#[async_trait]
-impl LightningInfo for ClnLightningInfo {
-    async fn find_node_by_alias(&self, symbol: &str, alias: &str) -> Result<Vec<Node>> {
-        let alias = alias.to_lowercase();
-        let comparator = jaro_winkler::BatchComparator::new(alias.chars());
+    pub fn with_config(
+        cache: Cache,
+        currencies: Currencies,
+        config: LightningInfoConfig,
+    ) -> Result<Self> {
+        config.validate()?;
+        Ok(Self::start(cache, currencies, config))
+    }
 
-        let nodes = self.nodes.read().await;
-        let nodes = match nodes.get(symbol) {
-            Some(nodes) => nodes,
-            None => return Err(anyhow!("no nodes for {}", symbol)),
+    pub(super) async fn refresh_source_now<G: GossipSource + Send>(
+        &self,
+        source: &mut G,
+    ) -> Result<()> {
+        let symbol = source.symbol();
+        let _guard = match self.refresh_lock(&symbol).try_lock_owned() {
+            Ok(guard) => guard,
+            Err(_) => return Err(LookupError::RefreshInProgress(symbol).into()),
         };
 
-        let mut nodes = nodes
-            .values()
-            .filter_map(|node| {
-                node.alias.as_ref().and_then(|cmp| {
-                    let cmp = cmp.to_lowercase();
-                    let distance = comparator.distance(cmp.chars());
-                    if distance <= MAX_DISTANCE || cmp.contains(&alias) {
-                        Some(SearchResult {
-                            distance,
-                            node: node.clone(),
-                        })
-                    } else {
-                        None
-                    }
+        let stats = self.update_cache(source).await?;
+        info!(
+            "Refreshed {} lightning gossip on demand in: {:?}",
+            symbol, stats.last_refresh_duration
+        );
+
+        Ok(())
+    }
+
+    fn refresh_lock(&self, symbol: &str) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = match self.refresh_locks.lock() {
+            Ok(locks) => locks,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        locks.entry(symbol.to_string()).or_default().clone()
+    }
+
+    fn start(cache: Cache, currencies: Currencies, config: LightningInfoConfig) -> Self {
+        let info = Self::build(cache, currencies, config);
+
+        info!(
+            "Updating lightning gossip every: {:?}",
+            info.config.refresh_interval
+        );
+
+        for currency in info.currencies.values() {
+            if let Some(cln) = &currency.cln {
+                info.spawn_refresh(cln.clone());
+            }
+        }
+
+        info
+    }
+
+    // Creates the cache without any refresh tasks
+    pub(super) fn build(cache: Cache, currencies: Currencies, config: LightningInfoConfig) -> Self {
+        let cancellation_token = CancellationToken::new();
+        Self {
+            cache,
+            currencies,
+            config,
+            nodes: Arc::new(RwLock::new(HashMap::new())),
+            stats: Arc::new(RwLock::new(HashMap::new())),
+            refreshed: Arc::new(RwLock::new(HashMap::new())),
+            written: Arc::new(RwLock::new(HashMap::new())),
+            fields: Arc::new(RwLock::new(HashMap::new())),
+            misses: Arc::new(RwLock::new(HashMap::new())),
+            sources: Arc::new(Mutex::new(HashSet::new())),
+            refresh_locks: Arc::new(Mutex::new(HashMap::new())),
+            cancellation_token: cancellation_token.clone(),
+            _shutdown: Some(Arc::new(cancellation_token.drop_guard())),
+        }
+    }
+
+    pub(super) fn spawn_refresh<G: GossipSource + Send + 'static>(
+        &self,
+        mut cln: G,
+    ) -> JoinHandle<()> {
+        match self.sources.lock() {
+            Ok(mut sources) => sources.insert(cln.symbol()),
+            Err(poisoned) => poisoned.into_inner().insert(cln.symbol()),
+        };
+
+        // The task must not keep itself alive
+        let mut info = self.clone();
+        info._shutdown = None;
+
+        tokio::spawn(async move {
+            let symbol = cln.symbol();
+            match info.warm_from_cache(&symbol).await {
+                Ok(0) => {}
+                Ok(count) => debug!("Restored {} cached {} lightning nodes", count, symbol),
+                Err(err) => warn!(
+                    "Could not restore cached {} lightning nodes: {}",
+                    symbol, err
+                ),
+            }
+
+            // The first refresh happens right away instead of after a full interval; the
+            // jitter only keeps all currencies from querying their nodes at the same time
+            tokio::select! {
+                _ = info.cancellation_token.cancelled() => return,
+                _ = tokio::time::sleep(Self::startup_delay()) => {}
+            }
+
+            let mut backoff = Backoff::new(info.config.refresh_interval);
+            loop {
+                let delay = tokio::select! {
+                    _ = info.cancellation_token.cancelled() => break,
+                    delay = info.refresh(&mut cln, &mut backoff) => delay,
+                };
+
+                tokio::select! {
+                    _ = info.cancellation_token.cancelled() => break,
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
+
+            debug!("Stopped updating {} lightning gossip", cln.symbol());
+        })
+    }
+
+    fn missing_nodes(&self, symbol: &str) -> anyhow::Error {
+        let known = match self.sources.lock() {
+            Ok(sources) => sources.contains(symbol),
+            Err(poisoned) => poisoned.into_inner().contains(symbol),
+        };
+
+        if known {
+            LookupError::GossipNotReady(symbol.to_string()).into()
+        } else {
+            LookupError::UnknownSymbol(symbol.to_string()).into()
+        }
+    }
+
+    // Rebuilds the nodes of the previous run from the cache so that they can be
+    // searched before the first refresh; a no-op for caches that cannot list them
+    async fn warm_from_cache(&self, symbol: &str) -> Result<usize> {
+        let (key, _) = self.cache_key_node(symbol, String::new());
+        let nodes = self
+            .cache
+            .get_all::<Node>(&key)
+            .await?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        if nodes.is_empty() {
+            return Ok(0);
+        }
+
+        let count = nodes.len();
+        // Nodes of a refresh that already happened are more recent
+        self.nodes
+            .write()
+            .await
+            .entry(symbol.to_string())
+            .or_insert_with(|| GossipNodes::from(nodes));
+
+        Ok(count)
+    }
+
+    fn startup_delay() -> Duration {
+        STARTUP_JITTER.mul_f64(rand::random::<f64>())
+    }
+
+    // Returns how long to wait before the next refresh
+    async fn refresh<G: GossipSource + Send>(
+        &self,
+        cln: &mut G,
+        backoff: &mut Backoff,
+    ) -> Duration {
+        // Waits for refreshes that were triggered on demand
+        let lock = self.refresh_lock(&cln.symbol());
+        let _guard = lock.lock().await;
+
+        match self.update_cache(cln).await {
+            Ok(stats) => {
+                debug!(
+                    "Updated {} lighting gossip in: {:?}",
+                    cln.symbol(),
+                    stats.last_refresh_duration
+                );
+                backoff.reset();
+
+                self.config
+                    .refresh_interval
+                    .saturating_sub(stats.last_refresh_duration)
+            }
+            Err(err) => {
+                let delay = backoff.next_delay();
+                warn!(
+                    "Updating {} lightning gossip failed: {}; retrying in {:?}",
+                    cln.symbol(),
+                    err,
+                    delay
+                );
+                delay
+            }
+        }
+    }
+
+    #[instrument(name = "ClnLightningInfo::update_cache", skip_all, fields(symbol = cln.symbol()))]
+    async fn update_cache<G: GossipSource + Send>(
+        &self,
+        cln: &mut G,
+    ) -> Result<GossipStats, UpdateError> {
+        let start = Instant::now();
+        let mut phases = RefreshPhases::default();
+
+        let symbol = cln.symbol();
+        info!("Updating {} lightning gossip", symbol);
+
+        let node_infos = self
+            .update_nodes(&symbol, cln, &mut phases)
+            .await
+            .map_err(UpdateError::Nodes)?;
+
+        // Commit the nodes right away so that they stay fresh even when updating the channels fails
+        self.set_nodes(symbol.clone(), node_infos.clone()).await;
+
+        let (channel_count, capacities, channel_counts) = self
+            .update_channels(&symbol, &node_infos, cln, &mut phases)
+            .await
+            .map_err(UpdateError::Channels)?;
+        self.set_capacities(&symbol, capacities, channel_counts)
+            .await;
+
+        let stats = GossipStats {
+            node_count: node_infos.len(),
+            channel_count,
+            last_refresh_duration: start.elapsed(),
+            last_refresh_ts: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or_default(),
+            last_refresh_phases: phases,
+        };
+        debug!(
+            "Fetching {} lightning nodes took {:?}, channels {:?} and writing to the cache {:?}",
+            symbol,
+            stats.last_refresh_phases.node_fetch,
+            stats.last_refresh_phases.channel_fetch,
+            stats.last_refresh_phases.cache_write
+        );
+        self.set_stats(symbol.clone(), stats.clone()).await;
+        self.refreshed.write().await.insert(symbol, Instant::now());
+
+        Ok(stats)
+    }
+
+    async fn set_stats(&self, symbol: String, stats: GossipStats) {
+        #[cfg(feature = "metrics")]
+        {
+            metrics::gauge!(crate::metrics::GOSSIP_NODE_COUNT, "symbol" => symbol.clone())
+                .set(stats.node_count as f64);
+            metrics::gauge!(crate::metrics::GOSSIP_CHANNEL_COUNT, "symbol" => symbol.clone())
+                .set(stats.channel_count as f64);
+            metrics::gauge!(crate::metrics::GOSSIP_REFRESH_DURATION, "symbol" => symbol.clone())
+                .set(stats.last_refresh_duration.as_secs_f64());
+            metrics::gauge!(crate::metrics::GOSSIP_LAST_REFRESH, "symbol" => symbol.clone())
+                .set(stats.last_refresh_ts as f64);
+
+            let phases = &stats.last_refresh_phases;
+            for (phase, duration) in [
+                ("node_fetch", phases.node_fetch),
+                ("channel_fetch", phases.channel_fetch),
+                ("cache_write", phases.cache_write),
+            ] {
+                metrics::histogram!(
+                    crate::metrics::GOSSIP_REFRESH_PHASE_DURATION,
+                    "symbol" => symbol.clone(),
+                    "phase" => phase
+                )
+                .record(duration.as_secs_f64());
+            }
+        }
+
+        self.stats.write().await.insert(symbol, stats);
+    }
+
+    #[instrument(
+        name = "ClnLightningInfo::update_channels",
+        skip(self, node_infos, cln, phases)
+    )]
+    async fn update_channels<G: GossipSource + Send>(
+        &self,
+        symbol: &str,
+        node_infos: &HashMap<String, Node>,
+        cln: &mut G,
+        phases: &mut RefreshPhases,
+    ) -> Result<(usize, HashMap<String, u64>, HashMap<String, usize>)> {
+        let mut channel_infos = HashMap::<String, ChannelInfo>::new();
+        let mut capacities = HashMap::<String, u64>::new();
+        let mut channel_counts = HashMap::<String, usize>::new();
+
+        // Both groupings only store indexes into the list of channels to not duplicate them
+        let mut channels = Vec::<Channel>::new();
+        let mut channels_to_nodes = HashMap::<Vec<u8>, Vec<usize>>::new();
+        let mut channels_from_nodes = HashMap::<Vec<u8>, Vec<usize>>::new();
+        let mut private_channels = HashMap::<String, Vec<Channel>>::new();
+
+        let fetch_start = Instant::now();
+        let channels_raw = self
+            .with_gossip_timeout("channels", cln.gossip_channels())
+            .await?;
+        phases.channel_fetch = fetch_start.elapsed();
+
+        for channel_raw in channels_raw {
+            if !channel_raw.public {
+                let destination = hex::encode(&channel_raw.destination);
+                if !self.is_own_node(&destination) {
+                    continue;
+                }
+
+                // Peers of private channels are often not announced
+                let source_id = hex::encode(&channel_raw.source);
+                let source_info = node_infos.get(&source_id).cloned().unwrap_or(Node {
+                    id: source_id,
+                    alias: None,
+                    color: None,
+                    last_update: None,
+                });
+                let destination_info = node_infos.get(&destination).cloned();
+                private_channels
+                    .entry(destination)
+                    .or_default()
+                    .push((channel_raw, source_info, destination_info).into());
+                continue;
+            }
+
+            let source_info = match node_infos.get(&hex::encode(&channel_raw.source)) {
+                Some(info) => info,
+                None => continue,
+            };
+
+            let source = channel_raw.source.clone();
+            let destination = channel_raw.destination.clone();
+            let destination_info = node_infos.get(&hex::encode(&destination)).cloned();
+            let channel: Channel = (channel_raw, source_info.clone(), destination_info).into();
+
+            // Channels with unknown capacity can't be judged, so they are kept
+            if channel
+                .capacity_sat
+                .is_some_and(|capacity| capacity < self.config.min_channel_capacity_sat)
+            {
+                continue;
+            }
+
+            // Both directions of a channel are in the gossip, but it counts only once per node
+            if !channel_infos.contains_key(&channel.short_channel_id) {
+                let capacity = channel.capacity_sat.unwrap_or_default();
+                for node in [&source, &destination] {
+                    *capacities.entry(hex::encode(node)).or_default() += capacity;
+                    *channel_counts.entry(hex::encode(node)).or_default() += 1;
+                }
+            }
+
+            channel_infos
+                .entry(channel.short_channel_id.clone())
+                .or_insert_with(|| ChannelInfo {
+                    short_channel_id: channel.short_channel_id.clone(),
+                    capacity_sat: channel.capacity_sat,
+                    policies: Vec::new(),
+                })
+                .policies
+                .push(ChannelInfoSide {
+                    node: source_info.clone(),
+                    policy: channel.info.clone(),
+                });
+
+            let index = channels.len();
+            channels.push(channel);
+
+            channels_to_nodes
+                .entry(destination)
+                .or_default()
+                .push(index);
+            channels_from_nodes.entry(source).or_default().push(index);
+        }
+
+        let write_start = Instant::now();
+        let mut destinations = HashSet::new();
+        for (destination, indexes) in channels_to_nodes {
+            let (key, field) = self.cache_key_channels(symbol, hex::encode(destination));
+            let mut resolved = Self::resolve_channels(&channels, &indexes);
+            let truncated =
+                Self::truncate_channels(&mut resolved, self.config.max_channels_per_destination);
+            if truncated {
+                debug!(
+                    "Cached only {} of {} {} channels to {}",
+                    resolved.len(),
+                    indexes.len(),
+                    symbol,
+                    field
+                );
+            }
+
+            self.set_if_changed(
+                &key,
+                &field,
+                &DestinationChannels {
+                    channels: resolved,
+                    truncated,
+                },
+            )
+            .await?;
+            destinations.insert(field);
+        }
+        self.remove_stale(
+            &self.cache_key_channels(symbol, String::new()).0,
+            destinations,
+        )
+        .await?;
+
+        let mut own_nodes = HashSet::new();
+        for (destination, channels) in private_channels {
+            let (key, field) = self.cache_key_private_channels(symbol, destination);
+            self.set_if_changed(&key, &field, &channels).await?;
+            own_nodes.insert(field);
+        }
+        self.remove_stale(
+            &self.cache_key_private_channels(symbol, String::new()).0,
+            own_nodes,
+        )
+        .await?;
+
+        let mut sources = HashSet::new();
+        for (source, indexes) in channels_from_nodes {
+            let (key, field) = self.cache_key_channels_from(symbol, hex::encode(source));
+            self.set_if_changed(&key, &field, &Self::resolve_channels(&channels, &indexes))
+                .await?;
+            sources.insert(field);
+        }
+        self.remove_stale(
+            &self.cache_key_channels_from(symbol, String::new()).0,
+            sources,
+        )
+        .await?;
+
+        let channel_count = channel_infos.len();
+        let mut short_channel_ids = HashSet::new();
+        for (short_channel_id, channel_info) in channel_infos {
+            let (key, field) = self.cache_key_channel(symbol, short_channel_id);
+            self.set_if_changed(&key, &field, &channel_info).await?;
+            short_channel_ids.insert(field);
+        }
+        self.remove_stale(
+            &self.cache_key_channel(symbol, String::new()).0,
+            short_channel_ids,
+        )
+        .await?;
+        phases.cache_write += write_start.elapsed();
+
+        Ok((channel_count, capacities, channel_counts))
+    }
+
+    // Skips writing values that did not change since the last refresh, unless they
+    // would expire before the next one
+    async fn set_if_changed<V: Serialize + Sync>(
+        &self,
+        key: &str,
+        field: &str,
+        value: &V,
+    ) -> Result<()> {
+        let cache_key = format!("{key}:{field}");
+        let hash = match self.changed_hash(&cache_key, value).await? {
+            Some(hash) => hash,
+            None => return Ok(()),
+        };
+
+        self.cache
+            .set(key, field, value, Some(self.config.cache_ttl.as_secs()))
+            .await?;
+        self.mark_written(vec![(cache_key, hash)]).await;
+
+        Ok(())
+    }
+
+    // Returns the hash of the value when it has to be written to the cache
+    async fn changed_hash<V: Serialize>(&self, cache_key: &str, value: &V) -> Result<Option<u64>> {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(&serde_json::to_vec(value)?);
+        let hash = hasher.finish();
+
+        // An unchanged value might have been evicted since it was written
+        if self.cache.is_bounded() {
+            return Ok(Some(hash));
+        }
+
+        let unchanged = match self.written.read().await.get(cache_key) {
+            Some(entry) => {
+                entry.hash == hash
+                    && entry.written_at.elapsed() + self.config.refresh_interval
+                        < self.config.cache_ttl
+            }
+            None => false,
+        };
+
+        Ok(if unchanged { None } else { Some(hash) })
+    }
+
+    async fn mark_written(&self, entries: Vec<(String, u64)>) {
+        let written_at = Instant::now();
+        let mut written = self.written.write().await;
+        let mut misses = self.misses.write().await;
+        for (cache_key, hash) in entries {
+            misses.remove(&cache_key);
+            written.insert(cache_key, WrittenEntry { hash, written_at });
+        }
+    }
+
+    // Answers lookups of entries that were missing recently without querying the cache again
+    async fn get_cached<V: DeserializeOwned>(&self, key: &str, field: &str) -> Result<Option<V>> {
+        let ttl = self.config.negative_cache_ttl;
+        if ttl.is_zero() {
+            return self.cache.get(key, field).await;
+        }
+
+        let cache_key = format!("{key}:{field}");
+        if self
+            .misses
+            .read()
+            .await
+            .get(&cache_key)
+            .is_some_and(|missed_at| missed_at.elapsed() < ttl)
+        {
+            return Ok(None);
+        }
+
+        let value = self.cache.get(key, field).await?;
+
+        let mut misses = self.misses.write().await;
+        match value {
+            Some(_) => {
+                misses.remove(&cache_key);
+            }
+            None => {
+                if misses.len() >= MAX_NEGATIVE_CACHE_ENTRIES {
+                    misses.retain(|_, missed_at| missed_at.elapsed() < ttl);
+                }
+
+                if misses.len() < MAX_NEGATIVE_CACHE_ENTRIES {
+                    misses.insert(cache_key, Instant::now());
+                }
+            }
+        }
+
+        Ok(value)
+    }
+
+    async fn set_nodes(&self, symbol: String, nodes: HashMap<String, Node>) {
+        let nodes = GossipNodes::from(nodes);
+        self.nodes.write().await.insert(symbol, nodes);
+    }
+
+    async fn set_capacities(
+        &self,
+        symbol: &str,
+        capacities: HashMap<String, u64>,
+        channel_counts: HashMap<String, usize>,
+    ) {
+        if let Some(nodes) = self.nodes.write().await.get_mut(symbol) {
+            nodes.capacities = capacities;
+            nodes.channel_counts = channel_counts;
+        }
+    }
+
+    #[instrument(name = "ClnLightningInfo::update_nodes", skip(self, cln, phases))]
+    async fn update_nodes<G: GossipSource + Send>(
+        &self,
+        symbol: &str,
+        cln: &mut G,
+        phases: &mut RefreshPhases,
+    ) -> Result<HashMap<String, Node>> {
+        let fetch_start = Instant::now();
+        let nodes = self
+            .with_gossip_timeout("nodes", cln.gossip_nodes())
+            .await?;
+        phases.node_fetch = fetch_start.elapsed();
+
+        let mut infos = HashMap::new();
+        for node in nodes {
+            let id_hex = hex::encode(&node.nodeid);
+            let node_info = Node {
+                id: id_hex.clone(),
+                alias: node.alias,
+                color: node.color.as_deref().and_then(normalize_color),
+                last_update: node.last_timestamp.map(u64::from),
+            };
+            infos.insert(id_hex, node_info);
+        }
+
+        let write_start = Instant::now();
+        let ttl = Some(self.config.cache_ttl.as_secs());
+        let mut entries = Vec::new();
+        let mut hashes = Vec::new();
+        for node_info in infos.values() {
+            let (key, field) = self.cache_key_node(symbol, node_info.id.clone());
+            let cache_key = format!("{key}:{field}");
+            if let Some(hash) = self.changed_hash(&cache_key, node_info).await? {
+                entries.push((key, field, serde_json::to_value(node_info)?, ttl));
+                hashes.push((cache_key, hash));
+            }
+        }
+
+        Self::write_concurrently(entries.chunks(NODE_WRITE_BATCH_SIZE), |chunk| {
+            self.cache.set_many(chunk)
+        })
+        .await?;
+        self.mark_written(hashes).await;
+
+        self.remove_stale(
+            &self.cache_key_node(symbol, String::new()).0,
+            infos.keys().cloned().collect(),
+        )
+        .await?;
+        phases.cache_write += write_start.elapsed();
+
+        Ok(infos)
+    }
+
+    // Deletes the fields of the key that were written in the last refresh but are gone now
+    async fn remove_stale(&self, key: &str, fields: HashSet<String>) -> Result<()> {
+        let stale = match self.fields.read().await.get(key) {
+            Some(previous) => previous.difference(&fields).cloned().collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+
+        for field in &stale {
+            self.cache.del(key, field).await?;
+        }
+
+        if !stale.is_empty() {
+            debug!("Removed {} stale entries of {}", stale.len(), key);
+
+            let mut written = self.written.write().await;
+            for field in &stale {
+                written.remove(&format!("{key}:{field}"));
+            }
+        }
+
+        self.fields.write().await.insert(key.to_string(), fields);
+        Ok(())
+    }
+
+    // A stalled gossip source must not block the refresh loop forever
+    async fn with_gossip_timeout<T>(
+        &self,
+        kind: &str,
+        call: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        match tokio::time::timeout(self.config.gossip_timeout, call).await {
+            Ok(res) => res,
+            Err(_) => Err(anyhow!(
+                "fetching {} from the gossip source timed out after {:?}",
+                kind,
+                self.config.gossip_timeout
+            )),
+        }
+    }
+
+    async fn write_concurrently<I, F, Fut>(items: I, write: F) -> Result<()>
+    where
+        I: IntoIterator,
+        F: FnMut(I::Item) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        futures::stream::iter(items)
+            .map(write)
+            .buffer_unordered(CACHE_WRITE_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        Ok(())
+    }
+
+    // Exact matches have a distance of 0, no matter what the fuzzy metric makes of them
+    fn alias_matches(&self, nodes: &GossipNodes, alias: &str) -> Vec<SearchResult<Node>> {
+        let mut matches = nodes
+            .aliases
+            .search(
+                alias,
+                self.config.search_metric,
+                self.config.max_distance(),
+                self.config
+                    .substring_match
+                    .then_some(self.config.substring_match_mode),
+            )
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        for id in nodes.aliases.exact(alias) {
+            matches.insert(id, 0.0);
+        }
+
+        matches
+            .into_iter()
+            .filter_map(|(id, distance)| {
+                nodes.nodes.get(id).map(|node| SearchResult {
+                    distance,
+                    node: node.clone(),
                 })
             })
+            .collect()
+    }
+
+    // Only the entries of the page are cloned, not everything after the cursor
+    fn page_after<'a, T: Clone + 'a>(
+        entries: impl Iterator<Item = &'a T>,
+        id: impl Fn(&T) -> &String,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Vec<T> {
+        let mut page = entries
+            .filter(|entry| after.is_none_or(|after| id(entry).as_str() > after))
             .collect::<Vec<_>>();
-        nodes.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
+        page.sort_by(|a, b| id(a).cmp(id(b)));
+        page.truncate(limit);
+
+        page.into_iter().cloned().collect()
+    }
+
+    fn is_own_node(&self, id: &str) -> bool {
+        self.config
+            .own_node_ids
+            .iter()
+            .any(|own| own.eq_ignore_ascii_case(id))
+    }
+
+    fn is_excluded(&self, id: &str) -> bool {
+        self.config
+            .exclude_node_ids
+            .iter()
+            .any(|excluded| excluded.eq_ignore_ascii_case(id))
+    }
+
+    // Blends the alias distance with how well connected the nodes are. Capacities are
+    // compared on a log scale relative to the best connected result, because they span
+    // many orders of magnitude
+    fn weigh_by_capacity(
+        results: &mut [SearchResult<Node>],
+        capacities: &HashMap<String, u64>,
+        weight: f64,
+    ) {
+        let connectivity =
+            |node: &Node| (capacities.get(&node.id).copied().unwrap_or_default() as f64).ln_1p();
+
+        let max = results
+            .iter()
+            .map(|result| connectivity(&result.node))
+            .fold(0.0, f64::max);
+
+        for result in results {
+            let connectivity = if max > 0.0 {
+                connectivity(&result.node) / max
+            } else {
+                0.0
+            };
+            result.distance = (1.0 - weight) * result.distance + weight * (1.0 - connectivity);
+        }
+    }
+
+    pub(super) fn merge_search_results(
+        results: Vec<(String, Vec<(Node, f64)>)>,
+    ) -> Vec<SearchResult<NodeSymbols>> {
+        let mut merged: HashMap<String, SearchResult<NodeSymbols>> = HashMap::new();
+
+        for (symbol, nodes) in results {
+            for (node, distance) in nodes {
+                match merged.get_mut(&node.id) {
+                    Some(existing) => {
+                        existing.node.symbols.push(symbol.clone());
+                        if distance < existing.distance {
+                            existing.distance = distance;
+                            existing.node.node = node;
+                        }
+                    }
+                    None => {
+                        merged.insert(
+                            node.id.clone(),
+                            SearchResult {
+                                distance,
+                                node: NodeSymbols {
+                                    node,
+                                    symbols: vec![symbol.clone()],
+                                },
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut merged = merged.into_values().collect::<Vec<_>>();
+        merged.sort_by(|a, b| {
+            a.distance
+                .partial_cmp(&b.distance)
+                .unwrap()
+                .then_with(|| a.node.node.id.cmp(&b.node.node.id))
+        });
+
+        merged
+    }
+
+    fn scid_lnd_to_cln(s: &str) -> Result<String> {
+        let big: u128 = s.parse::<u128>()?;
+
+        let block: u128 = big >> 40;
+        let tx: u128 = (big >> 16) & 0x00FF_FFFF;
+        let output: u128 = big & 0xFFFF;
 
-        Ok(nodes.into_iter().map(|r| r.node).collect())
+        Ok(format!("{block}x{tx}x{output}"))
+    }
+
+    fn cache_key_node(&self, symbol: &str, id: String) -> (String, String) {
+        (format!("{}:{symbol}:node", self.config.cache_prefix), id)
+    }
+
+    fn cache_key_channel(&self, symbol: &str, short_channel_id: String) -> (String, String) {
+        (
+            format!("{}:{symbol}:channel", self.config.cache_prefix),
+            short_channel_id,
+        )
+    }
+
+    fn cache_key_channels(&self, symbol: &str, destination: String) -> (String, String) {
+        (
+            format!("{}:{symbol}:channels", self.config.cache_prefix),
+            destination,
+        )
+    }
+
+    fn cache_key_private_channels(&self, symbol: &str, destination: String) -> (String, String) {
+        (
+            format!("{}:{symbol}:private_channels", self.config.cache_prefix),
+            destination,
+        )
+    }
+
+    fn cache_key_channels_from(&self, symbol: &str, source: String) -> (String, String) {
+        (
+            format!("{}:{symbol}:channels_from", self.config.cache_prefix),
+            source,
+        )
+    }
+
+    // Biggest channels first; the ones with unknown capacity go last
+    pub(super) fn sort_by_capacity(channels: &mut [Channel]) {
+        channels.sort_by(|a, b| {
+            b.capacity_sat
+                .cmp(&a.capacity_sat)
+                .then_with(|| a.short_channel_id.cmp(&b.short_channel_id))
+        });
+    }
+
+    // Keeps one entry per channel and prefers the direction that ends at the destination,
+    // because its policy is the one that applies when routing to it
+    fn dedupe_channels(channels: Vec<Channel>, destination: &str) -> Vec<Channel> {
+        let ends_at_destination = |channel: &Channel| {
+            channel
+                .destination
+                .as_ref()
+                .is_some_and(|node| node.id == destination)
+                && channel.source.id != destination
+        };
+
+        let mut deduped = Vec::<Channel>::with_capacity(channels.len());
+        let mut indexes = HashMap::<String, usize>::new();
+        for channel in channels {
+            match indexes.get(&channel.short_channel_id) {
+                Some(index) => {
+                    if !ends_at_destination(&deduped[*index]) && ends_at_destination(&channel) {
+                        deduped[*index] = channel;
+                    }
+                }
+                None => {
+                    indexes.insert(channel.short_channel_id.clone(), deduped.len());
+                    deduped.push(channel);
+                }
+            }
+        }
+
+        deduped
+    }
+
+    fn resolve_channels<'a>(channels: &'a [Channel], indexes: &[usize]) -> Vec<&'a Channel> {
+        indexes.iter().map(|index| &channels[*index]).collect()
+    }
+
+    // Keeps the channels with the highest capacity; returns whether any were dropped
+    fn truncate_channels(channels: &mut Vec<&Channel>, max: Option<usize>) -> bool {
+        let max = match max {
+            Some(max) if channels.len() > max => max,
+            _ => return false,
+        };
+
+        channels.sort_by(|a, b| {
+            b.capacity_sat
+                .cmp(&a.capacity_sat)
+                .then_with(|| a.short_channel_id.cmp(&b.short_channel_id))
+        });
+        channels.truncate(max);
+        true
+    }
+}
+
+#[async_trait]
+impl LightningInfo for ClnLightningInfo {
+    async fn find_node_by_alias(&self, symbol: &str, alias: &str) -> Result<Vec<Node>> {
+        Ok(self
+            .find_node_by_alias_scored(symbol, alias)
+            .await?
+            .into_iter()
+            .map(|(node, _)| node)
+            .collect())
+    }
+
+    async fn find_node_by_alias_scored(
+        &self,
+        symbol: &str,
+        alias: &str,
+    ) -> Result<Vec<(Node, f64)>> {
+        let nodes = self.nodes.read().await;
+        let nodes = match nodes.get(symbol) {
+            Some(nodes) => nodes,
+            None => return Err(self.missing_nodes(symbol)),
+        };
+        let capacities = &nodes.capacities;
+
+        let mut nodes = self.alias_matches(nodes, alias);
+        if self.config.capacity_weight > 0.0 {
+            Self::weigh_by_capacity(&mut nodes, capacities, self.config.capacity_weight);
+        }
+        nodes.sort_by(|a, b| {
+            a.distance
+                .partial_cmp(&b.distance)
+                .unwrap()
+                .then_with(|| a.node.id.cmp(&b.node.id))
+        });
+
+        // Filtered after ranking so that the capacity weighing of the others is not affected
+        Ok(nodes
+            .into_iter()
+            .filter(|r| !self.is_excluded(&r.node.id))
+            .map(|r| (r.node, r.distance))
+            .collect())
+    }
+
+    async fn find_node_by_alias_paged(
+        &self,
+        symbol: &str,
+        alias: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Node>> {
+        Ok(self
+            .find_node_by_alias_scored(symbol, alias)
+            .await?
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(node, _)| node)
+            .collect())
+    }
+
+    async fn find_node_by_alias_all(&self, alias: &str) -> Result<Vec<NodeSymbols>> {
+        let mut symbols = self.nodes.read().await.keys().cloned().collect::<Vec<_>>();
+        symbols.sort();
+
+        let mut results = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            let nodes = self.find_node_by_alias_scored(&symbol, alias).await?;
+            results.push((symbol, nodes));
+        }
+
+        Ok(Self::merge_search_results(results)
+            .into_iter()
+            .map(|r| r.node)
+            .collect())
+    }
+
+    async fn find_node_by_id_prefix(&self, symbol: &str, prefix: &str) -> Result<Vec<Node>> {
+        if prefix.is_empty() || prefix.len() > MAX_NODE_ID_PREFIX_LENGTH {
+            return Err(anyhow!(
+                "node id prefix has to be between 1 and {} characters",
+                MAX_NODE_ID_PREFIX_LENGTH
+            ));
+        }
+        if !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow!("invalid node id prefix: {}", prefix));
+        }
+
+        let nodes = self.nodes.read().await;
+        let nodes = match nodes.get(symbol) {
+            Some(nodes) => nodes,
+            None => return Err(self.missing_nodes(symbol)),
+        };
+
+        let prefix = prefix.to_lowercase();
+        let mut nodes = nodes
+            .nodes
+            .values()
+            .filter(|node| node.id.starts_with(&prefix))
+            .cloned()
+            .collect::<Vec<_>>();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Ok(nodes)
+    }
+
+    async fn get_channels(&self, symbol: &str, destination: &[u8]) -> Result<Vec<Channel>> {
+        let destination = hex::encode(destination);
+        let (key, field) = self.cache_key_channels(symbol, destination.clone());
+        let public = self
+            .get_cached::<DestinationChannels<Channel>>(&key, &field)
+            .await?
+            .map(|cached| cached.channels);
+
+        let private = if self.is_own_node(&destination) {
+            let (key, field) = self.cache_key_private_channels(symbol, destination.clone());
+            self.get_cached::<Vec<Channel>>(&key, &field).await?
+        } else {
+            None
+        };
+
+        let mut channels = match (public, private) {
+            (None, None) => return Err(anyhow!("no channels for node")),
+            (public, private) => public
+                .unwrap_or_default()
+                .into_iter()
+                .chain(private.unwrap_or_default())
+                .collect::<Vec<_>>(),
+        };
+        Self::sort_by_capacity(&mut channels);
+        Ok(Self::dedupe_channels(channels, &destination))
+    }
+
+    async fn get_active_channels(&self, symbol: &str, destination: &[u8]) -> Result<Vec<Channel>> {
+        Ok(self
+            .get_channels(symbol, destination)
+            .await?
+            .into_iter()
+            .filter(|channel| channel.active)
+            .collect())
+    }
+
+    async fn get_channels_from(&self, symbol: &str, source: &[u8]) -> Result<Vec<Channel>> {
+        let (key, field) = self.cache_key_channels_from(symbol, hex::encode(source));
+        if let Some(mut channels) = self.cache.get(&key, &field).await? {
+            Self::sort_by_capacity(&mut channels);
+            return Ok(channels);
+        }
+
+        Err(anyhow!("no channels for node"))
+    }
+
+    async fn get_channel(&self, symbol: &str, short_channel_id: String) -> Result<ChannelInfo> {
+        let short_channel_id = short_channel_id.to_lowercase();
+        let short_channel_id = match short_channel_id.contains("x") {
+            true => short_channel_id,
+            false => Self::scid_lnd_to_cln(&short_channel_id)?,
+        };
+
+        let (key, field) = self.cache_key_channel(symbol, short_channel_id);
+        if let Some(channel) = self.cache.get(&key, &field).await? {
+            return Ok(channel);
+        }
+
+        Err(anyhow!("channel not found"))
+    }
+
+    async fn get_channel_by_scid(&self, symbol: &str, short_channel_id: String) -> Result<Channel> {
+        let info = self.get_channel(symbol, short_channel_id).await?;
+
+        // Prefer the direction that is active; when none is, any direction will do
+        let side_index = info
+            .policies
+            .iter()
+            .position(|side| side.policy.active)
+            .unwrap_or_default();
+        let mut policies = info.policies;
+        if side_index >= policies.len() {
+            return Err(anyhow!("channel not found"));
+        }
+
+        // What remains is the other direction, which starts at the destination
+        let side = policies.swap_remove(side_index);
+        let destination = policies.pop().map(|other| other.node);
+
+        Ok(Channel {
+            source: side.node,
+            short_channel_id: info.short_channel_id,
+            capacity_sat: info.capacity_sat,
+            active: side.policy.active,
+            info: side.policy,
+            destination,
+        })
+    }
+
+    async fn get_node_info(&self, symbol: &str, node: &[u8]) -> Result<Node> {
+        let (key, field) = self.cache_key_node(symbol, hex::encode(node));
+        if let Some(node) = self.get_cached(&key, &field).await? {
+            return Ok(node);
+        }
+
+        Err(anyhow!("node not found"))
+    }
+
+    async fn node_exists(&self, symbol: &str, node: &[u8]) -> Result<bool> {
+        let (key, field) = self.cache_key_node(symbol, hex::encode(node));
+        self.cache.exists(&key, &field).await
+    }
+
+    async fn node_ttl(&self, symbol: &str, node: &[u8]) -> Result<Option<Duration>> {
+        let (key, field) = self.cache_key_node(symbol, hex::encode(node));
+        Ok(self
+            .cache
+            .get_with_ttl::<Node>(&key, &field)
+            .await?
+            .and_then(|(_, ttl)| ttl))
+    }
+
+    async fn get_node_infos(&self, symbol: &str, nodes: &[Vec<u8>]) -> Result<Vec<Option<Node>>> {
+        let keys = nodes
+            .iter()
+            .map(|node| self.cache_key_node(symbol, hex::encode(node)))
+            .collect::<Vec<_>>();
+        self.cache.get_many(&keys).await
+    }
+
+    async fn gossip_age(&self, symbol: &str) -> Option<Duration> {
+        self.refreshed
+            .read()
+            .await
+            .get(symbol)
+            .map(|refreshed| refreshed.elapsed())
+    }
+
+    async fn is_gossip_fresh(&self, symbol: &str) -> bool {
+        match self.gossip_age(symbol).await {
+            Some(age) => age < self.config.cache_ttl,
+            None => false,
+        }
+    }
+
+    async fn get_node_policy_summary(
+        &self,
+        symbol: &str,
+        node: &[u8],
+    ) -> Result<Option<PolicySummary>> {
+        let (key, field) = self.cache_key_channels(symbol, hex::encode(node));
+        Ok(self
+            .get_cached::<DestinationChannels<Channel>>(&key, &field)
+            .await?
+            .and_then(|cached| PolicySummary::from_channels(&cached.channels)))
+    }
+
+    async fn gossip_stats(&self, symbol: &str) -> Option<GossipStats> {
+        self.stats.read().await.get(symbol).cloned()
+    }
+
+    async fn gossip_diagnostics(&self, symbol: &str) -> Option<GossipDiagnostics> {
+        let stats = self.gossip_stats(symbol).await?;
+        let newest_node_update = self.nodes.read().await.get(symbol).and_then(|nodes| {
+            nodes
+                .nodes
+                .values()
+                .filter_map(|node| node.last_update)
+                .max()
+        });
+
+        Some(GossipDiagnostics {
+            node_count: stats.node_count,
+            channel_count: stats.channel_count,
+            last_refresh_ts: stats.last_refresh_ts,
+            newest_node_update,
+        })
+    }
+
+    async fn loaded_symbols(&self) -> Vec<String> {
+        let mut symbols = self.nodes.read().await.keys().cloned().collect::<Vec<_>>();
+        symbols.sort();
+        symbols
+    }
+
+    async fn node_count(&self, symbol: &str) -> Option<usize> {
+        self.nodes
+            .read()
+            .await
+            .get(symbol)
+            .map(|nodes| nodes.nodes.len())
+    }
+
+    async fn refresh_now(&self, symbol: &str) -> Result<()> {
+        match self
+            .currencies
+            .get(symbol)
+            .and_then(|currency| currency.cln.clone())
+        {
+            Some(mut cln) => self.refresh_source_now(&mut cln).await,
+            None => Err(LookupError::UnknownSymbol(symbol.to_string()).into()),
+        }
+    }
+
+    async fn export_graph(
+        &self,
+        symbol: &str,
+        cursor: &GraphCursor,
+        limit: usize,
+    ) -> Result<GraphExport> {
+        let nodes = match self.nodes.read().await.get(symbol) {
+            Some(nodes) => Self::page_after(
+                nodes.nodes.values(),
+                |node| &node.id,
+                cursor.after_node.as_deref(),
+                limit,
+            ),
+            None => return Err(self.missing_nodes(symbol)),
+        };
+
+        let (key, _) = self.cache_key_channel(symbol, String::new());
+        let short_channel_ids = match self.fields.read().await.get(&key) {
+            Some(fields) => Self::page_after(
+                fields.iter(),
+                |short_channel_id| short_channel_id,
+                cursor.after_channel.as_deref(),
+                limit,
+            ),
+            None => Vec::new(),
+        };
+
+        let has_more = nodes.len() == limit || short_channel_ids.len() == limit;
+        let next_node = nodes
+            .last()
+            .map(|node| node.id.clone())
+            .or_else(|| cursor.after_node.clone());
+        let next_channel = short_channel_ids
+            .last()
+            .cloned()
+            .or_else(|| cursor.after_channel.clone());
+
+        let mut channels = Vec::with_capacity(short_channel_ids.len());
+        for chunk in short_channel_ids.chunks(NODE_WRITE_BATCH_SIZE) {
+            let keys = chunk
+                .iter()
+                .map(|short_channel_id| (key.clone(), short_channel_id.clone()))
+                .collect::<Vec<_>>();
+
+            // Entries that expired since the last refresh are skipped
+            channels.extend(
+                self.cache
+                    .get_many::<ChannelInfo>(&keys)
+                    .await?
+                    .into_iter()
+                    .flatten(),
+            );
+        }
+
+        Ok(GraphExport {
+            nodes,
+            channels,
+            next_node,
+            next_channel,
+            has_more,
+        })
+    }
+
+    async fn top_nodes_by_capacity(&self, symbol: &str, n: usize) -> Result<Vec<RankedNode>> {
+        let nodes = self.nodes.read().await;
+        let nodes = match nodes.get(symbol) {
+            Some(nodes) => nodes,
+            None => return Err(self.missing_nodes(symbol)),
+        };
+
+        let mut ranked = nodes
+            .capacities
+            .iter()
+            .filter_map(|(id, capacity_sat)| {
+                nodes.nodes.get(id).map(|node| RankedNode {
+                    node: node.clone(),
+                    capacity_sat: *capacity_sat,
+                    channel_count: nodes.channel_counts.get(id).copied().unwrap_or_default(),
+                })
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| {
+            b.capacity_sat
+                .cmp(&a.capacity_sat)
+                .then_with(|| a.node.id.cmp(&b.node.id))
+        });
+        ranked.truncate(n);
+
+        Ok(ranked)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cache::test::CountingBackend;
+    use crate::cache::{Cache, MemCache};
+    use crate::currencies::{Currencies, Currency};
+    use crate::lightning::cln::cln_rpc::Amount;
+    use crate::lightning::cln::test::cln_client;
+    use crate::service::lightning_info::{ClnLightningInfo, LightningInfo};
+    use crate::wallet::{Bitcoin, Network};
+    use alloy::hex;
+    use bip39::Mnemonic;
+    use mockall::mock;
+    use rstest::rstest;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    mock! {
+        Gossip {}
+
+        #[async_trait]
+        impl BaseClient for Gossip {
+            fn kind(&self) -> String;
+            fn symbol(&self) -> String;
+            async fn connect(&mut self) -> Result<()>;
+        }
+
+        #[async_trait]
+        impl GossipSource for Gossip {
+            async fn gossip_nodes(&mut self) -> Result<Vec<ListnodesNodes>>;
+            async fn gossip_channels(&mut self) -> Result<Vec<ListchannelsChannels>>;
+        }
+    }
+
+    // Hangs forever on the configured call like a stalled socket would
+    struct StalledGossip {
+        stall_nodes: bool,
+    }
+
+    #[async_trait]
+    impl BaseClient for StalledGossip {
+        fn kind(&self) -> String {
+            "Stalled".to_string()
+        }
+
+        fn symbol(&self) -> String {
+            "BTC".to_string()
+        }
+
+        async fn connect(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl GossipSource for StalledGossip {
+        async fn gossip_nodes(&mut self) -> Result<Vec<ListnodesNodes>> {
+            if self.stall_nodes {
+                std::future::pending::<()>().await;
+            }
+
+            Ok(mock_gossip_nodes())
+        }
+
+        async fn gossip_channels(&mut self) -> Result<Vec<ListchannelsChannels>> {
+            std::future::pending().await
+        }
+    }
+
+    fn mock_gossip_nodes() -> Vec<ListnodesNodes> {
+        vec![
+            ListnodesNodes {
+                nodeid: vec![2; 33],
+                alias: Some("Boltz".to_string()),
+                ..Default::default()
+            },
+            ListnodesNodes {
+                nodeid: vec![3; 33],
+                alias: Some("bfx-lnd0".to_string()),
+                ..Default::default()
+            },
+        ]
+    }
+
+    async fn get_currencies() -> Currencies {
+        Arc::new(HashMap::<String, Currency>::from([(
+            "BTC".to_string(),
+            Currency {
+                network: Network::Regtest,
+                wallet: Arc::new(
+                    Bitcoin::new(
+                        Network::Regtest,
+                        &Mnemonic::from_str(
+                            "test test test test test test test test test test test junk",
+                        )
+                        .unwrap()
+                        .to_seed(""),
+                        "m/0/0".to_string(),
+                    )
+                    .unwrap(),
+                ),
+                chain: Some(Arc::new(Box::new(
+                    crate::chain::chain_client::test::get_client(),
+                ))),
+                cln: Some(cln_client().await),
+                lnd: None,
+                eclair: None,
+            },
+        )]))
+    }
+
+    fn test_nodes() -> HashMap<String, Node> {
+        let mut nodes = HashMap::new();
+        nodes.insert(
+            "1".to_string(),
+            Node {
+                id: "1".to_string(),
+                alias: None,
+                color: None,
+                last_update: None,
+            },
+        );
+        nodes.insert(
+            "026165850492521f4ac8abd9bd8088123446d126f648ca35e60f88177dc149ceb2".to_string(),
+            Node {
+                id: "026165850492521f4ac8abd9bd8088123446d126f648ca35e60f88177dc149ceb2"
+                    .to_string(),
+                alias: Some("Boltz".to_string()),
+                color: None,
+                last_update: None,
+            },
+        );
+        nodes.insert(
+            "02d96eadea3d780104449aca5c93461ce67c1564e2e1d73225fa67dd3b997a6018".to_string(),
+            Node {
+                id: "02d96eadea3d780104449aca5c93461ce67c1564e2e1d73225fa67dd3b997a6018"
+                    .to_string(),
+                alias: Some("Boltz|CLN".to_string()),
+                color: None,
+                last_update: None,
+            },
+        );
+        nodes.insert(
+            "033d8656219478701227199cbd6f670335c8d408a92ae88b962c49d4dc0e83e025".to_string(),
+            Node {
+                id: "033d8656219478701227199cbd6f670335c8d408a92ae88b962c49d4dc0e83e025"
+                    .to_string(),
+                alias: Some("bfx-lnd0".to_string()),
+                color: None,
+                last_update: None,
+            },
+        );
+        nodes.insert(
+            "03cde60a6323f7122d5178255766e38114b4722ede08f7c9e0c5df9b912cc201d6".to_string(),
+            Node {
+                id: "03cde60a6323f7122d5178255766e38114b4722ede08f7c9e0c5df9b912cc201d6"
+                    .to_string(),
+                alias: Some("bfx-lnd1".to_string()),
+                color: None,
+                last_update: None,
+            },
+        );
+        nodes
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias() {
+        let mem_cache = MemCache::new();
+        let cache = Cache::Memory(mem_cache);
+        let currencies = get_currencies().await;
+
+        let info = ClnLightningInfo::new(cache.clone(), currencies.clone());
+        info.set_nodes("BTC".to_string(), test_nodes()).await;
+
+        let nodes = info.find_node_by_alias("BTC", "test").await;
+        assert!(nodes.is_ok());
+        assert!(nodes.unwrap().is_empty());
+
+        let nodes = info.find_node_by_alias("BTC", "BOLTZ").await;
+        assert!(nodes.is_ok());
+        let nodes = nodes.unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes.iter().any(|n| n.alias == Some("Boltz".to_string())));
+        assert!(
+            nodes
+                .iter()
+                .any(|n| n.alias == Some("Boltz|CLN".to_string()))
+        );
+
+        let nodes = info.find_node_by_alias("BTC", "bfx").await;
+        assert!(nodes.is_ok());
+        let nodes = nodes.unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert!(
+            nodes
+                .iter()
+                .any(|n| n.alias == Some("bfx-lnd0".to_string()))
+        );
+        assert!(
+            nodes
+                .iter()
+                .any(|n| n.alias == Some("bfx-lnd1".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_scored() {
+        let cache = Cache::Memory(MemCache::new());
+        let currencies = Arc::new(HashMap::new());
+
+        let info = ClnLightningInfo::new(cache, currencies);
+        info.set_nodes("BTC".to_string(), test_nodes()).await;
+
+        let nodes = info
+            .find_node_by_alias_scored("BTC", "boltz")
+            .await
+            .unwrap();
+        assert_eq!(nodes.len(), 2);
+
+        assert_eq!(nodes[0].0.alias, Some("Boltz".to_string()));
+        assert_eq!(nodes[0].1, 0.0);
+
+        assert_eq!(nodes[1].0.alias, Some("Boltz|CLN".to_string()));
+        assert!(nodes[1].1 > 0.0);
+
+        // Substring matches report their actual distance
+        let nodes = info.find_node_by_alias_scored("BTC", "cln").await.unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].0.alias, Some("Boltz|CLN".to_string()));
+        assert!(nodes[0].1 > LightningInfoConfig::default().max_distance());
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_exclude_node_ids() {
+        let excluded = "026165850492521f4ac8abd9bd8088123446d126f648ca35e60f88177dc149ceb2";
+        let info = ClnLightningInfo::build(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                exclude_node_ids: vec![excluded.to_uppercase()],
+                ..Default::default()
+            },
+        );
+        info.set_nodes("BTC".to_string(), test_nodes()).await;
+
+        // The excluded node is the exact match
+        let nodes = info.find_node_by_alias("BTC", "Boltz").await.unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].alias, Some("Boltz|CLN".to_string()));
+
+        let scored = info
+            .find_node_by_alias_scored("BTC", "boltz")
+            .await
+            .unwrap();
+        assert_eq!(scored.len(), 1);
+        assert_eq!(scored[0].0.alias, Some("Boltz|CLN".to_string()));
+        assert!(scored[0].1 > 0.0);
+
+        let paged = info
+            .find_node_by_alias_paged("BTC", "boltz", 10, 0)
+            .await
+            .unwrap();
+        assert!(paged.iter().all(|node| node.id != excluded));
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_all() {
+        let cache = Cache::Memory(MemCache::new());
+        let currencies = Arc::new(HashMap::new());
+
+        let info = ClnLightningInfo::new(cache, currencies);
+        info.set_nodes("BTC".to_string(), test_nodes()).await;
+
+        let mut liquid_nodes = HashMap::new();
+        for node in test_nodes()
+            .into_values()
+            .filter(|node| node.alias == Some("Boltz".to_string()))
+        {
+            liquid_nodes.insert(node.id.clone(), node);
+        }
+        liquid_nodes.insert(
+            "liquid-only".to_string(),
+            Node {
+                id: "liquid-only".to_string(),
+                alias: Some("Boltz Liquid".to_string()),
+                color: None,
+                last_update: None,
+            },
+        );
+        info.set_nodes("L-BTC".to_string(), liquid_nodes).await;
+
+        let nodes = info.find_node_by_alias_all("boltz").await.unwrap();
+        assert_eq!(nodes.len(), 3);
+
+        assert_eq!(nodes[0].node.alias, Some("Boltz".to_string()));
+        assert_eq!(
+            nodes[0].symbols,
+            vec!["BTC".to_string(), "L-BTC".to_string()]
+        );
+
+        assert_eq!(
+            nodes
+                .iter()
+                .filter(|n| n.node.id == nodes[0].node.id)
+                .count(),
+            1
+        );
+        assert!(
+            nodes
+                .iter()
+                .any(|n| n.node.id == "liquid-only" && n.symbols == vec!["L-BTC".to_string()])
+        );
+        assert!(
+            nodes
+                .iter()
+                .any(|n| n.node.alias == Some("Boltz|CLN".to_string())
+                    && n.symbols == vec!["BTC".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_merge_search_results_keeps_best_distance() {
+        let node = |alias: &str| Node {
+            id: "id".to_string(),
+            alias: Some(alias.to_string()),
+            color: None,
+            last_update: None,
+        };
+
+        let merged = ClnLightningInfo::merge_search_results(vec![
+            ("BTC".to_string(), vec![(node("worse"), 0.08)]),
+            ("L-BTC".to_string(), vec![(node("better"), 0.01)]),
+        ]);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].distance, 0.01);
+        assert_eq!(merged[0].node.node.alias, Some("better".to_string()));
+        assert_eq!(
+            merged[0].node.symbols,
+            vec!["BTC".to_string(), "L-BTC".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_node_symbols_serialize() {
+        let node = NodeSymbols {
+            node: Node {
+                id: "id".to_string(),
+                alias: Some("Boltz".to_string()),
+                color: None,
+                last_update: None,
+            },
+            symbols: vec!["BTC".to_string()],
+        };
+
+        assert_eq!(
+            serde_json::to_string(&node).unwrap(),
+            "{\"id\":\"id\",\"alias\":\"Boltz\",\"symbols\":[\"BTC\"]}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_paged() {
+        let cache = Cache::Memory(MemCache::new());
+        let currencies = Arc::new(HashMap::new());
+
+        let info = ClnLightningInfo::new(cache, currencies);
+        info.set_nodes(
+            "BTC".to_string(),
+            (0..50)
+                .map(|i| {
+                    let id = i.to_string();
+                    (
+                        id.clone(),
+                        Node {
+                            id,
+                            alias: Some(format!("query{}", "a".repeat(i))),
+                            color: None,
+                            last_update: None,
+                        },
+                    )
+                })
+                .collect(),
+        )
+        .await;
+
+        let nodes = info.find_node_by_alias("BTC", "query").await.unwrap();
+        assert_eq!(nodes.len(), 50);
+
+        let nodes = info
+            .find_node_by_alias_paged("BTC", "query", 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(nodes.len(), 10);
+        assert_eq!(
+            nodes.iter().map(|n| n.id.clone()).collect::<Vec<_>>(),
+            (0..10).map(|i| i.to_string()).collect::<Vec<_>>()
+        );
+
+        let nodes = info
+            .find_node_by_alias_paged("BTC", "query", 10, 45)
+            .await
+            .unwrap();
+        assert_eq!(
+            nodes.iter().map(|n| n.id.clone()).collect::<Vec<_>>(),
+            (45..50).map(|i| i.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    async fn info_with_ids(ids: &[&str]) -> ClnLightningInfo {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        info.set_nodes(
+            "BTC".to_string(),
+            ids.iter()
+                .map(|id| {
+                    (
+                        id.to_string(),
+                        Node {
+                            id: id.to_string(),
+                            alias: None,
+                            color: None,
+                            last_update: None,
+                        },
+                    )
+                })
+                .collect(),
+        )
+        .await;
+
+        info
+    }
+
+    #[rstest]
+    #[case("02a", vec!["02a1", "02a2"])]
+    #[case("02A", vec!["02a1", "02a2"])]
+    #[case("02a1", vec!["02a1"])]
+    #[case("0", vec!["02a1", "02a2", "03b1"])]
+    #[case("04", vec![])]
+    #[tokio::test]
+    async fn test_find_node_by_id_prefix(#[case] prefix: &str, #[case] expected: Vec<&str>) {
+        let info = info_with_ids(&["03b1", "02a2", "02a1"]).await;
+
+        let nodes = info.find_node_by_id_prefix("BTC", prefix).await.unwrap();
+        assert_eq!(
+            nodes
+                .iter()
+                .map(|node| node.id.as_str())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("BTC", "02xz", "invalid node id prefix: 02xz")]
+    #[case("BTC", "", "node id prefix has to be between 1 and 66 characters")]
+    #[case(
+        "BTC",
+        &"0".repeat(67),
+        "node id prefix has to be between 1 and 66 characters"
+    )]
+    #[case("L-BTC", "02", "no gossip source for L-BTC")]
+    #[tokio::test]
+    async fn test_find_node_by_id_prefix_err(
+        #[case] symbol: &str,
+        #[case] prefix: &str,
+        #[case] expected: &str,
+    ) {
+        let info = info_with_ids(&["02a1"]).await;
+
+        assert_eq!(
+            info.find_node_by_id_prefix(symbol, prefix)
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_exact() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        info.set_nodes(
+            "BTC".to_string(),
+            [
+                "boltz",
+                "Boltz",
+                "boltz1",
+                "boltz12",
+                "bolts",
+                "xboltz",
+                "boltz|cln",
+            ]
+            .into_iter()
+            .enumerate()
+            .map(|(i, alias)| {
+                let id = format!("{i:066x}");
+                (
+                    id.clone(),
+                    Node {
+                        id,
+                        alias: Some(alias.to_string()),
+                        color: None,
+                        last_update: None,
+                    },
+                )
+            })
+            .collect(),
+        )
+        .await;
+
+        let aliases = |nodes: &[Node]| {
+            nodes
+                .iter()
+                .map(|node| node.alias.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        // Exact matches come first, followed by the fuzzy ones
+        let scored = info
+            .find_node_by_alias_scored("BTC", "BOLTZ")
+            .await
+            .unwrap();
+        assert_eq!(scored[0].0.alias, Some("boltz".to_string()));
+        assert_eq!(scored[0].1, 0.0);
+        assert_eq!(scored[1].0.alias, Some("Boltz".to_string()));
+        assert_eq!(scored[1].1, 0.0);
+        assert!(scored[2..].iter().all(|(_, distance)| *distance > 0.0));
+
+        let nodes = info.find_node_by_alias("BTC", "BOLTZ").await.unwrap();
+        assert_eq!(
+            aliases(&nodes),
+            scored
+                .iter()
+                .map(|(node, _)| node.alias.clone().unwrap())
+                .collect::<Vec<_>>()
+        );
+        let paged = info
+            .find_node_by_alias_paged("BTC", "BOLTZ", 2, 0)
+            .await
+            .unwrap();
+        assert_eq!(aliases(&paged), vec!["boltz", "Boltz"]);
+
+        let scored = info
+            .find_node_by_alias_scored("BTC", "boltz1")
+            .await
+            .unwrap();
+        assert_eq!(scored[0].0.alias, Some("boltz1".to_string()));
+        assert_eq!(scored[0].1, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_many_nodes() {
+        let cache = Cache::Memory(MemCache::new());
+        let currencies = Arc::new(HashMap::new());
+
+        let info = ClnLightningInfo::new(cache, currencies);
+        info.set_nodes(
+            "BTC".to_string(),
+            (0..10_000)
+                .map(|i| {
+                    let id = format!("{i:066x}");
+                    (
+                        id.clone(),
+                        Node {
+                            id,
+                            alias: Some(format!("node-{i}-{}", i % 97)),
+                            color: None,
+                            last_update: None,
+                        },
+                    )
+                })
+                .collect(),
+        )
+        .await;
+
+        // Substring matching only compares the aliases that contain every n-gram of the query
+        {
+            let nodes = info.nodes.read().await;
+            let aliases = &nodes.get("BTC").unwrap().aliases;
+            for (query, candidates) in [("node-1234", 1), ("boltz", 0), ("-42", 212)] {
+                assert_eq!(
+                    aliases
+                        .substring_candidates(&query.chars().collect::<Vec<_>>())
+                        .len(),
+                    candidates
+                );
+            }
+        }
+
+        let nodes = info.find_node_by_alias("BTC", "node-1234-").await.unwrap();
+        assert!(
+            nodes
+                .iter()
+                .any(|n| n.alias == Some("node-1234-70".to_string()))
+        );
+    }
+
+    #[rstest]
+    #[case(SubstringMatch::Anywhere, vec!["bfx-lnd0", "bfx-lnd1"])]
+    #[case(SubstringMatch::WordBoundary, vec![])]
+    #[tokio::test]
+    async fn test_find_node_by_alias_substring_match_mode(
+        #[case] substring_match_mode: SubstringMatch,
+        #[case] mid_word: Vec<&str>,
+    ) {
+        let info = ClnLightningInfo::with_config(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                substring_match_mode,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        info.set_nodes("BTC".to_string(), test_nodes()).await;
+
+        let aliases = |nodes: Vec<Node>| {
+            let mut aliases = nodes
+                .into_iter()
+                .filter_map(|node| node.alias)
+                .collect::<Vec<_>>();
+            aliases.sort();
+            aliases
+        };
+
+        assert_eq!(
+            aliases(info.find_node_by_alias("BTC", "bfx").await.unwrap()),
+            vec!["bfx-lnd0", "bfx-lnd1"]
+        );
+        assert_eq!(
+            aliases(info.find_node_by_alias("BTC", "cln").await.unwrap()),
+            vec!["Boltz|CLN"]
+        );
+        assert_eq!(
+            aliases(info.find_node_by_alias("BTC", "nd").await.unwrap()),
+            mid_word
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_custom_threshold() {
+        let cache = Cache::Memory(MemCache::new());
+        let currencies = get_currencies().await;
+
+        let info = ClnLightningInfo::with_config(
+            cache,
+            currencies,
+            LightningInfoConfig {
+                max_distance: Some(0.02),
+                substring_match: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        info.set_nodes("BTC".to_string(), test_nodes()).await;
+
+        let nodes = info.find_node_by_alias("BTC", "Boltz").await.unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].alias, Some("Boltz".to_string()));
+        assert!(
+            !nodes
+                .iter()
+                .any(|n| n.alias == Some("Boltz|CLN".to_string()))
+        );
+    }
+
+    fn mock_gossip_capacities() -> MockGossip {
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip.expect_gossip_nodes().returning(|| {
+            Ok([2, 3, 4]
+                .into_iter()
+                .map(|id| ListnodesNodes {
+                    nodeid: vec![id; 33],
+                    alias: Some(if id == 3 { "bfx-lnd0" } else { "Boltz" }.to_string()),
+                    ..Default::default()
+                })
+                .collect())
+        });
+        gossip.expect_gossip_channels().returning(|| {
+            Ok([
+                (2, 3, "1x1x1", 10_000),
+                (3, 2, "1x1x1", 10_000),
+                (4, 3, "1x1x2", 5_000_000),
+                (3, 4, "1x1x2", 5_000_000),
+            ]
+            .into_iter()
+            .map(
+                |(source, destination, short_channel_id, capacity)| ListchannelsChannels {
+                    source: vec![source; 33],
+                    destination: vec![destination; 33],
+                    short_channel_id: short_channel_id.to_string(),
+                    amount_msat: Some(Amount {
+                        msat: capacity * 1_000,
+                    }),
+                    public: true,
+                    active: true,
+                    ..Default::default()
+                },
+            )
+            .collect())
+        });
+        gossip
+    }
+
+    #[rstest]
+    #[case(0.0, [2, 4])]
+    #[case(0.5, [4, 2])]
+    #[tokio::test]
+    async fn test_find_node_by_alias_capacity_weight(
+        #[case] capacity_weight: f64,
+        #[case] expected: [u8; 2],
+    ) {
+        let info = ClnLightningInfo::with_config(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                capacity_weight,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        info.update_cache(&mut mock_gossip_capacities())
+            .await
+            .unwrap();
+
+        let expected = expected.map(|id| hex::encode([id; 33]));
+
+        let nodes = info.find_node_by_alias("BTC", "Boltz").await.unwrap();
+        assert_eq!(
+            nodes.iter().map(|n| n.id.clone()).collect::<Vec<_>>(),
+            expected
+        );
+
+        let nodes = info.find_node_by_alias("BTC", "Bolt").await.unwrap();
+        assert_eq!(
+            nodes.iter().map(|n| n.id.clone()).collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_capacity_weight_scores() {
+        let info = ClnLightningInfo::with_config(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                capacity_weight: 0.5,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        info.update_cache(&mut mock_gossip_capacities())
+            .await
+            .unwrap();
+
+        let nodes = info
+            .find_node_by_alias_scored("BTC", "Boltz")
+            .await
+            .unwrap();
+        assert_eq!(nodes.len(), 2);
+
+        // The best connected node only keeps its weighted alias distance
+        assert_eq!(nodes[0].0.id, hex::encode([4; 33]));
+        assert_eq!(nodes[0].1, 0.0);
+
+        assert_eq!(nodes[1].0.id, hex::encode([2; 33]));
+        assert!(nodes[1].1 > 0.0 && nodes[1].1 < 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_capacities_count_channels_once() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        info.update_cache(&mut mock_gossip_capacities())
+            .await
+            .unwrap();
+
+        let nodes = info.nodes.read().await;
+        let capacities = &nodes.get("BTC").unwrap().capacities;
+        assert_eq!(capacities.get(&hex::encode([2; 33])), Some(&10_000));
+        assert_eq!(capacities.get(&hex::encode([3; 33])), Some(&5_010_000));
+        assert_eq!(capacities.get(&hex::encode([4; 33])), Some(&5_000_000));
+    }
+
+    #[rstest]
+    #[case(3, vec![(3, 5_010_000, 2), (4, 5_000_000, 1), (2, 10_000, 1)])]
+    #[case(2, vec![(3, 5_010_000, 2), (4, 5_000_000, 1)])]
+    #[case(10, vec![(3, 5_010_000, 2), (4, 5_000_000, 1), (2, 10_000, 1)])]
+    #[case(0, vec![])]
+    #[tokio::test]
+    async fn test_top_nodes_by_capacity(#[case] n: usize, #[case] expected: Vec<(u8, u64, usize)>) {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        info.update_cache(&mut mock_gossip_capacities())
+            .await
+            .unwrap();
+
+        let ranked = info.top_nodes_by_capacity("BTC", n).await.unwrap();
+        assert_eq!(
+            ranked
+                .iter()
+                .map(|ranked| (
+                    ranked.node.id.clone(),
+                    ranked.capacity_sat,
+                    ranked.channel_count
+                ))
+                .collect::<Vec<_>>(),
+            expected
+                .into_iter()
+                .map(|(id, capacity, count)| (hex::encode([id; 33]), capacity, count))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    fn mock_gossip_private() -> MockGossip {
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| {
+            Ok([
+                (2, 3, "1x1x1", true),
+                (3, 2, "1x1x1", true),
+                (4, 3, "2x1x1", false),
+                (4, 2, "3x1x1", false),
+            ]
+            .into_iter()
+            .map(
+                |(source, destination, short_channel_id, public)| ListchannelsChannels {
+                    source: vec![source; 33],
+                    destination: vec![destination; 33],
+                    short_channel_id: short_channel_id.to_string(),
+                    public,
+                    active: true,
+                    ..Default::default()
+                },
+            )
+            .collect())
+        });
+        gossip
+    }
+
+    #[rstest]
+    #[case(vec![], vec!["1x1x1"])]
+    #[case(vec![hex::encode([3; 33])], vec!["1x1x1", "2x1x1"])]
+    #[case(vec![hex::encode([3; 33]).to_uppercase()], vec!["1x1x1", "2x1x1"])]
+    #[tokio::test]
+    async fn test_own_node_private_channels(
+        #[case] own_node_ids: Vec<String>,
+        #[case] expected: Vec<&str>,
+    ) {
+        let info = ClnLightningInfo::with_config(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                own_node_ids,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let stats = info.update_cache(&mut mock_gossip_private()).await.unwrap();
+        assert_eq!(stats.channel_count, 1);
+
+        let short_channel_ids = |channels: Vec<Channel>| {
+            let mut ids = channels
+                .into_iter()
+                .map(|channel| channel.short_channel_id)
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(
+            short_channel_ids(info.get_channels("BTC", &[3; 33]).await.unwrap()),
+            expected
+        );
+
+        // Private channels to other nodes stay filtered
+        assert_eq!(
+            short_channel_ids(info.get_channels("BTC", &[2; 33]).await.unwrap()),
+            vec!["1x1x1"]
+        );
+        assert!(info.get_channels("BTC", &[4; 33]).await.is_err());
+        assert!(info.get_channel("BTC", "2x1x1".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_top_nodes_by_capacity_unknown_symbol() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        assert_eq!(
+            info.top_nodes_by_capacity("BTC", 1)
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "no gossip source for BTC"
+        );
+    }
+
+    #[rstest]
+    #[case(None, 3, false)]
+    #[case(Some(5), 3, false)]
+    #[case(Some(3), 3, false)]
+    #[case(Some(2), 2, true)]
+    #[case(Some(1), 1, true)]
+    #[tokio::test]
+    async fn test_max_channels_per_destination(
+        #[case] max_channels_per_destination: Option<usize>,
+        #[case] expected_count: usize,
+        #[case] expected_truncated: bool,
+    ) {
+        let cache = Cache::Memory(MemCache::new());
+        let info = ClnLightningInfo::with_config(
+            cache.clone(),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                max_channels_per_destination,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| {
+            Ok(
+                [("1x1x1", 10_000), ("1x1x2", 5_000_000), ("1x1x3", 200_000)]
+                    .into_iter()
+                    .map(|(short_channel_id, capacity)| ListchannelsChannels {
+                        source: vec![2; 33],
+                        destination: vec![3; 33],
+                        short_channel_id: short_channel_id.to_string(),
+                        amount_msat: Some(Amount {
+                            msat: capacity * 1_000,
+                        }),
+                        public: true,
+                        active: true,
+                        ..Default::default()
+                    })
+                    .collect(),
+            )
+        });
+        info.update_cache(&mut gossip).await.unwrap();
+
+        let channels = info.get_channels("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(
+            channels
+                .iter()
+                .map(|channel| channel.short_channel_id.as_str())
+                .collect::<Vec<_>>(),
+            ["1x1x2", "1x1x3", "1x1x1"][..expected_count]
+        );
+
+        let (key, field) = info.cache_key_channels("BTC", hex::encode([3; 33]));
+        let cached = cache
+            .get::<DestinationChannels<Channel>>(&key, &field)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cached.truncated, expected_truncated);
+        assert_eq!(cached.channels.len(), expected_count);
+
+        // The outbound channels of the source are not capped
+        assert_eq!(
+            info.get_channels_from("BTC", &[2; 33]).await.unwrap().len(),
+            3
+        );
+    }
+
+    #[rstest]
+    #[case(0, vec!["1x1x2", "1x1x3", "1x1x1", "1x1x4"])]
+    #[case(10_000, vec!["1x1x2", "1x1x3", "1x1x1", "1x1x4"])]
+    #[case(10_001, vec!["1x1x2", "1x1x3", "1x1x4"])]
+    #[case(10_000_000, vec!["1x1x4"])]
+    #[tokio::test]
+    async fn test_min_channel_capacity(
+        #[case] min_channel_capacity_sat: u64,
+        #[case] expected: Vec<&str>,
+    ) {
+        let info = ClnLightningInfo::with_config(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                min_channel_capacity_sat,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| {
+            Ok([
+                ("1x1x1", Some(10_000)),
+                ("1x1x2", Some(5_000_000)),
+                ("1x1x3", Some(200_000)),
+                ("1x1x4", None),
+            ]
+            .into_iter()
+            .map(|(short_channel_id, capacity)| ListchannelsChannels {
+                source: vec![2; 33],
+                destination: vec![3; 33],
+                short_channel_id: short_channel_id.to_string(),
+                amount_msat: capacity.map(|capacity: u64| Amount {
+                    msat: capacity * 1_000,
+                }),
+                public: true,
+                active: true,
+                ..Default::default()
+            })
+            .collect())
+        });
+        info.update_cache(&mut gossip).await.unwrap();
+
+        let channels = info.get_channels("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(
+            channels
+                .iter()
+                .map(|channel| channel.short_channel_id.as_str())
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            info.get_channels_from("BTC", &[2; 33]).await.unwrap().len(),
+            expected.len()
+        );
+
+        let skipped = info.get_channel("BTC", "1x1x1".to_string()).await;
+        assert_eq!(skipped.is_ok(), expected.contains(&"1x1x1"));
+    }
+
+    #[rstest]
+    #[case(
+        true,
+        "updating nodes failed: fetching nodes from the gossip source timed out after 100ms"
+    )]
+    #[case(
+        false,
+        "updating channels failed: fetching channels from the gossip source timed out after 100ms"
+    )]
+    #[tokio::test]
+    async fn test_update_cache_gossip_timeout(#[case] stall_nodes: bool, #[case] expected: &str) {
+        let gossip_timeout = Duration::from_millis(100);
+        let info = ClnLightningInfo::with_config(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                gossip_timeout,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let start = Instant::now();
+        let res = tokio::time::timeout(
+            gossip_timeout + Duration::from_secs(1),
+            info.update_cache(&mut StalledGossip { stall_nodes }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(res.err().unwrap().to_string(), expected);
+        assert!(start.elapsed() >= gossip_timeout);
+        // The nodes are committed even when fetching the channels stalls
+        assert_eq!(info.node_count("BTC").await.is_some(), !stall_nodes);
+    }
+
+    #[test]
+    fn test_validate_gossip_timeout() {
+        let config = LightningInfoConfig {
+            gossip_timeout: Duration::ZERO,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate().err().unwrap().to_string(),
+            "gossip timeout has to be greater than 0"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_node_capacity() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip.expect_gossip_nodes().returning(|| {
+            Ok([2, 3, 4]
+                .into_iter()
+                .map(|id| ListnodesNodes {
+                    nodeid: vec![id; 33],
+                    ..Default::default()
+                })
+                .collect())
+        });
+        gossip.expect_gossip_channels().returning(|| {
+            Ok([
+                (2, 3, "1x1x1", Some(10_000)),
+                (3, 2, "1x1x1", Some(10_000)),
+                (4, 3, "1x1x2", Some(5_000_000)),
+                (3, 4, "1x1x2", Some(5_000_000)),
+                (4, 3, "1x1x3", None),
+            ]
+            .into_iter()
+            .map(
+                |(source, destination, short_channel_id, capacity)| ListchannelsChannels {
+                    source: vec![source; 33],
+                    destination: vec![destination; 33],
+                    short_channel_id: short_channel_id.to_string(),
+                    amount_msat: capacity.map(|capacity: u64| Amount {
+                        msat: capacity * 1_000,
+                    }),
+                    public: true,
+                    active: true,
+                    ..Default::default()
+                },
+            )
+            .collect())
+        });
+        info.update_cache(&mut gossip).await.unwrap();
+
+        assert_eq!(
+            info.get_node_capacity("BTC", &[3; 33]).await.unwrap(),
+            NodeCapacity {
+                inbound_sat: 5_010_000,
+                outbound_sat: 5_010_000,
+                channel_count: 3,
+                channels_missing_capacity: 1,
+            }
+        );
+        assert_eq!(
+            info.get_node_capacity("BTC", &[4; 33]).await.unwrap(),
+            NodeCapacity {
+                inbound_sat: 5_000_000,
+                outbound_sat: 5_000_000,
+                channel_count: 2,
+                channels_missing_capacity: 1,
+            }
+        );
+        assert_eq!(
+            info.get_node_capacity("BTC", &[5; 33])
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "no channels for node"
+        );
+    }
+
+    fn fee_policy(base_fee_millisatoshi: u32, fee_ppm: u32) -> ChannelPolicy {
+        ChannelPolicy {
+            active: true,
+            base_fee_millisatoshi,
+            fee_ppm,
+            delay: 80,
+            htlc_minimum_millisatoshi: None,
+            htlc_maximum_millisatoshi: None,
+            last_update: None,
+        }
+    }
+
+    #[rstest]
+    #[case(1_000, 100, 0, 1_000)]
+    #[case(1_000, 100, 1_000_000, 1_100)]
+    #[case(0, 1, 999_999, 0)]
+    #[case(0, 1, 1_000_000, 1)]
+    #[case(1, 2_500, 1_999, 5)]
+    #[case(500, 1_000_000, 21_000, 21_500)]
+    #[case(u32::MAX, u32::MAX, u64::MAX, u64::MAX)]
+    fn test_total_fee_msat(
+        #[case] base_fee_millisatoshi: u32,
+        #[case] fee_ppm: u32,
+        #[case] amount_msat: u64,
+        #[case] expected: u64,
+    ) {
+        assert_eq!(
+            fee_policy(base_fee_millisatoshi, fee_ppm).total_fee_msat(amount_msat),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(999, 0)]
+    #[case(1_000, 1)]
+    #[case(1_999, 1)]
+    #[case(21_000, 21)]
+    fn test_base_fee_sat(#[case] base_fee_millisatoshi: u32, #[case] expected: u32) {
+        assert_eq!(
+            fee_policy(base_fee_millisatoshi, 0).base_fee_sat(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(vec![5], FeeRange { min: 5, median: 5.0, max: 5 })]
+    #[case(vec![7, 1, 3], FeeRange { min: 1, median: 3.0, max: 7 })]
+    #[case(vec![4, 1, 10, 2], FeeRange { min: 1, median: 3.0, max: 10 })]
+    #[case(vec![1, 2], FeeRange { min: 1, median: 1.5, max: 2 })]
+    fn test_fee_range(#[case] values: Vec<u32>, #[case] expected: FeeRange) {
+        assert_eq!(FeeRange::from_values(values), Some(expected));
+    }
+
+    #[test]
+    fn test_fee_range_empty() {
+        assert_eq!(FeeRange::from_values(Vec::new()), None);
+    }
+
+    #[rstest]
+    #[case(
+        vec![(1_000, 100), (0, 1), (2_000, 5_000)],
+        FeeRange { min: 0, median: 1_000.0, max: 2_000 },
+        FeeRange { min: 1, median: 100.0, max: 5_000 }
+    )]
+    #[case(
+        vec![(1_000, 100), (0, 1), (2_000, 5_000), (1, 250)],
+        FeeRange { min: 0, median: 500.5, max: 2_000 },
+        FeeRange { min: 1, median: 175.0, max: 5_000 }
+    )]
+    #[tokio::test]
+    async fn test_get_node_policy_summary(
+        #[case] fees: Vec<(u32, u32)>,
+        #[case] base_fee_millisatoshi: FeeRange,
+        #[case] fee_ppm: FeeRange,
+    ) {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        let count = fees.len();
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(move || {
+            Ok(fees
+                .iter()
+                .enumerate()
+                .map(|(i, (base_fee, fee_ppm))| ListchannelsChannels {
+                    source: vec![2; 33],
+                    destination: vec![3; 33],
+                    short_channel_id: format!("1x1x{i}"),
+                    base_fee_millisatoshi: *base_fee,
+                    fee_per_millionth: *fee_ppm,
+                    public: true,
+                    active: true,
+                    ..Default::default()
+                })
+                .collect())
+        });
+        info.update_cache(&mut gossip).await.unwrap();
+
+        assert_eq!(
+            info.get_node_policy_summary("BTC", &[3; 33]).await.unwrap(),
+            Some(PolicySummary {
+                channel_count: count,
+                base_fee_millisatoshi,
+                fee_ppm,
+            })
+        );
+        assert_eq!(
+            info.get_node_policy_summary("BTC", &[2; 33]).await.unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_validate_max_channels_per_destination() {
+        let config = LightningInfoConfig {
+            max_channels_per_destination: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate().err().unwrap().to_string(),
+            "max channels per destination has to be greater than 0"
+        );
+    }
+
+    #[rstest]
+    #[case(-0.1)]
+    #[case(1.1)]
+    #[case(f64::NAN)]
+    fn test_validate_capacity_weight(#[case] capacity_weight: f64) {
+        let config = LightningInfoConfig {
+            capacity_weight,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate().err().unwrap().to_string(),
+            format!("capacity weight {capacity_weight} has to be between 0 and 1")
+        );
+    }
+
+    #[rstest]
+    #[case(60, 120, None)]
+    #[case(
+        120,
+        120,
+        Some("gossip refresh interval 120s has to be shorter than the cache TTL 120s")
+    )]
+    #[case(
+        180,
+        120,
+        Some("gossip refresh interval 180s has to be shorter than the cache TTL 120s")
+    )]
+    #[case(0, 120, Some("gossip refresh interval has to be greater than 0"))]
+    #[tokio::test]
+    async fn test_with_config_refresh_interval(
+        #[case] refresh_interval: u64,
+        #[case] cache_ttl: u64,
+        #[case] expected_err: Option<&str>,
+    ) {
+        let res = ClnLightningInfo::with_config(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                refresh_interval: Duration::from_secs(refresh_interval),
+                cache_ttl: Duration::from_secs(cache_ttl),
+                ..Default::default()
+            },
+        );
+
+        match expected_err {
+            Some(expected) => assert_eq!(res.err().unwrap().to_string(), expected),
+            None => assert!(res.is_ok()),
+        }
+    }
+
+    #[rstest]
+    #[case(0, None)]
+    #[case(30, None)]
+    #[case(
+        120,
+        Some("negative cache TTL 120s has to be shorter than the cache TTL 120s")
+    )]
+    #[tokio::test]
+    async fn test_with_config_negative_cache_ttl(
+        #[case] negative_cache_ttl: u64,
+        #[case] expected_err: Option<&str>,
+    ) {
+        let res = ClnLightningInfo::with_config(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                refresh_interval: Duration::from_secs(60),
+                cache_ttl: Duration::from_secs(120),
+                negative_cache_ttl: Duration::from_secs(negative_cache_ttl),
+                ..Default::default()
+            },
+        );
+
+        match expected_err {
+            Some(expected) => assert_eq!(res.err().unwrap().to_string(), expected),
+            None => assert!(res.is_ok()),
+        }
+    }
+
+    #[rstest]
+    #[case(0, 2)]
+    #[case(60, 1)]
+    #[tokio::test]
+    async fn test_negative_cache(#[case] negative_cache_ttl: u64, #[case] expected_reads: usize) {
+        let backend = CountingBackend::default();
+        let gets = backend.gets.clone();
+
+        let info = ClnLightningInfo::with_config(
+            Cache::Custom(Arc::new(backend)),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                negative_cache_ttl: Duration::from_secs(negative_cache_ttl),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(
+                info.get_node_info("BTC", &[2; 33])
+                    .await
+                    .err()
+                    .unwrap()
+                    .to_string(),
+                "node not found"
+            );
+        }
+        assert_eq!(gets.load(Ordering::SeqCst), expected_reads);
+    }
+
+    #[rstest]
+    #[case(0, 4)]
+    #[case(60, 2)]
+    #[tokio::test]
+    async fn test_negative_cache_private_channels(
+        #[case] negative_cache_ttl: u64,
+        #[case] expected_reads: usize,
+    ) {
+        let backend = CountingBackend::default();
+        let gets = backend.gets.clone();
+
+        let info = ClnLightningInfo::with_config(
+            Cache::Custom(Arc::new(backend)),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                negative_cache_ttl: Duration::from_secs(negative_cache_ttl),
+                own_node_ids: vec![hex::encode([2; 33])],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(
+                info.get_channels("BTC", &[2; 33])
+                    .await
+                    .err()
+                    .unwrap()
+                    .to_string(),
+                "no channels for node"
+            );
+        }
+        assert_eq!(gets.load(Ordering::SeqCst), expected_reads);
+    }
+
+    #[tokio::test]
+    async fn test_negative_cache_cleared_by_refresh() {
+        let info = ClnLightningInfo::with_config(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                negative_cache_ttl: Duration::from_secs(60),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert!(info.get_node_info("BTC", &[2; 33]).await.is_err());
+        assert!(info.get_channels("BTC", &[3; 33]).await.is_err());
+
+        info.update_cache(&mut mock_gossip_unchanged())
+            .await
+            .unwrap();
+        assert!(info.get_node_info("BTC", &[2; 33]).await.is_ok());
+        assert!(info.get_channels("BTC", &[3; 33]).await.is_ok());
+    }
+
+    #[test]
+    fn test_config_deserialize() {
+        let config: LightningInfoConfig = toml::from_str("").unwrap();
+        assert_eq!(config, LightningInfoConfig::default());
+
+        let config: LightningInfoConfig = toml::from_str(
+            "refreshInterval = 30\ncacheTtl = 90\nnegativeCacheTtl = 10\nsubstringMatch = false\n\
+            substringMatchMode = \"wordBoundary\"",
+        )
+        .unwrap();
+        assert_eq!(config.refresh_interval, Duration::from_secs(30));
+        assert_eq!(config.cache_ttl, Duration::from_secs(90));
+        assert_eq!(config.negative_cache_ttl, Duration::from_secs(10));
+        assert!(!config.substring_match);
+        assert_eq!(config.substring_match_mode, SubstringMatch::WordBoundary);
+        assert_eq!(config.search_metric, SearchMetric::JaroWinkler);
+        assert_eq!(config.max_distance(), 0.1);
+
+        let config: LightningInfoConfig = toml::from_str("searchMetric = \"levenshtein\"").unwrap();
+        assert_eq!(config.search_metric, SearchMetric::Levenshtein);
+        assert_eq!(config.max_distance(), 0.3);
+
+        let config: LightningInfoConfig =
+            toml::from_str("searchMetric = \"jaro\"\nmaxDistance = 0.05").unwrap();
+        assert_eq!(config.search_metric, SearchMetric::Jaro);
+        assert_eq!(config.max_distance(), 0.05);
+    }
+
+    #[tokio::test]
+    async fn test_lightning_info_cache_updates() {
+        let mem_cache = MemCache::new();
+        let cache = Cache::Memory(mem_cache);
+        let currencies = get_currencies().await;
+
+        let lightning_info = ClnLightningInfo::new(cache.clone(), currencies.clone());
+
+        // Allow some time for the background task to update the cache
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+
+        let btc = currencies.get("BTC").unwrap();
+        let mut cln = btc.cln.clone().unwrap();
+
+        let nodes = cln.list_nodes(None).await.unwrap();
+        assert!(!nodes.is_empty());
+
+        let test_node = &nodes[0];
+        let node_id_hex = hex::encode(&test_node.nodeid);
+
+        let node_info = lightning_info.get_node_info("BTC", &test_node.nodeid).await;
+        assert!(node_info.is_ok());
+
+        let node = node_info.unwrap();
+        assert_eq!(node.id, node_id_hex);
+        assert_eq!(node.alias, test_node.alias);
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_channels_failed() {
+        let cache = Cache::Memory(MemCache::new());
+        let info = ClnLightningInfo::new(cache, Arc::new(HashMap::new()));
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip
+            .expect_gossip_channels()
+            .returning(|| Err(anyhow!("connection refused")));
+
+        let res = info.update_cache(&mut gossip).await;
+        assert!(matches!(res, Err(UpdateError::Channels(_))));
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "updating channels failed: connection refused"
+        );
+
+        let nodes = info.find_node_by_alias("BTC", "boltz").await.unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].id, hex::encode(vec![2; 33]));
+
+        let node = info.get_node_info("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(node.alias, Some("bfx-lnd0".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_node_exists() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| Ok(Vec::new()));
+        info.update_cache(&mut gossip).await.unwrap();
+
+        assert!(info.node_exists("BTC", &[2; 33]).await.unwrap());
+        assert!(info.node_exists("BTC", &[3; 33]).await.unwrap());
+        assert!(!info.node_exists("BTC", &[4; 33]).await.unwrap());
+        assert!(!info.node_exists("L-BTC", &[2; 33]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_get_node_infos() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| Ok(Vec::new()));
+        info.update_cache(&mut gossip).await.unwrap();
+
+        let nodes = info
+            .get_node_infos("BTC", &[vec![3; 33], vec![4; 33], vec![2; 33], vec![3; 33]])
+            .await
+            .unwrap();
+        assert_eq!(
+            nodes
+                .iter()
+                .map(|node| node.as_ref().map(|node| node.id.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                Some(hex::encode([3; 33])),
+                None,
+                Some(hex::encode([2; 33])),
+                Some(hex::encode([3; 33])),
+            ]
+        );
+        assert_eq!(nodes[2].as_ref().unwrap().alias, Some("Boltz".to_string()));
+
+        assert!(info.get_node_infos("BTC", &[]).await.unwrap().is_empty());
+        assert_eq!(
+            info.get_node_infos("L-BTC", &[vec![2; 33]])
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_stats() {
+        let cache = Cache::Memory(MemCache::new());
+        let info = ClnLightningInfo::new(cache, Arc::new(HashMap::new()));
+        assert!(info.gossip_stats("BTC").await.is_none());
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| {
+            Ok(vec![ListchannelsChannels {
+                source: vec![2; 33],
+                destination: vec![3; 33],
+                short_channel_id: "1x1x1".to_string(),
+                public: true,
+                active: true,
+                ..Default::default()
+            }])
+        });
+
+        let stats = info.update_cache(&mut gossip).await.unwrap();
+        assert_eq!(stats.node_count, 2);
+        assert_eq!(stats.channel_count, 1);
+        assert!(stats.last_refresh_ts > 0);
+
+        assert_eq!(info.gossip_stats("BTC").await, Some(stats));
+    }
+
+    #[tokio::test]
+    async fn test_gossip_diagnostics() {
+        let info = ClnLightningInfo::build(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig::default(),
+        );
+        assert!(info.gossip_diagnostics("BTC").await.is_none());
+
+        let timestamps = [Some(1_700_000_000), Some(1_700_000_500), None];
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip.expect_gossip_nodes().returning(move || {
+            Ok(timestamps
+                .iter()
+                .enumerate()
+                .map(|(i, last_timestamp)| ListnodesNodes {
+                    nodeid: vec![i as u8 + 2; 33],
+                    last_timestamp: *last_timestamp,
+                    ..Default::default()
+                })
+                .collect())
+        });
+        gossip.expect_gossip_channels().returning(|| Ok(Vec::new()));
+
+        let stats = info.update_cache(&mut gossip).await.unwrap();
+        let diagnostics = info.gossip_diagnostics("BTC").await.unwrap();
+        assert_eq!(
+            diagnostics,
+            GossipDiagnostics {
+                node_count: 3,
+                channel_count: 0,
+                last_refresh_ts: stats.last_refresh_ts,
+                newest_node_update: Some(1_700_000_500),
+            }
+        );
+        assert!(info.gossip_diagnostics("L-BTC").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_phases() {
+        let info = ClnLightningInfo::build(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig::default(),
+        );
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip.expect_gossip_nodes().returning(|| {
+            std::thread::sleep(Duration::from_millis(20));
+            Ok(mock_gossip_nodes())
+        });
+        gossip.expect_gossip_channels().returning(|| {
+            std::thread::sleep(Duration::from_millis(30));
+            Ok(vec![ListchannelsChannels {
+                source: vec![2; 33],
+                destination: vec![3; 33],
+                short_channel_id: "1x1x1".to_string(),
+                public: true,
+                active: true,
+                ..Default::default()
+            }])
+        });
+
+        let stats = info.update_cache(&mut gossip).await.unwrap();
+        let phases = &stats.last_refresh_phases;
+        assert!(phases.node_fetch >= Duration::from_millis(20));
+        assert!(phases.channel_fetch >= Duration::from_millis(30));
+        assert!(phases.cache_write > Duration::ZERO);
+        assert!(
+            phases.node_fetch + phases.channel_fetch + phases.cache_write
+                <= stats.last_refresh_duration
+        );
+
+        let serialized = serde_json::to_value(&stats).unwrap();
+        assert!(
+            serialized["lastRefreshPhases"]["nodeFetchMs"]
+                .as_u64()
+                .unwrap()
+                >= 20
+        );
+        assert!(
+            serialized["lastRefreshPhases"]["channelFetchMs"]
+                .as_u64()
+                .unwrap()
+                >= 30
+        );
+        assert!(serialized["lastRefreshPhases"]["cacheWriteMs"].is_u64());
+    }
+
+    fn nodes_gossip() -> MockGossip {
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| Ok(Vec::new()));
+        gossip
+    }
+
+    #[tokio::test]
+    async fn test_refresh_source_now() {
+        let cache = Cache::Memory(MemCache::new());
+        let info = ClnLightningInfo::build(
+            cache.clone(),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig::default(),
+        );
+        assert!(info.gossip_stats("BTC").await.is_none());
+
+        info.refresh_source_now(&mut nodes_gossip()).await.unwrap();
+
+        assert_eq!(info.gossip_stats("BTC").await.unwrap().node_count, 2);
+        let cached: Option<Node> = cache
+            .get("cln:BTC:node", &hex::encode([2; 33]))
+            .await
+            .unwrap();
+        assert_eq!(cached.unwrap().alias, Some("Boltz".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_source_now_in_progress() {
+        let info = ClnLightningInfo::build(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig::default(),
+        );
+
+        let lock = info.refresh_lock("BTC");
+        let guard = lock.lock().await;
+        assert_eq!(
+            info.refresh_source_now(&mut nodes_gossip())
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "refresh of BTC lightning gossip is already in progress"
+        );
+        assert!(info.gossip_stats("BTC").await.is_none());
+
+        drop(guard);
+        info.refresh_source_now(&mut nodes_gossip()).await.unwrap();
+        assert!(info.gossip_stats("BTC").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_now() {
+        let currencies = get_currencies().await;
+        let info = ClnLightningInfo::build(
+            Cache::Memory(MemCache::new()),
+            currencies.clone(),
+            LightningInfoConfig::default(),
+        );
+        assert!(info.gossip_stats("BTC").await.is_none());
+
+        info.refresh_now("BTC").await.unwrap();
+
+        let nodes = currencies
+            .get("BTC")
+            .unwrap()
+            .cln
+            .clone()
+            .unwrap()
+            .list_nodes(None)
+            .await
+            .unwrap();
+        assert!(info.gossip_stats("BTC").await.unwrap().node_count > 0);
+        assert!(info.get_node_info("BTC", &nodes[0].nodeid).await.is_ok());
+
+        assert_eq!(
+            info.refresh_now("L-BTC").await.err().unwrap().to_string(),
+            "no gossip source for L-BTC"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_graph() {
+        let cache = Cache::Memory(MemCache::new());
+        let info = ClnLightningInfo::new(cache, Arc::new(HashMap::new()));
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| {
+            Ok((1..=NODE_WRITE_BATCH_SIZE + 1)
+                .flat_map(|block| {
+                    [([2; 33], [3; 33]), ([3; 33], [2; 33])].map(|(source, destination)| {
+                        ListchannelsChannels {
+                            source: source.to_vec(),
+                            destination: destination.to_vec(),
+                            short_channel_id: format!("{block}x1x1"),
+                            public: true,
+                            active: true,
+                            ..Default::default()
+                        }
+                    })
+                })
+                .collect())
+        });
+
+        let stats = info.update_cache(&mut gossip).await.unwrap();
+
+        let export = info
+            .export_graph("BTC", &GraphCursor::default(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(export.nodes.len(), stats.node_count);
+        assert_eq!(export.channels.len(), stats.channel_count);
+        assert_eq!(export.channels.len(), NODE_WRITE_BATCH_SIZE + 1);
+        assert!(!export.has_more);
+        assert_eq!(
+            export
+                .nodes
+                .iter()
+                .map(|node| node.id.clone())
+                .collect::<Vec<_>>(),
+            vec![hex::encode([2; 33]), hex::encode([3; 33])]
+        );
+        assert!(
+            export
+                .channels
+                .iter()
+                .all(|channel| channel.policies.len() == 2)
+        );
+
+        assert_eq!(
+            info.export_graph("L-BTC", &GraphCursor::default(), usize::MAX)
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "no gossip source for L-BTC"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_graph_pages() {
+        let cache = Cache::Memory(MemCache::new());
+        let info = ClnLightningInfo::new(cache, Arc::new(HashMap::new()));
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| {
+            Ok((1..=5)
+                .map(|block| ListchannelsChannels {
+                    source: [2; 33].to_vec(),
+                    destination: [3; 33].to_vec(),
+                    short_channel_id: format!("{block}x1x1"),
+                    public: true,
+                    active: true,
+                    ..Default::default()
+                })
+                .collect())
+        });
+        info.update_cache(&mut gossip).await.unwrap();
+
+        let mut cursor = GraphCursor::default();
+        let mut pages = Vec::new();
+        loop {
+            let page = info.export_graph("BTC", &cursor, 2).await.unwrap();
+            assert!(page.nodes.len() <= 2);
+            assert!(page.channels.len() <= 2);
+
+            pages.push((
+                page.nodes
+                    .iter()
+                    .map(|node| node.id.clone())
+                    .collect::<Vec<_>>(),
+                page.channels
+                    .iter()
+                    .map(|channel| channel.short_channel_id.clone())
+                    .collect::<Vec<_>>(),
+            ));
+            if !page.has_more {
+                break;
+            }
+
+            cursor = GraphCursor {
+                after_node: page.next_node,
+                after_channel: page.next_channel,
+            };
+        }
+
+        assert_eq!(
+            pages
+                .iter()
+                .flat_map(|(nodes, _)| nodes)
+                .collect::<Vec<_>>(),
+            vec![&hex::encode([2; 33]), &hex::encode([3; 33])]
+        );
+        assert_eq!(
+            pages
+                .iter()
+                .flat_map(|(_, channels)| channels)
+                .collect::<Vec<_>>(),
+            vec!["1x1x1", "2x1x1", "3x1x1", "4x1x1", "5x1x1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gossip_fresh_after_refresh() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        assert!(info.gossip_age("BTC").await.is_none());
+        assert!(!info.is_gossip_fresh("BTC").await);
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| Ok(Vec::new()));
+
+        info.update_cache(&mut gossip).await.unwrap();
+
+        assert!(info.gossip_age("BTC").await.unwrap() < info.config.cache_ttl);
+        assert!(info.is_gossip_fresh("BTC").await);
+        assert!(!info.is_gossip_fresh("L-BTC").await);
+    }
+
+    #[tokio::test]
+    async fn test_node_ttl() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| Ok(Vec::new()));
+
+        info.update_cache(&mut gossip).await.unwrap();
+
+        let ttl = info.node_ttl("BTC", &[2; 33]).await.unwrap().unwrap();
+        assert!(ttl <= info.config.cache_ttl);
+        assert!(ttl > info.config.cache_ttl - Duration::from_secs(10));
+
+        assert_eq!(info.node_ttl("BTC", &[4; 33]).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_gossip_stale() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+
+        let ttl = info.config.cache_ttl;
+        info.refreshed.write().await.insert(
+            "BTC".to_string(),
+            Instant::now()
+                .checked_sub(ttl + Duration::from_secs(1))
+                .unwrap(),
+        );
+
+        assert!(info.gossip_age("BTC").await.unwrap() > ttl);
+        assert!(!info.is_gossip_fresh("BTC").await);
+    }
+
+    #[tokio::test]
+    async fn test_gossip_not_refreshed_when_update_fails() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip
+            .expect_gossip_channels()
+            .returning(|| Err(anyhow!("connection refused")));
+
+        assert!(info.update_cache(&mut gossip).await.is_err());
+        assert!(info.gossip_age("BTC").await.is_none());
+        assert!(!info.is_gossip_fresh("BTC").await);
+    }
+
+    fn mock_gossip_unchanged() -> MockGossip {
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| {
+            Ok(vec![ListchannelsChannels {
+                source: vec![2; 33],
+                destination: vec![3; 33],
+                short_channel_id: "1x1x1".to_string(),
+                public: true,
+                active: true,
+                ..Default::default()
+            }])
+        });
+        gossip
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_skips_unchanged_writes() {
+        let mem_cache = MemCache::new();
+        let info =
+            ClnLightningInfo::new(Cache::Memory(mem_cache.clone()), Arc::new(HashMap::new()));
+
+        let mut gossip = mock_gossip_unchanged();
+        info.update_cache(&mut gossip).await.unwrap();
+        assert_eq!(mem_cache.map.len(), 5);
+
+        mem_cache.map.clear();
+        info.update_cache(&mut gossip).await.unwrap();
+        assert!(mem_cache.map.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_bounded_rewrites_unchanged() {
+        let mem_cache = MemCache::with_capacity(100).unwrap();
+        let info =
+            ClnLightningInfo::new(Cache::Memory(mem_cache.clone()), Arc::new(HashMap::new()));
+
+        let mut gossip = mock_gossip_unchanged();
+        info.update_cache(&mut gossip).await.unwrap();
+        assert_eq!(mem_cache.map.len(), 5);
+
+        let (key, field) = info.cache_key_node("BTC", hex::encode([2; 33]));
+        mem_cache.del(&key, &field);
+        info.update_cache(&mut gossip).await.unwrap();
+        assert_eq!(mem_cache.map.len(), 5);
+        assert!(mem_cache.map.contains_key(&format!("{key}:{field}")));
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_rewrites_changed() {
+        let mem_cache = MemCache::new();
+        let info =
+            ClnLightningInfo::new(Cache::Memory(mem_cache.clone()), Arc::new(HashMap::new()));
+
+        let mut gossip = mock_gossip_unchanged();
+        info.update_cache(&mut gossip).await.unwrap();
+        mem_cache.map.clear();
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip.expect_gossip_nodes().returning(|| {
+            let mut nodes = mock_gossip_nodes();
+            nodes[0].alias = Some("Boltz|CLN".to_string());
+            Ok(nodes)
+        });
+        gossip.expect_gossip_channels().returning(|| {
+            Ok(vec![ListchannelsChannels {
+                source: vec![2; 33],
+                destination: vec![3; 33],
+                short_channel_id: "1x1x1".to_string(),
+                public: true,
+                active: true,
+                ..Default::default()
+            }])
+        });
+        info.update_cache(&mut gossip).await.unwrap();
+
+        // The renamed node and every channel entry embedding it are written again
+        let (key, field) = info.cache_key_node("BTC", hex::encode([2; 33]));
+        assert!(mem_cache.map.contains_key(&format!("{key}:{field}")));
+        let (key, field) = info.cache_key_node("BTC", hex::encode([3; 33]));
+        assert!(!mem_cache.map.contains_key(&format!("{key}:{field}")));
+        assert_eq!(mem_cache.map.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_rewrites_before_expiry() {
+        let mem_cache = MemCache::new();
+        let info =
+            ClnLightningInfo::new(Cache::Memory(mem_cache.clone()), Arc::new(HashMap::new()));
+
+        let mut gossip = mock_gossip_unchanged();
+        info.update_cache(&mut gossip).await.unwrap();
+        mem_cache.map.clear();
+
+        let written_at = Instant::now()
+            .checked_sub(info.config.cache_ttl - info.config.refresh_interval)
+            .unwrap();
+        for entry in info.written.write().await.values_mut() {
+            entry.written_at = written_at;
+        }
+
+        info.update_cache(&mut gossip).await.unwrap();
+        assert_eq!(mem_cache.map.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_removes_vanished() {
+        let mem_cache = MemCache::new();
+        let info =
+            ClnLightningInfo::new(Cache::Memory(mem_cache.clone()), Arc::new(HashMap::new()));
+
+        let mut gossip = mock_gossip_unchanged();
+        info.update_cache(&mut gossip).await.unwrap();
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip.expect_gossip_nodes().returning(|| {
+            Ok(mock_gossip_nodes()
+                .into_iter()
+                .filter(|node| node.nodeid != vec![3; 33])
+                .collect())
+        });
+        gossip.expect_gossip_channels().returning(|| Ok(Vec::new()));
+        info.update_cache(&mut gossip).await.unwrap();
+
+        assert!(
+            info.get_node_info("BTC", &[2; 33]).await.is_ok(),
+            "remaining node should still be cached"
+        );
+        assert_eq!(
+            info.get_node_info("BTC", &[3; 33])
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "node not found"
+        );
+        assert!(info.get_channels("BTC", &[3; 33]).await.is_err());
+        assert!(info.get_channels_from("BTC", &[2; 33]).await.is_err());
+        assert!(info.get_channel("BTC", "1x1x1".to_string()).await.is_err());
+        assert_eq!(mem_cache.map.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_rewrites_returning() {
+        let mem_cache = MemCache::new();
+        let info =
+            ClnLightningInfo::new(Cache::Memory(mem_cache.clone()), Arc::new(HashMap::new()));
+
+        let mut gossip = mock_gossip_unchanged();
+        info.update_cache(&mut gossip).await.unwrap();
+
+        let mut vanished = MockGossip::new();
+        vanished.expect_symbol().returning(|| "BTC".to_string());
+        vanished.expect_gossip_nodes().returning(|| Ok(Vec::new()));
+        vanished
+            .expect_gossip_channels()
+            .returning(|| Ok(Vec::new()));
+        info.update_cache(&mut vanished).await.unwrap();
+        assert!(mem_cache.map.is_empty());
+
+        info.update_cache(&mut gossip).await.unwrap();
+        assert_eq!(mem_cache.map.len(), 5);
+    }
+
+    #[rstest]
+    #[case(&[0x3b, 0x99, 0xfc], Some("#3b99fc"))]
+    #[case(&[0xff, 0xAB, 0x00], Some("#ffab00"))]
+    #[case(&[0x3b, 0x99], None)]
+    #[case(&[0x3b, 0x99, 0xfc, 0x00], None)]
+    #[case(&[], None)]
+    fn test_normalize_color(#[case] raw: &[u8], #[case] expected: Option<&str>) {
+        assert_eq!(
+            normalize_color(raw),
+            expected.map(|color| color.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_normalizes_color() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip.expect_gossip_nodes().returning(|| {
+            let mut nodes = mock_gossip_nodes();
+            nodes[0].color = Some(vec![0xAB, 0xCD, 0xEF]);
+            nodes[1].color = Some(vec![0xAB]);
+            Ok(nodes)
+        });
+        gossip.expect_gossip_channels().returning(|| Ok(Vec::new()));
+        info.update_cache(&mut gossip).await.unwrap();
+
+        let node = info.get_node_info("BTC", &[2; 33]).await.unwrap();
+        assert_eq!(node.color, Some("#abcdef".to_string()));
+        let node = info.get_node_info("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(node.color, None);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_stops_on_drop() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        let handle = info.spawn_refresh(mock_gossip_unchanged());
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !info.is_gossip_fresh("BTC").await {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(!handle.is_finished());
+
+        drop(info);
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_startup_delay() {
+        for _ in 0..10 {
+            assert!(ClnLightningInfo::startup_delay() <= STARTUP_JITTER);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_on_startup() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        assert_eq!(
+            info.find_node_by_alias("BTC", "Boltz")
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "no gossip source for BTC"
+        );
+
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip.expect_gossip_nodes().returning({
+            let refreshes = refreshes.clone();
+            move || {
+                refreshes.fetch_add(1, Ordering::SeqCst);
+                Ok(mock_gossip_nodes())
+            }
+        });
+        gossip.expect_gossip_channels().returning(|| Ok(Vec::new()));
+        let _handle = info.spawn_refresh(gossip);
+
+        let nodes = tokio::time::timeout(STARTUP_JITTER + Duration::from_secs(3), async {
+            loop {
+                match info.find_node_by_alias("BTC", "Boltz").await {
+                    Ok(nodes) => break nodes,
+                    Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(nodes.len(), 1);
+        // Served by the refresh on startup rather than one after the interval
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert!(info.get_node_info("BTC", &[2; 33]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_loaded_symbols() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        assert!(info.loaded_symbols().await.is_empty());
+        assert_eq!(info.node_count("BTC").await, None);
+
+        let _handle = info.spawn_refresh(mock_gossip_unchanged());
+        assert!(info.loaded_symbols().await.is_empty());
+
+        tokio::time::timeout(STARTUP_JITTER + Duration::from_secs(3), async {
+            while info.gossip_stats("BTC").await.is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(info.loaded_symbols().await, vec!["BTC".to_string()]);
+        assert_eq!(info.node_count("BTC").await, Some(2));
+        assert_eq!(info.node_count("L-BTC").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_warm_from_cache() {
+        let cache = Cache::Custom(Arc::new(CountingBackend::default()));
+        cache
+            .set(
+                "cln:BTC:node",
+                &hex::encode([2; 33]),
+                &Node {
+                    id: hex::encode([2; 33]),
+                    alias: Some("Boltz".to_string()),
+                    color: None,
+                    last_update: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let info = ClnLightningInfo::build(
+            cache,
+            Arc::new(HashMap::new()),
+            LightningInfoConfig::default(),
+        );
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Err(anyhow!("offline")));
+        gossip.expect_gossip_channels().returning(|| Ok(Vec::new()));
+        let _handle = info.spawn_refresh(gossip);
+
+        let nodes = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match info.find_node_by_alias("BTC", "Boltz").await {
+                    Ok(nodes) => break nodes,
+                    Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].id, hex::encode([2; 33]));
+        assert!(!info.is_gossip_fresh("BTC").await);
+        assert!(info.gossip_stats("BTC").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_warm_from_cache_memory() {
+        let cache = Cache::Memory(MemCache::new());
+        cache
+            .set("cln:BTC:node", &hex::encode([2; 33]), &"{}", None)
+            .await
+            .unwrap();
+
+        let info = ClnLightningInfo::build(
+            cache,
+            Arc::new(HashMap::new()),
+            LightningInfoConfig::default(),
+        );
+        assert_eq!(info.warm_from_cache("BTC").await.unwrap(), 0);
+        assert!(info.find_node_by_alias("BTC", "Boltz").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_runs_while_clones_exist() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        let handle = info.spawn_refresh(mock_gossip_unchanged());
+
+        let clone = info.clone();
+        drop(info);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!handle.is_finished());
+
+        drop(clone);
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_concurrently() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        ClnLightningInfo::write_concurrently(0..CACHE_WRITE_CONCURRENCY * 4, |_| {
+            let in_flight = in_flight.clone();
+            let max_in_flight = max_in_flight.clone();
+
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            }
+        })
+        .await
+        .unwrap();
+
+        let max_in_flight = max_in_flight.load(Ordering::SeqCst);
+        assert!(max_in_flight > 1);
+        assert!(max_in_flight <= CACHE_WRITE_CONCURRENCY);
+        assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_write_concurrently_error() {
+        let res = ClnLightningInfo::write_concurrently(0..100, |i| async move {
+            if i == 42 {
+                Err(anyhow!("write {} failed", i))
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+
+        assert_eq!(res.err().unwrap().to_string(), "write 42 failed");
+    }
+
+    #[rstest]
+    #[case(0, Duration::from_secs(5))]
+    #[case(1, Duration::from_secs(10))]
+    #[case(3, Duration::from_secs(40))]
+    #[case(8, Duration::from_secs(300))]
+    #[case(u32::MAX, Duration::from_secs(300))]
+    fn test_backoff_base_delay(#[case] failures: u32, #[case] expected: Duration) {
+        let backoff = Backoff {
+            failures,
+            max: Duration::from_secs(300),
+        };
+        assert_eq!(backoff.base_delay(), expected);
+    }
+
+    #[test]
+    fn test_backoff_jitter() {
+        let mut backoff = Backoff::new(Duration::from_secs(300));
+
+        for _ in 0..10 {
+            let base = backoff.base_delay();
+            let delay = backoff.next_delay();
+            assert!(delay >= base / 2);
+            assert!(delay <= base);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_backoff() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        let mut backoff = Backoff::new(info.config.refresh_interval);
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+
+        let mut seq = mockall::Sequence::new();
+        gossip
+            .expect_gossip_nodes()
+            .times(3)
+            .in_sequence(&mut seq)
+            .returning(|| Err(anyhow!("connection refused")));
+        gossip
+            .expect_gossip_nodes()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip
+            .expect_gossip_nodes()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|| Err(anyhow!("connection refused")));
+        gossip.expect_gossip_channels().returning(|| Ok(Vec::new()));
+
+        for failures in 0..3 {
+            let delay = info.refresh(&mut gossip, &mut backoff).await;
+            assert_eq!(backoff.failures, failures + 1);
+
+            let max = BACKOFF_INITIAL_DELAY * 2u32.pow(failures);
+            assert!(delay >= max / 2);
+            assert!(delay <= max);
+        }
+
+        // A success resets the backoff and waits for the regular interval
+        let delay = info.refresh(&mut gossip, &mut backoff).await;
+        assert_eq!(backoff.failures, 0);
+        assert!(delay > Duration::from_secs(60));
+        assert!(delay <= info.config.refresh_interval);
+
+        let delay = info.refresh(&mut gossip, &mut backoff).await;
+        assert_eq!(backoff.failures, 1);
+        assert!(delay <= BACKOFF_INITIAL_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_nodes_failed() {
+        let cache = Cache::Memory(MemCache::new());
+        let info = ClnLightningInfo::new(cache, Arc::new(HashMap::new()));
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Err(anyhow!("connection refused")));
+        gossip.expect_gossip_channels().never();
+
+        let res = info.update_cache(&mut gossip).await;
+        assert!(matches!(res, Err(UpdateError::Nodes(_))));
+        assert_eq!(
+            info.find_node_by_alias("BTC", "boltz")
+                .await
+                .unwrap_err()
+                .downcast_ref::<LookupError>(),
+            Some(&LookupError::UnknownSymbol("BTC".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lookup_unknown_symbol() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        let _handle = info.spawn_refresh(mock_gossip_unchanged());
+
+        let err = info.find_node_by_alias("L-BTC", "boltz").await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<LookupError>(),
+            Some(&LookupError::UnknownSymbol("L-BTC".to_string()))
+        );
+        assert_eq!(err.to_string(), "no gossip source for L-BTC");
+    }
+
+    #[tokio::test]
+    async fn test_lookup_gossip_not_ready() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Err(anyhow!("connection refused")));
+        gossip.expect_gossip_channels().never();
+        let _handle = info.spawn_refresh(gossip);
+
+        for alias in ["boltz", "bolt"] {
+            let err = info.find_node_by_alias("BTC", alias).await.unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<LookupError>(),
+                Some(&LookupError::GossipNotReady("BTC".to_string()))
+            );
+            assert_eq!(err.to_string(), "gossip of BTC is not ready");
+        }
+        assert!(
+            info.find_node_by_id_prefix("BTC", "02")
+                .await
+                .unwrap_err()
+                .is::<LookupError>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_channels() {
+        let mem_cache = MemCache::new();
+        let cache = Cache::Memory(mem_cache);
+        let currencies = get_currencies().await;
+
+        let lightning_info = ClnLightningInfo::new(cache.clone(), currencies.clone());
+
+        // Allow some time for the background task to update the cache
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+
+        let btc = currencies.get("BTC").unwrap();
+        let mut cln = btc.cln.clone().unwrap();
+
+        let channels = cln.list_channels(None).await.unwrap();
+
+        let channel = &channels[0];
+        let dest_node = &channel.destination;
+
+        let channel_info = lightning_info.get_channels("BTC", dest_node).await;
+        assert!(channel_info.is_ok());
+
+        let channels = channel_info.unwrap();
+        assert!(!channels.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_channels_from() {
+        let mem_cache = MemCache::new();
+        let cache = Cache::Memory(mem_cache);
+        let currencies = get_currencies().await;
+
+        let lightning_info = ClnLightningInfo::new(cache.clone(), currencies.clone());
+
+        // Allow some time for the background task to update the cache
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+
+        let btc = currencies.get("BTC").unwrap();
+        let mut cln = btc.cln.clone().unwrap();
+
+        let channels = cln.list_channels(None).await.unwrap();
+        let channel = channels.iter().find(|c| c.public).unwrap();
+
+        let outbound = lightning_info
+            .get_channels_from("BTC", &channel.source)
+            .await
+            .unwrap();
+        assert!(
+            outbound
+                .iter()
+                .any(|c| c.short_channel_id == channel.short_channel_id)
+        );
+        assert!(
+            outbound
+                .iter()
+                .all(|c| c.source.id == hex::encode(&channel.source))
+        );
+
+        let inbound = lightning_info
+            .get_channels("BTC", &channel.destination)
+            .await
+            .unwrap();
+        assert!(
+            inbound
+                .iter()
+                .any(|c| c.short_channel_id == channel.short_channel_id)
+        );
     }
 
-    async fn get_channels(&self, symbol: &str, destination: &[u8]) -> Result<Vec<Channel>> {
-        let (key, field) = Self::cache_key_channels(symbol, hex::encode(destination));
-        if let Some(channels) = self.cache.get(&key, &field).await? {
-            return Ok(channels);
-        }
+    #[tokio::test]
+    async fn test_get_active_channels() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
 
-        Err(anyhow!("no channels for node"))
-    }
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| {
+            Ok(["1x1x1", "1x1x2", "1x1x3"]
+                .into_iter()
+                .enumerate()
+                .map(|(i, short_channel_id)| ListchannelsChannels {
+                    source: vec![2; 33],
+                    destination: vec![3; 33],
+                    short_channel_id: short_channel_id.to_string(),
+                    public: true,
+                    active: i != 1,
+                    ..Default::default()
+                })
+                .collect())
+        });
+        info.update_cache(&mut gossip).await.unwrap();
 
-    async fn get_channel(&self, symbol: &str, short_channel_id: String) -> Result<ChannelInfo> {
-        let short_channel_id = short_channel_id.to_lowercase();
-        let short_channel_id = match short_channel_id.contains("x") {
-            true => short_channel_id,
-            false => Self::scid_lnd_to_cln(&short_channel_id)?,
-        };
+        let channels = info.get_channels("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(channels.len(), 3);
 
-        let (key, field) = Self::cache_key_channel(symbol, short_channel_id);
-        if let Some(channel) = self.cache.get(&key, &field).await? {
-            return Ok(channel);
-        }
+        let channels = info.get_active_channels("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(
+            channels
+                .iter()
+                .map(|channel| channel.short_channel_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1x1x1", "1x1x3"]
+        );
+        assert!(channels.iter().all(|channel| channel.active));
 
-        Err(anyhow!("channel not found"))
+        assert!(info.get_active_channels("BTC", &[2; 33]).await.is_err());
     }
 
-    async fn get_node_info(&self, symbol: &str, node: &[u8]) -> Result<Node> {
-        let (key, field) = Self::cache_key_node(symbol, hex::encode(node));
-        if let Some(node) = self.cache.get(&key, &field).await? {
-            return Ok(node);
-        }
+    #[tokio::test]
+    async fn test_channel_endpoints() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
 
-        Err(anyhow!("node not found"))
-    }
-}
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| {
+            Ok(vec![
+                ListchannelsChannels {
+                    source: vec![2; 33],
+                    destination: vec![3; 33],
+                    short_channel_id: "1x1x1".to_string(),
+                    public: true,
+                    active: true,
+                    ..Default::default()
+                },
+                ListchannelsChannels {
+                    source: vec![2; 33],
+                    destination: vec![4; 33],
+                    short_channel_id: "1x1x2".to_string(),
+                    public: true,
+                    active: true,
+                    ..Default::default()
+                },
+            ])
+        });
+        info.update_cache(&mut gossip).await.unwrap();
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::cache::{Cache, MemCache};
-    use crate::currencies::{Currencies, Currency};
-    use crate::lightning::cln::test::cln_client;
-    use crate::service::lightning_info::{ClnLightningInfo, LightningInfo};
-    use crate::wallet::{Bitcoin, Network};
-    use alloy::hex;
-    use bip39::Mnemonic;
-    use rstest::rstest;
-    use std::collections::HashMap;
-    use std::str::FromStr;
-    use std::sync::Arc;
-    use std::time::Duration;
+        let channels = info.get_channels("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].source.id, hex::encode([2; 33]));
+        assert_eq!(channels[0].source.alias, Some("Boltz".to_string()));
+        let destination = channels[0].destination.as_ref().unwrap();
+        assert_eq!(destination.id, hex::encode([3; 33]));
+        assert_eq!(destination.alias, Some("bfx-lnd0".to_string()));
 
-    async fn get_currencies() -> Currencies {
-        Arc::new(HashMap::<String, Currency>::from([(
-            "BTC".to_string(),
-            Currency {
-                network: Network::Regtest,
-                wallet: Arc::new(
-                    Bitcoin::new(
-                        Network::Regtest,
-                        &Mnemonic::from_str(
-                            "test test test test test test test test test test test junk",
-                        )
-                        .unwrap()
-                        .to_seed(""),
-                        "m/0/0".to_string(),
-                    )
-                    .unwrap(),
-                ),
-                chain: Some(Arc::new(Box::new(
-                    crate::chain::chain_client::test::get_client(),
-                ))),
-                cln: Some(cln_client().await),
-                lnd: None,
-            },
-        )]))
+        // Destinations that are not in the gossip are left out
+        let channels = info.get_channels("BTC", &[4; 33]).await.unwrap();
+        assert_eq!(channels.len(), 1);
+        assert!(channels[0].destination.is_none());
     }
 
     #[tokio::test]
-    async fn test_find_node_by_alias() {
-        let mem_cache = MemCache::new();
-        let cache = Cache::Memory(mem_cache);
-        let currencies = get_currencies().await;
+    async fn test_get_channels_sorted_by_capacity() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
 
-        let info = ClnLightningInfo::new(cache.clone(), currencies.clone());
+        let mut gossip = MockGossip::new();
+        gossip.expect_symbol().returning(|| "BTC".to_string());
+        gossip
+            .expect_gossip_nodes()
+            .returning(|| Ok(mock_gossip_nodes()));
+        gossip.expect_gossip_channels().returning(|| {
+            Ok([
+                ("1x1x1", Some(1_000)),
+                ("1x1x2", None),
+                ("1x1x3", Some(5_000)),
+                ("1x1x5", Some(1_000)),
+                ("1x1x4", Some(1_000)),
+            ]
+            .into_iter()
+            .map(|(short_channel_id, capacity)| ListchannelsChannels {
+                source: vec![2; 33],
+                destination: vec![3; 33],
+                short_channel_id: short_channel_id.to_string(),
+                amount_msat: capacity.map(|sat: u64| Amount { msat: sat * 1_000 }),
+                public: true,
+                active: true,
+                ..Default::default()
+            })
+            .collect())
+        });
+        info.update_cache(&mut gossip).await.unwrap();
 
-        let mut nodes = HashMap::new();
-        nodes.insert(
-            "1".to_string(),
-            Node {
-                id: "1".to_string(),
-                alias: None,
-                color: None,
-            },
+        let expected = vec!["1x1x3", "1x1x1", "1x1x4", "1x1x5", "1x1x2"];
+        let channels = info.get_channels("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(channels[0].capacity_sat, Some(5_000));
+        assert_eq!(
+            channels
+                .iter()
+                .map(|channel| channel.short_channel_id.as_str())
+                .collect::<Vec<_>>(),
+            expected
         );
-        nodes.insert(
-            "026165850492521f4ac8abd9bd8088123446d126f648ca35e60f88177dc149ceb2".to_string(),
-            Node {
-                id: "026165850492521f4ac8abd9bd8088123446d126f648ca35e60f88177dc149ceb2"
-                    .to_string(),
-                alias: Some("Boltz".to_string()),
-                color: None,
-            },
+
+        let channels = info.get_channels_from("BTC", &[2; 33]).await.unwrap();
+        assert_eq!(
+            channels
+                .iter()
+                .map(|channel| channel.short_channel_id.as_str())
+                .collect::<Vec<_>>(),
+            expected
         );
-        nodes.insert(
-            "02d96eadea3d780104449aca5c93461ce67c1564e2e1d73225fa67dd3b997a6018".to_string(),
-            Node {
-                id: "02d96eadea3d780104449aca5c93461ce67c1564e2e1d73225fa67dd3b997a6018"
-                    .to_string(),
-                alias: Some("Boltz|CLN".to_string()),
+    }
+
+    fn test_channel_side(id: &str, active: bool) -> ChannelInfoSide {
+        ChannelInfoSide {
+            node: Node {
+                id: id.to_string(),
+                alias: None,
                 color: None,
+                last_update: None,
             },
+            policy: ChannelPolicy {
+                active,
+                base_fee_millisatoshi: 1_000,
+                fee_ppm: 100,
+                delay: 80,
+                htlc_minimum_millisatoshi: None,
+                htlc_maximum_millisatoshi: None,
+                last_update: None,
+            },
+        }
+    }
+
+    #[rstest]
+    #[case(vec![("02a", true)], "02a", None)]
+    #[case(vec![("02a", false), ("02b", true)], "02b", Some("02a"))]
+    #[case(vec![("02a", true), ("02b", false)], "02a", Some("02b"))]
+    #[case(vec![("02a", false), ("02b", false)], "02a", Some("02b"))]
+    #[tokio::test]
+    async fn test_get_channel_by_scid(
+        #[case] sides: Vec<(&str, bool)>,
+        #[case] expected: &str,
+        #[case] expected_destination: Option<&str>,
+    ) {
+        let cache = Cache::Memory(MemCache::new());
+        let info = ClnLightningInfo::new(cache.clone(), Arc::new(HashMap::new()));
+
+        let short_channel_id = "700945x2144x1".to_string();
+        let (key, field) = info.cache_key_channel("BTC", short_channel_id.clone());
+        cache
+            .set(
+                &key,
+                &field,
+                &ChannelInfo {
+                    short_channel_id: short_channel_id.clone(),
+                    capacity_sat: Some(100_000),
+                    policies: sides
+                        .iter()
+                        .map(|(id, active)| test_channel_side(id, *active))
+                        .collect(),
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let channel = info
+            .get_channel_by_scid("BTC", short_channel_id.clone())
+            .await
+            .unwrap();
+        assert_eq!(channel.short_channel_id, short_channel_id);
+        assert_eq!(channel.capacity_sat, Some(100_000));
+        assert_eq!(channel.source.id, expected);
+        assert_eq!(
+            channel.destination.map(|destination| destination.id),
+            expected_destination.map(|id| id.to_string())
         );
-        nodes.insert(
-            "033d8656219478701227199cbd6f670335c8d408a92ae88b962c49d4dc0e83e025".to_string(),
-            Node {
-                id: "033d8656219478701227199cbd6f670335c8d408a92ae88b962c49d4dc0e83e025"
-                    .to_string(),
-                alias: Some("bfx-lnd0".to_string()),
+
+        // Both directions are still available
+        let channel = info.get_channel("BTC", short_channel_id).await.unwrap();
+        assert_eq!(channel.policies.len(), sides.len());
+
+        // LND format of the short channel id
+        let channel = info
+            .get_channel_by_scid("BTC", "770697178071957505".to_string())
+            .await
+            .unwrap();
+        assert_eq!(channel.source.id, expected);
+    }
+
+    fn test_channel(short_channel_id: &str, source: &str, destination: &str) -> Channel {
+        let side = test_channel_side(source, true);
+        Channel {
+            source: side.node,
+            short_channel_id: short_channel_id.to_string(),
+            capacity_sat: Some(100_000),
+            active: true,
+            info: side.policy,
+            destination: Some(Node {
+                id: destination.to_string(),
+                alias: None,
                 color: None,
+                last_update: None,
+            }),
+        }
+    }
+
+    #[rstest]
+    #[case(Some(1_000), 0, false)]
+    #[case(Some(1_000), 600, false)]
+    #[case(Some(1_000), 601, true)]
+    #[case(Some(2_000), 0, false)]
+    #[case(None, 0, true)]
+    fn test_channel_is_stale(
+        #[case] last_update: Option<u64>,
+        #[case] elapsed_secs: u64,
+        #[case] expected: bool,
+    ) {
+        let mut channel = test_channel("1x1x1", "02", "03");
+        channel.info.last_update = last_update;
+
+        let now = UNIX_EPOCH + Duration::from_secs(1_000 + elapsed_secs);
+        assert_eq!(channel.is_stale(Duration::from_secs(600), now), expected);
+    }
+
+    #[rstest]
+    #[case(0, None)]
+    #[case(1_704_067_200, Some(1_704_067_200))]
+    fn test_channel_last_update(#[case] last_update: u32, #[case] expected: Option<u64>) {
+        let channel = Channel::from((
+            ListchannelsChannels {
+                source: vec![2; 33],
+                destination: vec![3; 33],
+                short_channel_id: "1x1x1".to_string(),
+                last_update,
+                ..Default::default()
             },
-        );
-        nodes.insert(
-            "03cde60a6323f7122d5178255766e38114b4722ede08f7c9e0c5df9b912cc201d6".to_string(),
             Node {
-                id: "03cde60a6323f7122d5178255766e38114b4722ede08f7c9e0c5df9b912cc201d6"
-                    .to_string(),
-                alias: Some("bfx-lnd1".to_string()),
+                id: hex::encode([2; 33]),
+                alias: None,
                 color: None,
+                last_update: None,
             },
-        );
-        info.nodes.write().await.insert("BTC".to_string(), nodes);
+            None,
+        ));
+        assert_eq!(channel.info.last_update, expected);
+    }
 
-        let nodes = info.find_node_by_alias("BTC", "test").await;
-        assert!(nodes.is_ok());
-        assert!(nodes.unwrap().is_empty());
+    #[tokio::test]
+    async fn test_get_channels_dedupe() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        let destination = hex::encode([3; 33]);
+        let source = hex::encode([2; 33]);
 
-        let nodes = info.find_node_by_alias("BTC", "BOLTZ").await;
-        assert!(nodes.is_ok());
-        let nodes = nodes.unwrap();
-        assert_eq!(nodes.len(), 2);
-        assert!(nodes.iter().any(|n| n.alias == Some("Boltz".to_string())));
-        assert!(
-            nodes
-                .iter()
-                .any(|n| n.alias == Some("Boltz|CLN".to_string()))
-        );
+        let (key, field) = info.cache_key_channels("BTC", destination.clone());
+        info.cache
+            .set(
+                &key,
+                &field,
+                &DestinationChannels {
+                    channels: vec![
+                        test_channel("1x1x1", &destination, &source),
+                        test_channel("1x1x1", &source, &destination),
+                        test_channel("2x1x1", &source, &destination),
+                    ],
+                    truncated: false,
+                },
+                None,
+            )
+            .await
+            .unwrap();
 
-        let nodes = info.find_node_by_alias("BTC", "bfx").await;
-        assert!(nodes.is_ok());
-        let nodes = nodes.unwrap();
-        assert_eq!(nodes.len(), 2);
-        assert!(
-            nodes
-                .iter()
-                .any(|n| n.alias == Some("bfx-lnd0".to_string()))
-        );
-        assert!(
-            nodes
+        let channels = info.get_channels("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(
+            channels
                 .iter()
-                .any(|n| n.alias == Some("bfx-lnd1".to_string()))
+                .map(|channel| channel.short_channel_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1x1x1", "2x1x1"]
         );
+        assert_eq!(channels[0].source.id, source);
+        assert_eq!(channels[0].destination.as_ref().unwrap().id, destination);
     }
 
     #[tokio::test]
-    async fn test_lightning_info_cache_updates() {
-        let mem_cache = MemCache::new();
-        let cache = Cache::Memory(mem_cache);
-        let currencies = get_currencies().await;
+    async fn test_get_channels_legacy_entry() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        let destination = hex::encode([3; 33]);
+        let source = hex::encode([2; 33]);
 
-        let lightning_info = ClnLightningInfo::new(cache.clone(), currencies.clone());
+        let (key, field) = info.cache_key_channels("BTC", destination.clone());
+        info.cache
+            .set(
+                &key,
+                &field,
+                &vec![test_channel("1x1x1", &source, &destination)],
+                None,
+            )
+            .await
+            .unwrap();
 
-        // Allow some time for the background task to update the cache
-        tokio::time::sleep(Duration::from_millis(1_000)).await;
+        let channels = info.get_channels("BTC", &[3; 33]).await.unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].short_channel_id, "1x1x1");
 
-        let btc = currencies.get("BTC").unwrap();
-        let mut cln = btc.cln.clone().unwrap();
+        let cached = info
+            .cache
+            .get::<DestinationChannels<Channel>>(&key, &field)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!cached.truncated);
+    }
 
-        let nodes = cln.list_nodes(None).await.unwrap();
-        assert!(!nodes.is_empty());
+    #[tokio::test]
+    async fn test_get_channels_between() {
+        let info = ClnLightningInfo::build(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig::default(),
+        );
+        let (a, b, other) = (
+            hex::encode([2; 33]),
+            hex::encode([3; 33]),
+            hex::encode([4; 33]),
+        );
 
-        let test_node = &nodes[0];
-        let node_id_hex = hex::encode(&test_node.nodeid);
+        for (destination, channels) in [
+            (
+                &b,
+                vec![
+                    test_channel("1x1x1", &a, &b),
+                    test_channel("2x1x1", &other, &b),
+                ],
+            ),
+            (
+                &a,
+                vec![
+                    test_channel("1x1x1", &b, &a),
+                    test_channel("3x1x1", &b, &a),
+                    test_channel("4x1x1", &other, &a),
+                ],
+            ),
+        ] {
+            let (key, field) = info.cache_key_channels("BTC", destination.clone());
+            info.cache
+                .set(
+                    &key,
+                    &field,
+                    &DestinationChannels {
+                        channels,
+                        truncated: false,
+                    },
+                    None,
+                )
+                .await
+                .unwrap();
+        }
 
-        let node_info = lightning_info.get_node_info("BTC", &test_node.nodeid).await;
-        assert!(node_info.is_ok());
+        let between = |channels: Vec<Channel>| {
+            channels
+                .into_iter()
+                .map(|channel| (channel.short_channel_id, channel.source.id))
+                .collect::<Vec<_>>()
+        };
+        let expected = vec![
+            ("1x1x1".to_string(), a.clone()),
+            ("1x1x1".to_string(), b.clone()),
+            ("3x1x1".to_string(), b.clone()),
+        ];
+        assert_eq!(
+            between(
+                info.get_channels_between("BTC", &[2; 33], &[3; 33])
+                    .await
+                    .unwrap()
+            ),
+            expected
+        );
+        assert_eq!(
+            between(
+                info.get_channels_between("BTC", &[3; 33], &[2; 33])
+                    .await
+                    .unwrap()
+            ),
+            expected
+        );
 
-        let node = node_info.unwrap();
-        assert_eq!(node.id, node_id_hex);
-        assert_eq!(node.alias, test_node.alias);
+        // Only one of the nodes has cached channels
+        assert!(
+            info.get_channels_between("BTC", &[2; 33], &[5; 33])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            info.get_channels_between("BTC", &[5; 33], &[6; 33])
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "no channels for node"
+        );
+    }
+
+    async fn set_channel_info(info: &ClnLightningInfo, sides: &[(&str, bool)]) {
+        let (key, field) = info.cache_key_channel("BTC", "700945x2144x1".to_string());
+        info.cache
+            .set(
+                &key,
+                &field,
+                &ChannelInfo {
+                    short_channel_id: "700945x2144x1".to_string(),
+                    capacity_sat: Some(100_000),
+                    policies: sides
+                        .iter()
+                        .map(|(id, active)| test_channel_side(id, *active))
+                        .collect(),
+                },
+                None,
+            )
+            .await
+            .unwrap();
     }
 
     #[tokio::test]
-    async fn test_get_channels() {
-        let mem_cache = MemCache::new();
-        let cache = Cache::Memory(mem_cache);
-        let currencies = get_currencies().await;
+    async fn test_get_channel_pair() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        set_channel_info(&info, &[("03bb", false), ("02aa", true)]).await;
 
-        let lightning_info = ClnLightningInfo::new(cache.clone(), currencies.clone());
+        let pair = info
+            .get_channel_pair("BTC", "700945x2144x1".to_string())
+            .await
+            .unwrap();
+        assert_eq!(pair.short_channel_id, "700945x2144x1");
+        assert_eq!(pair.capacity_sat, Some(100_000));
+        assert_eq!(pair.node1, "02aa");
+        assert_eq!(pair.node2, Some("03bb".to_string()));
+        assert!(pair.node1_policy.unwrap().active);
+        assert!(!pair.node2_policy.unwrap().active);
+    }
 
-        // Allow some time for the background task to update the cache
-        tokio::time::sleep(Duration::from_millis(1_000)).await;
+    #[tokio::test]
+    async fn test_get_channel_pair_one_direction() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+        set_channel_info(&info, &[("03bb", true)]).await;
 
-        let btc = currencies.get("BTC").unwrap();
-        let mut cln = btc.cln.clone().unwrap();
+        // The other node is not known without channels from the announced one
+        let pair = info
+            .get_channel_pair("BTC", "700945x2144x1".to_string())
+            .await
+            .unwrap();
+        assert_eq!(pair.node1, "03bb");
+        assert_eq!(pair.node2, None);
+        assert!(pair.node1_policy.is_some());
+        assert!(pair.node2_policy.is_none());
 
-        let channels = cln.list_channels(None).await.unwrap();
+        let side = test_channel_side("03bb", true);
+        let channel = Channel {
+            source: side.node,
+            short_channel_id: "700945x2144x1".to_string(),
+            capacity_sat: Some(100_000),
+            active: true,
+            info: side.policy,
+            destination: Some(Node {
+                id: "02aa".to_string(),
+                alias: None,
+                color: None,
+                last_update: None,
+            }),
+        };
+        let (key, field) = info.cache_key_channels_from("BTC", "03bb".to_string());
+        info.cache
+            .set(&key, &field, &vec![channel], None)
+            .await
+            .unwrap();
 
-        let channel = &channels[0];
-        let dest_node = &channel.destination;
+        let pair = info
+            .get_channel_pair("BTC", "700945x2144x1".to_string())
+            .await
+            .unwrap();
+        assert_eq!(pair.node1, "02aa");
+        assert_eq!(pair.node2, Some("03bb".to_string()));
+        assert!(pair.node1_policy.is_none());
+        assert!(pair.node2_policy.unwrap().active);
+    }
 
-        let channel_info = lightning_info.get_channels("BTC", dest_node).await;
-        assert!(channel_info.is_ok());
+    #[tokio::test]
+    async fn test_get_channel_pair_not_found() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
 
-        let channels = channel_info.unwrap();
-        assert!(!channels.is_empty());
+        let res = info
+            .get_channel_pair("BTC", "700945x2144x1".to_string())
+            .await;
+        assert_eq!(res.err().unwrap().to_string(), "channel not found");
+    }
+
+    #[tokio::test]
+    async fn test_get_channel_by_scid_not_found() {
+        let info = ClnLightningInfo::new(Cache::Memory(MemCache::new()), Arc::new(HashMap::new()));
+
+        let res = info
+            .get_channel_by_scid("BTC", "700945x2144x1".to_string())
+            .await;
+        assert_eq!(res.err().unwrap().to_string(), "channel not found");
     }
 
     #[rstest]
@@ -534,18 +5371,120 @@ mod test {
         assert!(result.is_err());
     }
 
+    #[rstest]
+    #[case(None, "cln")]
+    #[case(Some("mainnet-1"), "mainnet-1")]
     #[tokio::test]
-    async fn test_cache_keys() {
+    async fn test_cache_keys(#[case] cache_prefix: Option<&str>, #[case] expected_prefix: &str) {
+        let mut config = LightningInfoConfig::default();
+        if let Some(cache_prefix) = cache_prefix {
+            config.cache_prefix = cache_prefix.to_string();
+        }
+        let info = ClnLightningInfo::build(
+            Cache::Memory(MemCache::new()),
+            Arc::new(HashMap::new()),
+            config,
+        );
+
         let symbol = "BTC";
         let id = "03abcdef1234567890".to_string();
 
-        let (key, field) = ClnLightningInfo::cache_key_node(symbol, id.clone());
-        assert_eq!(key, "cln:BTC:node");
+        let (key, field) = info.cache_key_node(symbol, id.clone());
+        assert_eq!(key, format!("{expected_prefix}:BTC:node"));
         assert_eq!(field, id);
 
-        let (key, field) = ClnLightningInfo::cache_key_channels(symbol, id.clone());
-        assert_eq!(key, "cln:BTC:channels");
+        let (key, field) = info.cache_key_channel(symbol, id.clone());
+        assert_eq!(key, format!("{expected_prefix}:BTC:channel"));
         assert_eq!(field, id);
+
+        let (key, field) = info.cache_key_channels(symbol, id.clone());
+        assert_eq!(key, format!("{expected_prefix}:BTC:channels"));
+        assert_eq!(field, id);
+
+        let (key, field) = info.cache_key_channels_from(symbol, id.clone());
+        assert_eq!(key, format!("{expected_prefix}:BTC:channels_from"));
+        assert_eq!(field, id);
+    }
+
+    #[tokio::test]
+    async fn test_cache_prefix_refresh_and_lookup() {
+        let mem_cache = MemCache::new();
+        let info = ClnLightningInfo::with_config(
+            Cache::Memory(mem_cache.clone()),
+            Arc::new(HashMap::new()),
+            LightningInfoConfig {
+                cache_prefix: "instance".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        info.update_cache(&mut mock_gossip_unchanged())
+            .await
+            .unwrap();
+
+        assert!(
+            mem_cache
+                .map
+                .iter()
+                .all(|entry| entry.key().starts_with("instance:BTC:"))
+        );
+        assert!(info.get_node_info("BTC", &[2; 33]).await.is_ok());
+        assert_eq!(info.get_channels("BTC", &[3; 33]).await.unwrap().len(), 1);
+        assert_eq!(
+            info.get_channels_from("BTC", &[2; 33]).await.unwrap().len(),
+            1
+        );
+        assert!(info.get_channel("BTC", "1x1x1".to_string()).await.is_ok());
+    }
+
+    #[test]
+    fn test_validate_cache_prefix() {
+        let config = LightningInfoConfig {
+            cache_prefix: String::new(),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate().err().unwrap().to_string(),
+            "cache prefix must not be empty"
+        );
+    }
+
+    #[test]
+    fn test_node_serialize_last_update() {
+        let node = Node {
+            id: "02".to_string(),
+            alias: None,
+            color: None,
+            last_update: None,
+        };
+        assert_eq!(serde_json::to_string(&node).unwrap(), "{\"id\":\"02\"}");
+
+        let node = Node {
+            last_update: Some(1_700_000_000),
+            ..node
+        };
+        assert_eq!(
+            serde_json::to_string(&node).unwrap(),
+            "{\"id\":\"02\",\"lastUpdate\":1700000000}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_node_last_update_cache_roundtrip() {
+        let cache = Cache::Memory(MemCache::new());
+        let info = ClnLightningInfo::new(cache.clone(), Arc::new(HashMap::new()));
+        let node = Node {
+            id: "02".to_string(),
+            alias: Some("Boltz".to_string()),
+            color: None,
+            last_update: Some(1_700_000_000),
+        };
+
+        let (key, field) = info.cache_key_node("BTC", node.id.clone());
+        cache.set(&key, &field, &node, None).await.unwrap();
+
+        let cached: Node = cache.get(&key, &field).await.unwrap().unwrap();
+        assert_eq!(cached.last_update, Some(1_700_000_000));
     }
 
     #[tokio::test]
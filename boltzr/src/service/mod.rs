@@ -3,7 +3,9 @@ use crate::currencies::Currencies;
 use crate::db::helpers::chain_swap::ChainSwapHelper;
 use crate::db::helpers::swap::SwapHelper;
 use crate::service::country_codes::CountryCodes;
+use crate::service::eclair_info::EclairLightningInfo;
 use crate::service::lightning_info::{ClnLightningInfo, LightningInfo};
+use crate::service::multi_info::MultiLightningInfo;
 use crate::service::pair_stats::PairStatsFetcher;
 use crate::service::prometheus::{CachedPrometheusClient, RawPrometheusClient};
 use crate::service::rescue::SwapRescue;
@@ -11,13 +13,17 @@ use anyhow::Result;
 use std::sync::Arc;
 use tracing::warn;
 
+mod alias_index;
 mod country_codes;
+mod eclair_info;
 mod lightning_info;
+mod multi_info;
 mod pair_stats;
 mod prometheus;
 mod rescue;
 
 pub use country_codes::MarkingsConfig;
+pub use lightning_info::{Channel, GraphCursor, LightningInfoConfig, LookupError};
 pub use pair_stats::HistoricalConfig;
 
 pub struct Service {
@@ -34,12 +40,17 @@ impl Service {
         currencies: Currencies,
         markings_config: Option<MarkingsConfig>,
         historical_config: Option<HistoricalConfig>,
+        gossip_config: Option<LightningInfoConfig>,
         cache: Cache,
-    ) -> Self {
-        Self {
+    ) -> Result<Self> {
+        Ok(Self {
             swap_rescue: SwapRescue::new(swap_helper, chain_swap_helper, currencies.clone()),
             country_codes: CountryCodes::new(markings_config),
-            lightning_info: Box::new(ClnLightningInfo::new(cache.clone(), currencies)),
+            lightning_info: Self::lightning_info(
+                cache.clone(),
+                currencies,
+                gossip_config.unwrap_or_default(),
+            )?,
             pair_stats: if let Some(config) = historical_config {
                 Some(PairStatsFetcher::new(
                     Arc::new(CachedPrometheusClient::new(
@@ -55,7 +66,31 @@ impl Service {
                 warn!("Historical data config is missing");
                 None
             },
-        }
+        })
+    }
+
+    fn lightning_info(
+        cache: Cache,
+        currencies: Currencies,
+        config: LightningInfoConfig,
+    ) -> Result<Box<dyn LightningInfo + Send + Sync>> {
+        let has_cln = currencies.values().any(|currency| currency.cln.is_some());
+        let has_eclair = currencies
+            .values()
+            .any(|currency| currency.eclair.is_some());
+
+        Ok(match (has_cln, has_eclair) {
+            (true, true) => Box::new(MultiLightningInfo::new(vec![
+                Arc::new(ClnLightningInfo::with_config(
+                    cache.clone(),
+                    currencies.clone(),
+                    config.clone(),
+                )?),
+                Arc::new(EclairLightningInfo::with_config(cache, currencies, config)?),
+            ])),
+            (false, true) => Box::new(EclairLightningInfo::with_config(cache, currencies, config)?),
+            _ => Box::new(ClnLightningInfo::with_config(cache, currencies, config)?),
+        })
     }
 
     pub async fn start(&self) -> Result<()> {
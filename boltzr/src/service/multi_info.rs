@@ -0,0 +1,906 @@
+use crate::service::lightning_info::{
+    Channel, ChannelInfo, ClnLightningInfo, GossipDiagnostics, GossipStats, GraphCursor,
+    GraphExport, LightningInfo, Node, NodeSymbols, PolicySummary, RankedNode,
+};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use futures::future::join_all;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Combines the gossip of several backends into one view
+pub struct MultiLightningInfo {
+    backends: Vec<Arc<dyn LightningInfo + Send + Sync>>,
+}
+
+impl MultiLightningInfo {
+    pub fn new(backends: Vec<Arc<dyn LightningInfo + Send + Sync>>) -> Self {
+        Self { backends }
+    }
+
+    // Result of the first backend that succeeds, in the order they were added
+    async fn first<'a, T, F, Fut>(&'a self, lookup: F) -> Result<T>
+    where
+        F: Fn(&'a (dyn LightningInfo + Send + Sync)) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut last_err = None;
+        for backend in &self.backends {
+            match lookup(backend.as_ref()).await {
+                Ok(res) => return Ok(res),
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| anyhow!("no lightning info backends")))
+    }
+
+    // Results of all backends that succeeded; fails only when none did
+    async fn all<'a, T, F, Fut>(&'a self, lookup: F) -> Result<Vec<T>>
+    where
+        F: Fn(&'a (dyn LightningInfo + Send + Sync)) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut results = Vec::new();
+        let mut first_err = None;
+
+        for res in join_all(self.backends.iter().map(|backend| lookup(backend.as_ref()))).await {
+            match res {
+                Ok(res) => results.push(res),
+                Err(err) => {
+                    if first_err.is_none() {
+                        first_err = Some(err);
+                    }
+                }
+            }
+        }
+
+        match first_err {
+            Some(err) if results.is_empty() => Err(err),
+            _ => Ok(results),
+        }
+    }
+
+    fn merge_scored(results: Vec<Vec<(Node, f64)>>) -> Vec<(Node, f64)> {
+        let mut merged = HashMap::<String, (Node, f64)>::new();
+        for (node, distance) in results.into_iter().flatten() {
+            match merged.get_mut(&node.id) {
+                Some(existing) => {
+                    if distance < existing.1 {
+                        *existing = (node, distance);
+                    }
+                }
+                None => {
+                    merged.insert(node.id.clone(), (node, distance));
+                }
+            }
+        }
+
+        let mut merged = merged.into_values().collect::<Vec<_>>();
+        merged.sort_by(|a, b| {
+            a.1.partial_cmp(&b.1)
+                .unwrap()
+                .then_with(|| a.0.id.cmp(&b.0.id))
+        });
+
+        merged
+    }
+
+    fn merge_nodes(results: Vec<Vec<Node>>) -> Vec<Node> {
+        let mut seen = HashSet::new();
+        let mut merged = results
+            .into_iter()
+            .flatten()
+            .filter(|node| seen.insert(node.id.clone()))
+            .collect::<Vec<_>>();
+        merged.sort_by(|a, b| a.id.cmp(&b.id));
+
+        merged
+    }
+
+    // Each direction of a channel is a separate entry
+    fn merge_channels(results: Vec<Vec<Channel>>) -> Vec<Channel> {
+        let mut seen = HashSet::new();
+        let mut merged = results
+            .into_iter()
+            .flatten()
+            .filter(|channel| {
+                seen.insert((channel.short_channel_id.clone(), channel.source.id.clone()))
+            })
+            .collect::<Vec<_>>();
+        ClnLightningInfo::sort_by_capacity(&mut merged);
+
+        merged
+    }
+}
+
+#[async_trait]
+impl LightningInfo for MultiLightningInfo {
+    async fn find_node_by_alias(&self, symbol: &str, alias: &str) -> Result<Vec<Node>> {
+        Ok(self
+            .find_node_by_alias_scored(symbol, alias)
+            .await?
+            .into_iter()
+            .map(|(node, _)| node)
+            .collect())
+    }
+
+    async fn find_node_by_alias_paged(
+        &self,
+        symbol: &str,
+        alias: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Node>> {
+        Ok(self
+            .find_node_by_alias_scored(symbol, alias)
+            .await?
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(node, _)| node)
+            .collect())
+    }
+
+    async fn find_node_by_alias_scored(
+        &self,
+        symbol: &str,
+        alias: &str,
+    ) -> Result<Vec<(Node, f64)>> {
+        Ok(Self::merge_scored(
+            self.all(|backend| backend.find_node_by_alias_scored(symbol, alias))
+                .await?,
+        ))
+    }
+
+    // Merged per symbol with the distances, so that the best match of any backend ranks first
+    async fn find_node_by_alias_all(&self, alias: &str) -> Result<Vec<NodeSymbols>> {
+        let symbols = self.loaded_symbols().await;
+
+        let mut results = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            let nodes = self.find_node_by_alias_scored(&symbol, alias).await?;
+            results.push((symbol, nodes));
+        }
+
+        Ok(ClnLightningInfo::merge_search_results(results)
+            .into_iter()
+            .map(|r| r.node)
+            .collect())
+    }
+
+    async fn find_node_by_id_prefix(&self, symbol: &str, prefix: &str) -> Result<Vec<Node>> {
+        Ok(Self::merge_nodes(
+            self.all(|backend| backend.find_node_by_id_prefix(symbol, prefix))
+                .await?,
+        ))
+    }
+
+    async fn get_channels(&self, symbol: &str, destination: &[u8]) -> Result<Vec<Channel>> {
+        Ok(Self::merge_channels(
+            self.all(|backend| backend.get_channels(symbol, destination))
+                .await?,
+        ))
+    }
+
+    async fn get_active_channels(&self, symbol: &str, destination: &[u8]) -> Result<Vec<Channel>> {
+        Ok(Self::merge_channels(
+            self.all(|backend| backend.get_active_channels(symbol, destination))
+                .await?,
+        ))
+    }
+
+    async fn get_channels_from(&self, symbol: &str, source: &[u8]) -> Result<Vec<Channel>> {
+        Ok(Self::merge_channels(
+            self.all(|backend| backend.get_channels_from(symbol, source))
+                .await?,
+        ))
+    }
+
+    async fn get_channel(&self, symbol: &str, short_channel_id: String) -> Result<ChannelInfo> {
+        self.first(|backend| backend.get_channel(symbol, short_channel_id.clone()))
+            .await
+    }
+
+    async fn get_channel_by_scid(&self, symbol: &str, short_channel_id: String) -> Result<Channel> {
+        self.first(|backend| backend.get_channel_by_scid(symbol, short_channel_id.clone()))
+            .await
+    }
+
+    async fn get_node_info(&self, symbol: &str, node: &[u8]) -> Result<Node> {
+        self.first(|backend| backend.get_node_info(symbol, node))
+            .await
+    }
+
+    async fn node_ttl(&self, symbol: &str, node: &[u8]) -> Result<Option<Duration>> {
+        for backend in &self.backends {
+            if let Some(ttl) = backend.node_ttl(symbol, node).await? {
+                return Ok(Some(ttl));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn node_exists(&self, symbol: &str, node: &[u8]) -> Result<bool> {
+        Ok(self
+            .all(|backend| backend.node_exists(symbol, node))
+            .await?
+            .into_iter()
+            .any(|exists| exists))
+    }
+
+    // Backends fill in the nodes that the ones before them do not know
+    async fn get_node_infos(&self, symbol: &str, nodes: &[Vec<u8>]) -> Result<Vec<Option<Node>>> {
+        let mut merged = vec![None; nodes.len()];
+        for infos in self
+            .all(|backend| backend.get_node_infos(symbol, nodes))
+            .await?
+        {
+            for (merged, info) in merged.iter_mut().zip(infos) {
+                if merged.is_none() {
+                    *merged = info;
+                }
+            }
+        }
+
+        Ok(merged)
+    }
+
+    // Computed over the merged channels, since the summaries of the backends cannot be combined
+    async fn get_node_policy_summary(
+        &self,
+        symbol: &str,
+        node: &[u8],
+    ) -> Result<Option<PolicySummary>> {
+        let summaries = self
+            .all(|backend| backend.get_node_policy_summary(symbol, node))
+            .await?;
+        if summaries.iter().all(Option::is_none) {
+            return Ok(None);
+        }
+
+        Ok(PolicySummary::from_channels(
+            &self.get_channels(symbol, node).await?,
+        ))
+    }
+
+    async fn gossip_stats(&self, symbol: &str) -> Option<GossipStats> {
+        for backend in &self.backends {
+            if let Some(stats) = backend.gossip_stats(symbol).await {
+                return Some(stats);
+            }
+        }
+
+        None
+    }
+
+    async fn gossip_diagnostics(&self, symbol: &str) -> Option<GossipDiagnostics> {
+        for backend in &self.backends {
+            if let Some(diagnostics) = backend.gossip_diagnostics(symbol).await {
+                return Some(diagnostics);
+            }
+        }
+
+        None
+    }
+
+    // Age of the freshest gossip of all backends
+    async fn gossip_age(&self, symbol: &str) -> Option<Duration> {
+        join_all(
+            self.backends
+                .iter()
+                .map(|backend| backend.gossip_age(symbol)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    async fn is_gossip_fresh(&self, symbol: &str) -> bool {
+        for backend in &self.backends {
+            if backend.is_gossip_fresh(symbol).await {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    async fn loaded_symbols(&self) -> Vec<String> {
+        let mut symbols = join_all(self.backends.iter().map(|backend| backend.loaded_symbols()))
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
+    // The backends know mostly the same nodes, so their counts are not summed up
+    async fn node_count(&self, symbol: &str) -> Option<usize> {
+        join_all(
+            self.backends
+                .iter()
+                .map(|backend| backend.node_count(symbol)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .max()
+    }
+
+    // Succeeds when any backend has a gossip source for the symbol
+    async fn refresh_now(&self, symbol: &str) -> Result<()> {
+        self.all(|backend| backend.refresh_now(symbol)).await?;
+        Ok(())
+    }
+
+    // Merging the graphs of several backends would mix their views of the channels
+    async fn export_graph(
+        &self,
+        symbol: &str,
+        cursor: &GraphCursor,
+        limit: usize,
+    ) -> Result<GraphExport> {
+        self.first(|backend| backend.export_graph(symbol, cursor, limit))
+            .await
+    }
+
+    // Capacities are only comparable within the graph of one backend
+    async fn top_nodes_by_capacity(&self, symbol: &str, n: usize) -> Result<Vec<RankedNode>> {
+        self.first(|backend| backend.top_nodes_by_capacity(symbol, n))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::service::lightning_info::ChannelPolicy;
+    use mockall::mock;
+
+    mock! {
+        Info {}
+
+        #[async_trait]
+        impl LightningInfo for Info {
+            async fn find_node_by_alias(&self, symbol: &str, alias: &str) -> Result<Vec<Node>>;
+            async fn find_node_by_alias_paged(
+                &self,
+                symbol: &str,
+                alias: &str,
+                limit: usize,
+                offset: usize,
+            ) -> Result<Vec<Node>>;
+            async fn find_node_by_alias_scored(
+                &self,
+                symbol: &str,
+                alias: &str,
+            ) -> Result<Vec<(Node, f64)>>;
+            async fn find_node_by_alias_all(&self, alias: &str) -> Result<Vec<NodeSymbols>>;
+            async fn find_node_by_id_prefix(&self, symbol: &str, prefix: &str) -> Result<Vec<Node>>;
+            async fn get_channels(&self, symbol: &str, destination: &[u8]) -> Result<Vec<Channel>>;
+            async fn get_active_channels(
+                &self,
+                symbol: &str,
+                destination: &[u8],
+            ) -> Result<Vec<Channel>>;
+            async fn get_channels_from(&self, symbol: &str, source: &[u8]) -> Result<Vec<Channel>>;
+            async fn get_channel(&self, symbol: &str, short_channel_id: String) -> Result<ChannelInfo>;
+            async fn get_channel_by_scid(
+                &self,
+                symbol: &str,
+                short_channel_id: String,
+            ) -> Result<Channel>;
+            async fn get_node_info(&self, symbol: &str, node: &[u8]) -> Result<Node>;
+            async fn node_exists(&self, symbol: &str, node: &[u8]) -> Result<bool>;
+            async fn node_ttl(&self, symbol: &str, node: &[u8]) -> Result<Option<Duration>>;
+            async fn get_node_infos(
+                &self,
+                symbol: &str,
+                nodes: &[Vec<u8>],
+            ) -> Result<Vec<Option<Node>>>;
+            async fn get_node_policy_summary(
+                &self,
+                symbol: &str,
+                node: &[u8],
+            ) -> Result<Option<PolicySummary>>;
+            async fn gossip_stats(&self, symbol: &str) -> Option<GossipStats>;
+            async fn gossip_diagnostics(&self, symbol: &str) -> Option<GossipDiagnostics>;
+            async fn gossip_age(&self, symbol: &str) -> Option<Duration>;
+            async fn is_gossip_fresh(&self, symbol: &str) -> bool;
+            async fn loaded_symbols(&self) -> Vec<String>;
+            async fn node_count(&self, symbol: &str) -> Option<usize>;
+            async fn refresh_now(&self, symbol: &str) -> Result<()>;
+            async fn export_graph(
+                &self,
+                symbol: &str,
+                cursor: &GraphCursor,
+                limit: usize,
+            ) -> Result<GraphExport>;
+            async fn top_nodes_by_capacity(
+                &self,
+                symbol: &str,
+                n: usize,
+            ) -> Result<Vec<RankedNode>>;
+        }
+    }
+
+    fn node(id: &str) -> Node {
+        Node {
+            id: id.to_string(),
+            alias: Some(format!("alias-{id}")),
+            color: None,
+            last_update: None,
+        }
+    }
+
+    fn channel(short_channel_id: &str, source: &str, capacity_sat: u64) -> Channel {
+        Channel {
+            source: node(source),
+            short_channel_id: short_channel_id.to_string(),
+            capacity_sat: Some(capacity_sat),
+            active: true,
+            info: ChannelPolicy {
+                active: true,
+                base_fee_millisatoshi: 1_000,
+                fee_ppm: 100,
+                delay: 80,
+                htlc_minimum_millisatoshi: None,
+                htlc_maximum_millisatoshi: None,
+                last_update: None,
+            },
+            destination: None,
+        }
+    }
+
+    fn scored(nodes: Vec<(&'static str, f64)>) -> MockInfo {
+        let mut info = MockInfo::new();
+        info.expect_find_node_by_alias_scored()
+            .returning(move |_, _| {
+                Ok(nodes
+                    .iter()
+                    .map(|(id, distance)| (node(id), *distance))
+                    .collect())
+            });
+        info
+    }
+
+    fn failing_scored(err: &'static str) -> MockInfo {
+        let mut info = MockInfo::new();
+        info.expect_find_node_by_alias_scored()
+            .returning(move |_, _| Err(anyhow!(err)));
+        info
+    }
+
+    fn multi(backends: Vec<MockInfo>) -> MultiLightningInfo {
+        MultiLightningInfo::new(
+            backends
+                .into_iter()
+                .map(|backend| Arc::new(backend) as Arc<dyn LightningInfo + Send + Sync>)
+                .collect(),
+        )
+    }
+
+    fn ids<'a>(nodes: impl IntoIterator<Item = &'a Node>) -> Vec<&'a str> {
+        nodes.into_iter().map(|node| node.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_overlapping() {
+        let info = multi(vec![
+            scored(vec![("1", 0.05), ("2", 0.2)]),
+            scored(vec![("1", 0.01), ("3", 0.1)]),
+        ]);
+
+        let nodes = info
+            .find_node_by_alias_scored("BTC", "alias")
+            .await
+            .unwrap();
+        assert_eq!(ids(nodes.iter().map(|(node, _)| node)), vec!["1", "3", "2"]);
+        assert_eq!(nodes[0].1, 0.01);
+
+        let nodes = info.find_node_by_alias("BTC", "alias").await.unwrap();
+        assert_eq!(ids(&nodes), vec!["1", "3", "2"]);
+
+        let nodes = info
+            .find_node_by_alias_paged("BTC", "alias", 1, 1)
+            .await
+            .unwrap();
+        assert_eq!(ids(&nodes), vec!["3"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_disjoint() {
+        let info = multi(vec![
+            scored(vec![("2", 0.1)]),
+            failing_scored("no nodes for BTC"),
+            scored(vec![("1", 0.1)]),
+        ]);
+
+        let nodes = info.find_node_by_alias("BTC", "alias").await.unwrap();
+        assert_eq!(ids(&nodes), vec!["1", "2"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_id_prefix() {
+        let backends = [vec!["0a2", "0a1"], vec!["0a1", "0a3"]]
+            .into_iter()
+            .map(|nodes| {
+                let mut info = MockInfo::new();
+                info.expect_find_node_by_id_prefix()
+                    .returning(move |_, _| Ok(nodes.iter().map(|id| node(id)).collect()));
+                info
+            })
+            .collect();
+
+        let nodes = multi(backends)
+            .find_node_by_id_prefix("BTC", "0a")
+            .await
+            .unwrap();
+        assert_eq!(ids(&nodes), vec!["0a1", "0a2", "0a3"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_all_failed() {
+        let info = multi(vec![
+            failing_scored("no nodes for BTC"),
+            failing_scored("other"),
+        ]);
+
+        assert_eq!(
+            info.find_node_by_alias("BTC", "alias")
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "no nodes for BTC"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_node_by_alias_all() {
+        let backend = |btc: Vec<(&'static str, f64)>, liquid: Vec<(&'static str, f64)>| {
+            let mut info = MockInfo::new();
+            info.expect_loaded_symbols()
+                .returning(|| vec!["BTC".to_string(), "L-BTC".to_string()]);
+            info.expect_find_node_by_alias_scored()
+                .returning(move |symbol, _| {
+                    let nodes = if symbol == "BTC" { &btc } else { &liquid };
+                    Ok(nodes
+                        .iter()
+                        .map(|(id, distance)| (node(id), *distance))
+                        .collect())
+                });
+            info
+        };
+
+        let nodes = multi(vec![
+            backend(vec![("1", 0.2), ("2", 0.5)], vec![]),
+            backend(vec![("3", 0.1), ("1", 0.3)], vec![("1", 0.4), ("4", 0.5)]),
+        ])
+        .find_node_by_alias_all("alias")
+        .await
+        .unwrap();
+
+        // The better match of the second backend ranks above the first one
+        assert_eq!(ids(nodes.iter().map(|n| &n.node)), vec!["3", "1", "2", "4"]);
+        assert_eq!(nodes[1].symbols, vec!["BTC", "L-BTC"]);
+        assert_eq!(nodes[2].symbols, vec!["BTC"]);
+        assert_eq!(nodes[3].symbols, vec!["L-BTC"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_node_info_first_success() {
+        let mut first = MockInfo::new();
+        first
+            .expect_get_node_info()
+            .returning(|_, _| Err(anyhow!("node not found")));
+
+        let mut second = MockInfo::new();
+        second
+            .expect_get_node_info()
+            .returning(|_, _| Ok(node("2")));
+
+        let mut third = MockInfo::new();
+        third.expect_get_node_info().never();
+
+        let info = multi(vec![first, second, third]);
+        assert_eq!(info.get_node_info("BTC", &[2]).await.unwrap().id, "2");
+    }
+
+    #[tokio::test]
+    async fn test_node_exists() {
+        let mut first = MockInfo::new();
+        first
+            .expect_node_exists()
+            .returning(|_, _| Err(anyhow!("first")));
+
+        let mut second = MockInfo::new();
+        second
+            .expect_node_exists()
+            .returning(|_, node| Ok(node == [2]));
+
+        let info = multi(vec![first, second]);
+        assert!(info.node_exists("BTC", &[2]).await.unwrap());
+        assert!(!info.node_exists("BTC", &[3]).await.unwrap());
+
+        let mut failing = MockInfo::new();
+        failing
+            .expect_node_exists()
+            .returning(|_, _| Err(anyhow!("failing")));
+        assert_eq!(
+            multi(vec![failing])
+                .node_exists("BTC", &[2])
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "failing"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_node_infos() {
+        let mut first = MockInfo::new();
+        first
+            .expect_get_node_infos()
+            .returning(|_, _| Ok(vec![Some(node("1")), None, None]));
+
+        let mut second = MockInfo::new();
+        second
+            .expect_get_node_infos()
+            .returning(|_, _| Ok(vec![Some(node("other")), Some(node("2")), None]));
+
+        let mut third = MockInfo::new();
+        third
+            .expect_get_node_infos()
+            .returning(|_, _| Err(anyhow!("third")));
+
+        let info = multi(vec![first, second, third]);
+        let nodes = info
+            .get_node_infos("BTC", &[vec![1], vec![2], vec![3]])
+            .await
+            .unwrap();
+        assert_eq!(
+            nodes
+                .iter()
+                .map(|node| node.as_ref().map(|node| node.id.as_str()))
+                .collect::<Vec<_>>(),
+            vec![Some("1"), Some("2"), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_node_info_not_found() {
+        let mut first = MockInfo::new();
+        first
+            .expect_get_node_info()
+            .returning(|_, _| Err(anyhow!("first")));
+
+        let mut second = MockInfo::new();
+        second
+            .expect_get_node_info()
+            .returning(|_, _| Err(anyhow!("node not found")));
+
+        let info = multi(vec![first, second]);
+        assert_eq!(
+            info.get_node_info("BTC", &[2])
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "node not found"
+        );
+
+        assert_eq!(
+            multi(vec![])
+                .get_node_info("BTC", &[2])
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "no lightning info backends"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_channels_overlapping() {
+        let mut first = MockInfo::new();
+        first.expect_get_channels().returning(|_, _| {
+            Ok(vec![
+                channel("1x1x1", "02a", 1_000),
+                channel("1x1x2", "02a", 2_000),
+            ])
+        });
+
+        let mut second = MockInfo::new();
+        second.expect_get_channels().returning(|_, _| {
+            Ok(vec![
+                channel("1x1x1", "02a", 1_000),
+                channel("1x1x1", "02b", 1_000),
+                channel("1x1x3", "02c", 5_000),
+            ])
+        });
+
+        let channels = multi(vec![first, second])
+            .get_channels("BTC", &[2])
+            .await
+            .unwrap();
+        assert_eq!(
+            channels
+                .iter()
+                .map(|channel| (
+                    channel.short_channel_id.as_str(),
+                    channel.source.id.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("1x1x3", "02c"),
+                ("1x1x2", "02a"),
+                ("1x1x1", "02a"),
+                ("1x1x1", "02b"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_node_policy_summary() {
+        let mut first = MockInfo::new();
+        first
+            .expect_get_node_policy_summary()
+            .returning(|_, _| Ok(None));
+        first
+            .expect_get_channels()
+            .returning(|_, _| Err(anyhow!("no channels for node")));
+
+        let mut second = MockInfo::new();
+        second
+            .expect_get_node_policy_summary()
+            .returning(|_, _| Ok(PolicySummary::from_channels(&[channel("1x1x1", "02a", 1)])));
+        second.expect_get_channels().returning(|_, _| {
+            let mut cheap = channel("1x1x2", "02b", 1);
+            cheap.info.base_fee_millisatoshi = 0;
+            Ok(vec![channel("1x1x1", "02a", 1), cheap])
+        });
+
+        let summary = multi(vec![first, second])
+            .get_node_policy_summary("BTC", &[2])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(summary.channel_count, 2);
+        assert_eq!(summary.base_fee_millisatoshi.min, 0);
+        assert_eq!(summary.base_fee_millisatoshi.median, 500.0);
+        assert_eq!(summary.fee_ppm.max, 100);
+    }
+
+    #[tokio::test]
+    async fn test_get_node_policy_summary_none() {
+        let backends = (0..2)
+            .map(|_| {
+                let mut info = MockInfo::new();
+                info.expect_get_node_policy_summary()
+                    .returning(|_, _| Ok(None));
+                info.expect_get_channels().never();
+                info
+            })
+            .collect();
+
+        assert_eq!(
+            multi(backends)
+                .get_node_policy_summary("BTC", &[2])
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_channels_disjoint() {
+        let mut first = MockInfo::new();
+        first
+            .expect_get_channels_from()
+            .returning(|_, _| Err(anyhow!("no channels for node")));
+
+        let mut second = MockInfo::new();
+        second
+            .expect_get_channels_from()
+            .returning(|_, _| Ok(vec![channel("1x1x1", "02a", 1_000)]));
+
+        let channels = multi(vec![first, second])
+            .get_channels_from("BTC", &[2])
+            .await
+            .unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].short_channel_id, "1x1x1");
+    }
+
+    #[tokio::test]
+    async fn test_gossip_freshness() {
+        let mut first = MockInfo::new();
+        first
+            .expect_gossip_age()
+            .returning(|_| Some(Duration::from_secs(120)));
+        first.expect_is_gossip_fresh().returning(|_| false);
+        first.expect_gossip_stats().returning(|_| None);
+
+        let mut second = MockInfo::new();
+        second
+            .expect_gossip_age()
+            .returning(|_| Some(Duration::from_secs(60)));
+        second.expect_is_gossip_fresh().returning(|_| true);
+        second.expect_gossip_stats().returning(|_| {
+            Some(GossipStats {
+                node_count: 2,
+                ..Default::default()
+            })
+        });
+
+        let mut third = MockInfo::new();
+        third.expect_gossip_age().returning(|_| None);
+
+        let info = multi(vec![first, second, third]);
+        assert_eq!(info.gossip_age("BTC").await, Some(Duration::from_secs(60)));
+        assert!(info.is_gossip_fresh("BTC").await);
+        assert_eq!(info.gossip_stats("BTC").await.unwrap().node_count, 2);
+
+        assert_eq!(multi(vec![]).gossip_age("BTC").await, None);
+        assert!(!multi(vec![]).is_gossip_fresh("BTC").await);
+    }
+
+    #[tokio::test]
+    async fn test_loaded_symbols() {
+        let mut first = MockInfo::new();
+        first
+            .expect_loaded_symbols()
+            .returning(|| vec!["BTC".to_string()]);
+        first.expect_node_count().returning(|_| Some(2));
+
+        let mut second = MockInfo::new();
+        second
+            .expect_loaded_symbols()
+            .returning(|| vec!["BTC".to_string(), "L-BTC".to_string()]);
+        second
+            .expect_node_count()
+            .returning(|symbol| (symbol == "BTC").then_some(3));
+
+        let info = multi(vec![first, second]);
+        assert_eq!(
+            info.loaded_symbols().await,
+            vec!["BTC".to_string(), "L-BTC".to_string()]
+        );
+        assert_eq!(info.node_count("BTC").await, Some(3));
+        assert_eq!(info.node_count("L-BTC").await, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_now() {
+        let mut first = MockInfo::new();
+        first.expect_refresh_now().returning(|symbol| {
+            if symbol == "BTC" {
+                Ok(())
+            } else {
+                Err(anyhow!("no gossip source for {symbol}"))
+            }
+        });
+
+        let mut second = MockInfo::new();
+        second
+            .expect_refresh_now()
+            .times(2)
+            .returning(|symbol| Err(anyhow!("no gossip source for {symbol}")));
+
+        let info = multi(vec![first, second]);
+        info.refresh_now("BTC").await.unwrap();
+        assert_eq!(
+            info.refresh_now("L-BTC").await.err().unwrap().to_string(),
+            "no gossip source for L-BTC"
+        );
+    }
+}
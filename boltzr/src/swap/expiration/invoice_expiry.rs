@@ -166,6 +166,7 @@ mod test {
                         ))),
                         cln: None,
                         lnd: None,
+                        eclair: None,
                     },
                 )]))
             })
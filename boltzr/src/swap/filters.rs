@@ -1,11 +1,10 @@
 use crate::chain::utils::Outpoint;
-use crate::currencies::{Currencies, Currency};
+use crate::currencies::{Currencies, Currency, address_to_script_pubkey};
 use crate::db::helpers::chain_swap::ChainSwapHelper;
 use crate::db::helpers::reverse_swap::ReverseSwapHelper;
 use crate::db::helpers::swap::SwapHelper;
 use crate::db::models::{LightningSwap, SomeSwap};
 use crate::swap::status::{SwapUpdate, serialize_swap_updates};
-use crate::wallet::Wallet;
 use alloy::hex;
 use anyhow::Result;
 use diesel::{BoolExpressionMethods, ExpressionMethods};
@@ -47,11 +46,10 @@ fn get_swap_filters(
 
     for swap in pending_swaps {
         let chain_symbol = swap.chain_symbol()?;
-        let currency = match get_currency(currencies, &chain_symbol, &swap) {
-            Some(currency) => currency,
-            None => continue,
-        };
-        let script = match decode_script(&currency.wallet, &swap, &swap.lockupAddress) {
+        if get_currency(currencies, &chain_symbol, &swap).is_none() {
+            continue;
+        }
+        let script = match decode_script(currencies, &chain_symbol, &swap, &swap.lockupAddress) {
             Some(script) => script,
             None => continue,
         };
@@ -116,12 +114,15 @@ fn get_chain_filters(
         match swap.status() {
             SwapUpdate::SwapCreated | SwapUpdate::TransactionMempool => {
                 let receiving = swap.receiving();
-                let currency = match get_currency(currencies, &receiving.symbol, &swap) {
-                    Some(currency) => currency,
-                    None => continue,
-                };
-                let script = match decode_script(&currency.wallet, &swap, &receiving.lockupAddress)
-                {
+                if get_currency(currencies, &receiving.symbol, &swap).is_none() {
+                    continue;
+                }
+                let script = match decode_script(
+                    currencies,
+                    &receiving.symbol,
+                    &swap,
+                    &receiving.lockupAddress,
+                ) {
                     Some(script) => script,
                     None => continue,
                 };
@@ -178,14 +179,15 @@ where
 }
 
 fn decode_script<S>(
-    wallet: &Arc<dyn Wallet + Send + Sync>,
+    currencies: &Currencies,
+    symbol: &str,
     swap: &S,
     address: &str,
 ) -> Option<Vec<u8>>
 where
     S: SomeSwap,
 {
-    match wallet.decode_address(address) {
+    match address_to_script_pubkey(currencies, symbol, address) {
         Ok(script) => Some(script),
         Err(err) => {
             warn!(
@@ -292,6 +294,7 @@ mod test {
                             ))),
                             cln: None,
                             lnd: None,
+                            eclair: None,
                         },
                     ),
                     (
@@ -305,6 +308,7 @@ mod test {
                             chain: None,
                             cln: None,
                             lnd: None,
+                            eclair: None,
                         },
                     ),
                     (
@@ -320,6 +324,7 @@ mod test {
                             ))),
                             cln: None,
                             lnd: None,
+                            eclair: None,
                         },
                     ),
                 ]))
@@ -553,8 +558,7 @@ mod test {
 
     #[test]
     fn test_decode_script() {
-        let wallet: Arc<dyn Wallet + Send + Sync> =
-            Arc::new(Bitcoin::new(Network::Regtest, &get_seed(), "m/0/0".to_string()).unwrap());
+        let currencies = get_currencies();
         let swap = Swap {
             id: "id".to_string(),
             ..Default::default()
@@ -562,21 +566,36 @@ mod test {
         let address = "bcrt1pjcv9r3jeug6xmgug6hu0p4lux7r9996yxk9m2xxammfqq4kxdvkqhdu0h5";
 
         assert_eq!(
-            decode_script(&wallet, &swap, address),
-            Some(wallet.decode_address(address).unwrap())
+            decode_script(&currencies, "BTC", &swap, address),
+            Some(
+                currencies
+                    .get("BTC")
+                    .unwrap()
+                    .wallet
+                    .decode_address(address)
+                    .unwrap()
+            )
         );
     }
 
     #[test]
     fn test_decode_script_invalid() {
-        let wallet: Arc<dyn Wallet + Send + Sync> =
-            Arc::new(Bitcoin::new(Network::Regtest, &get_seed(), "m/0/0".to_string()).unwrap());
+        let currencies = get_currencies();
         let swap = Swap {
             id: "id".to_string(),
             ..Default::default()
         };
 
-        assert_eq!(decode_script(&wallet, &swap, "invalid"), None);
+        assert_eq!(decode_script(&currencies, "BTC", &swap, "invalid"), None);
+        assert_eq!(
+            decode_script(
+                &currencies,
+                "NOTFOUND",
+                &swap,
+                "bcrt1pjcv9r3jeug6xmgug6hu0p4lux7r9996yxk9m2xxammfqq4kxdvkqhdu0h5"
+            ),
+            None
+        );
     }
 
     #[test]
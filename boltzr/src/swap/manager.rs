@@ -193,7 +193,18 @@ impl SwapManager for Manager {
                 }
             };
 
-            (client.symbol(), client.scan_mempool(inputs, outputs).await)
+            // Rescans of big mempools take a while
+            let symbol = client.symbol();
+            let res = client
+                .scan_mempool_with_progress(inputs, outputs, &|scanned, total| {
+                    debug!(
+                        "Rescanned {}/{} mempool transactions of {}",
+                        scanned, total, symbol
+                    );
+                })
+                .await;
+
+            (client.symbol(), res)
         }))
         .await;
 
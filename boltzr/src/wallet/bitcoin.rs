@@ -15,6 +15,7 @@ impl Bitcoin {
             network: match network {
                 Network::Mainnet => bitcoin::Network::Bitcoin,
                 Network::Testnet => bitcoin::Network::Testnet,
+                Network::Testnet4 => bitcoin::Network::Testnet4,
                 Network::Signet => bitcoin::Network::Signet,
                 Network::Regtest => bitcoin::Network::Regtest,
             },
@@ -59,9 +60,20 @@ mod test {
         )
     }
 
+    // Native SegWit address of the key at the index
+    fn derive_address(wallet: &Bitcoin, index: u64) -> bitcoin::Address {
+        let key = wallet.derive_keys(index).unwrap();
+        let public_key =
+            bitcoin::CompressedPublicKey(key.private_key.public_key(&Secp256k1::signing_only()));
+
+        bitcoin::Address::p2wpkh(&public_key, wallet.network)
+    }
+
     #[rstest]
     #[case::mainnet(Network::Mainnet, bitcoin::Network::Bitcoin)]
     #[case::testnet(Network::Testnet, bitcoin::Network::Testnet)]
+    #[case::testnet4(Network::Testnet4, bitcoin::Network::Testnet4)]
+    #[case::signet(Network::Signet, bitcoin::Network::Signet)]
     #[case::regtest(Network::Regtest, bitcoin::Network::Regtest)]
     fn test_new(#[case] network: Network, #[case] expected: bitcoin::Network) {
         let (seed, path) = get_seed();
@@ -83,6 +95,21 @@ mod test {
         assert!(wallet.decode_address(address).is_ok());
     }
 
+    #[rstest]
+    #[case::mainnet(Network::Mainnet, "bc1qe8tncvy59ea8yzccjlgdcdgy0susy6rvcttj8d")]
+    #[case::testnet(Network::Testnet, "tb1qe8tncvy59ea8yzccjlgdcdgy0susy6rvjdspu7")]
+    #[case::testnet4(Network::Testnet4, "tb1qe8tncvy59ea8yzccjlgdcdgy0susy6rvjdspu7")]
+    #[case::signet(Network::Signet, "tb1qe8tncvy59ea8yzccjlgdcdgy0susy6rvjdspu7")]
+    #[case::regtest(Network::Regtest, "bcrt1qe8tncvy59ea8yzccjlgdcdgy0susy6rvsyfvth")]
+    fn test_derive_address(#[case] network: Network, #[case] expected: &str) {
+        let (seed, path) = get_seed();
+        let wallet = Bitcoin::new(network, &seed, path).unwrap();
+
+        let address = derive_address(&wallet, 0);
+        assert_eq!(address.to_string(), expected);
+        assert!(wallet.decode_address(expected).is_ok());
+    }
+
     #[test]
     fn test_decode_address_invalid() {
         let (seed, path) = get_seed();
@@ -0,0 +1,213 @@
+use crate::wallet::keys::child_path;
+use anyhow::{Result, anyhow};
+use bitcoin::bip32::{DerivationPath, Xpub};
+use bitcoin::key::Secp256k1;
+use bitcoin::{CompressedPublicKey, PublicKey, ScriptBuf};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptType {
+    Pkh,
+    Wpkh,
+    // Key path spends only
+    Tr,
+}
+
+impl ScriptType {
+    fn name(&self) -> &'static str {
+        match self {
+            ScriptType::Pkh => "pkh",
+            ScriptType::Wpkh => "wpkh",
+            ScriptType::Tr => "tr",
+        }
+    }
+}
+
+// Ranged descriptor of the form "wpkh(xpub/0/*)"
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputDescriptor {
+    script_type: ScriptType,
+    xpub: Xpub,
+    // Derivation path from the xpub without the wildcard
+    path: String,
+}
+
+impl OutputDescriptor {
+    pub fn script_pubkey(&self, index: u32) -> Result<Vec<u8>> {
+        let secp = Secp256k1::verification_only();
+        let key = self
+            .xpub
+            .derive_pub(&secp, &child_path(&self.path, index as u64)?)?
+            .public_key;
+
+        Ok(match self.script_type {
+            ScriptType::Pkh => ScriptBuf::new_p2pkh(&PublicKey::new(key).pubkey_hash()),
+            ScriptType::Wpkh => ScriptBuf::new_p2wpkh(&CompressedPublicKey(key).wpubkey_hash()),
+            ScriptType::Tr => ScriptBuf::new_p2tr(&secp, key.x_only_public_key().0, None),
+        }
+        .into_bytes())
+    }
+
+    // Scripts of all indexes up to the gap limit, positioned by their index
+    pub fn script_pubkeys(&self, gap_limit: u32) -> Result<Vec<Vec<u8>>> {
+        (0..gap_limit)
+            .map(|index| self.script_pubkey(index))
+            .collect()
+    }
+}
+
+impl FromStr for OutputDescriptor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (script_type, inner) = [ScriptType::Pkh, ScriptType::Wpkh, ScriptType::Tr]
+            .into_iter()
+            .find_map(|script_type| {
+                s.strip_prefix(script_type.name())
+                    .and_then(|rest| rest.strip_prefix('('))
+                    .and_then(|rest| rest.strip_suffix(')'))
+                    .map(|inner| (script_type, inner))
+            })
+            .ok_or_else(|| anyhow!("unsupported descriptor: {}", s))?;
+
+        let inner = inner
+            .strip_suffix("/*")
+            .ok_or_else(|| anyhow!("descriptor has to be ranged: {}", s))?;
+        let (xpub, path) = match inner.split_once('/') {
+            Some((xpub, path)) => (xpub, format!("m/{path}")),
+            None => (inner, "m".to_string()),
+        };
+
+        let derivation_path = DerivationPath::from_str(&path)?;
+        if derivation_path.into_iter().any(|child| child.is_hardened()) {
+            return Err(anyhow!("hardened derivation is not possible from an xpub"));
+        }
+
+        Ok(Self {
+            script_type,
+            xpub: Xpub::from_str(xpub)?,
+            path,
+        })
+    }
+}
+
+impl Display for OutputDescriptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.path.strip_prefix("m/") {
+            Some(path) => write!(f, "{}({}/{}/*)", self.script_type.name(), self.xpub, path),
+            None => write!(f, "{}({}/*)", self.script_type.name(), self.xpub),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::wallet::keys::Keys;
+    use crate::wallet::keys::test::get_seed;
+    use bitcoin::NetworkKind;
+    use bitcoin::bip32::Xpriv;
+    use rstest::rstest;
+
+    fn master_xpub() -> Xpub {
+        Xpub::from_priv(
+            &Secp256k1::signing_only(),
+            &Xpriv::new_master(NetworkKind::Main, &get_seed()).unwrap(),
+        )
+    }
+
+    fn key(index: u64) -> bitcoin::secp256k1::PublicKey {
+        Keys::new(&get_seed(), "m/0/0".to_string())
+            .unwrap()
+            .derive_key(index)
+            .unwrap()
+            .private_key
+            .public_key(&Secp256k1::signing_only())
+    }
+
+    #[rstest]
+    #[case("pkh", ScriptType::Pkh)]
+    #[case("wpkh", ScriptType::Wpkh)]
+    #[case("tr", ScriptType::Tr)]
+    fn test_from_str(#[case] name: &str, #[case] script_type: ScriptType) {
+        let descriptor =
+            OutputDescriptor::from_str(&format!("{}({}/0/0/*)", name, master_xpub())).unwrap();
+        assert_eq!(
+            descriptor,
+            OutputDescriptor {
+                script_type,
+                xpub: master_xpub(),
+                path: "m/0/0".to_string(),
+            }
+        );
+        assert_eq!(
+            descriptor.to_string(),
+            format!("{}({}/0/0/*)", name, master_xpub())
+        );
+    }
+
+    #[test]
+    fn test_from_str_no_path() {
+        let descriptor = OutputDescriptor::from_str(&format!("wpkh({}/*)", master_xpub())).unwrap();
+        assert_eq!(descriptor.path, "m");
+        assert_eq!(descriptor.to_string(), format!("wpkh({}/*)", master_xpub()));
+    }
+
+    #[rstest]
+    #[case("sh(wpkh(XPUB/*))", "unsupported descriptor: sh(wpkh(XPUB/*))")]
+    #[case("wpkh(XPUB/*", "unsupported descriptor: wpkh(XPUB/*")]
+    #[case("wpkh(XPUB/0)", "descriptor has to be ranged: wpkh(XPUB/0)")]
+    #[case("wpkh(XPUB/0'/*)", "hardened derivation is not possible from an xpub")]
+    fn test_from_str_invalid(#[case] descriptor: &str, #[case] expected: &str) {
+        let xpub = master_xpub().to_string();
+        assert_eq!(
+            OutputDescriptor::from_str(&descriptor.replace("XPUB", &xpub))
+                .err()
+                .unwrap()
+                .to_string(),
+            expected.replace("XPUB", &xpub)
+        );
+    }
+
+    #[test]
+    fn test_from_str_invalid_xpub() {
+        assert!(OutputDescriptor::from_str("wpkh(xpub/0/*)").is_err());
+    }
+
+    #[test]
+    fn test_script_pubkey() {
+        let descriptor =
+            OutputDescriptor::from_str(&format!("wpkh({}/0/0/*)", master_xpub())).unwrap();
+
+        // Derived from the xpub like the wallet derives its private keys
+        for index in 0..3 {
+            assert_eq!(
+                descriptor.script_pubkey(index).unwrap(),
+                ScriptBuf::new_p2wpkh(&CompressedPublicKey(key(index as u64)).wpubkey_hash())
+                    .into_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn test_script_pubkeys() {
+        let descriptor =
+            OutputDescriptor::from_str(&format!("tr({}/0/0/*)", master_xpub())).unwrap();
+
+        let scripts = descriptor.script_pubkeys(5).unwrap();
+        assert_eq!(scripts.len(), 5);
+        assert_eq!(scripts[4], descriptor.script_pubkey(4).unwrap());
+        assert_eq!(
+            scripts[1],
+            ScriptBuf::new_p2tr(
+                &Secp256k1::verification_only(),
+                key(1).x_only_public_key().0,
+                None
+            )
+            .into_bytes()
+        );
+    }
+}
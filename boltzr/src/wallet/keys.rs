@@ -20,13 +20,16 @@ impl Keys {
     }
 
     pub fn derive_key(&self, index: u64) -> Result<Xpriv> {
-        Ok(self.xpriv.derive_priv(
-            &self.secp,
-            &DerivationPath::from_str(&format!("{}/{}", self.path, index))?,
-        )?)
+        Ok(self
+            .xpriv
+            .derive_priv(&self.secp, &child_path(&self.path, index)?)?)
     }
 }
 
+pub fn child_path(path: &str, index: u64) -> Result<DerivationPath> {
+    Ok(DerivationPath::from_str(&format!("{path}/{index}"))?)
+}
+
 #[cfg(test)]
 pub mod test {
     use crate::wallet::keys::Keys;
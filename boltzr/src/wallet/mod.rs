@@ -2,16 +2,19 @@ use ::bitcoin::bip32::Xpriv;
 use anyhow::Result;
 
 mod bitcoin;
+mod descriptor;
 mod elements;
 mod keys;
 
 pub use bitcoin::*;
+pub use descriptor::*;
 pub use elements::*;
 
 #[derive(PartialEq, Debug, Clone, Copy)]
 pub enum Network {
     Mainnet,
     Testnet,
+    Testnet4,
     Signet,
     Regtest,
 }
@@ -28,6 +31,7 @@ impl Network {
             Network::Mainnet => ::bitcoin::Network::Bitcoin,
             Network::Signet => ::bitcoin::Network::Signet,
             Network::Testnet => ::bitcoin::Network::Testnet,
+            Network::Testnet4 => ::bitcoin::Network::Testnet4,
             Network::Regtest => ::bitcoin::Network::Regtest,
         }
     }
@@ -40,6 +44,9 @@ impl Network {
             Network::Signet => Err(anyhow::anyhow!(
                 "Signet is not supported for liquid addresses"
             )),
+            Network::Testnet4 => Err(anyhow::anyhow!(
+                "Testnet4 is not supported for liquid addresses"
+            )),
         }
     }
 }
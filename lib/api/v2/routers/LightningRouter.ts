@@ -107,12 +107,17 @@
  *     responses:
  *       '200':
  *         description: Information about the lightning node
+ *         headers:
+ *           Cache-Control:
+ *             schema:
+ *               type: string
+ *             description: Set to "max-age" with the seconds until the cached information about the node expires, when that is known
  *         content:
  *           application/json:
  *             schema:
  *               $ref: '#/components/schemas/LightningNode'
  *       '400':
- *         description: Unprocessable request parameters
+ *         description: Unprocessable request parameters or a currency that is not supported
  *         content:
  *           application/json:
  *             schema:
@@ -123,6 +128,12 @@
  *           application/json:
  *             schema:
  *               $ref: '#/components/schemas/ErrorResponse'
+ *       '503':
+ *         description: When the gossip of the currency is not loaded yet
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
  */
 
 /**
@@ -152,13 +163,19 @@
  *             schema:
  *               $ref: '#/components/schemas/LightningChannelInfo'
  *       '400':
- *         description: Unprocessable request parameters
+ *         description: Unprocessable request parameters or a currency that is not supported
  *         content:
  *           application/json:
  *             schema:
  *               $ref: '#/components/schemas/ErrorResponse'
  *       '404':
- *         description: When the channel cannot be found or the currency is not supported
+ *         description: When the channel cannot be found
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
+ *       '503':
+ *         description: When the gossip of the currency is not loaded yet
  *         content:
  *           application/json:
  *             schema:
@@ -184,6 +201,12 @@
  *         schema:
  *           type: string
  *         description: Public key of the node to get channels for
+ *       - in: query
+ *         name: maxAgeSecs
+ *         required: false
+ *         schema:
+ *           type: number
+ *         description: Skip channels whose policy was not updated for longer than that many seconds
  *     responses:
  *       '200':
  *         description: Channels of the lightning node
@@ -194,7 +217,75 @@
  *               items:
  *                 $ref: '#/components/schemas/LightningChannel'
  *       '400':
- *         description: Unprocessable acceptable request parameters
+ *         description: Unprocessable acceptable request parameters or a currency that is not supported
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
+ *       '404':
+ *         description: When the node cannot be found
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
+ *       '503':
+ *         description: When the gossip of the currency is not loaded yet
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
+ */
+
+/**
+ * @openapi
+ * /lightning/{currency}/channels/{node}/fees:
+ *   get:
+ *     tags: [Lightning]
+ *     description: Gets what the active channels to a lightning node charge for forwarding an amount
+ *     parameters:
+ *       - in: path
+ *         name: currency
+ *         required: true
+ *         schema:
+ *           type: string
+ *         description: Currency of the lightning network to use
+ *       - in: path
+ *         name: node
+ *         required: true
+ *         schema:
+ *           type: string
+ *         description: Public key of the node to get channel fees for
+ *       - in: query
+ *         name: amountMsat
+ *         required: true
+ *         schema:
+ *           type: number
+ *         description: Amount to forward in millisatoshi
+ *     responses:
+ *       '200':
+ *         description: Fees of the active channels of the lightning node
+ *         content:
+ *           application/json:
+ *             schema:
+ *               type: array
+ *               items:
+ *                 type: object
+ *                 required: ["shortChannelId", "source", "baseFeeSat", "feeMsat"]
+ *                 properties:
+ *                   shortChannelId:
+ *                     type: string
+ *                     description: ID of the channel
+ *                   source:
+ *                     type: string
+ *                     description: Public key of the node that charges the fee
+ *                   baseFeeSat:
+ *                     type: number
+ *                     description: Base fee of the channel in satoshi
+ *                   feeMsat:
+ *                     type: number
+ *                     description: Total fee for forwarding the amount in millisatoshi
+ *       '400':
+ *         description: When the currency is not supported
  *         content:
  *           application/json:
  *             schema:
@@ -205,6 +296,89 @@
  *           application/json:
  *             schema:
  *               $ref: '#/components/schemas/ErrorResponse'
+ *       '503':
+ *         description: When the gossip of the currency is not loaded yet
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
+ */
+
+/**
+ * @openapi
+ * /lightning/gossip:
+ *   get:
+ *     tags: [Lightning]
+ *     description: Lists the currencies with loaded lightning gossip
+ *     responses:
+ *       '200':
+ *         description: Currencies with loaded lightning gossip
+ *         content:
+ *           application/json:
+ *             schema:
+ *               type: array
+ *               items:
+ *                 type: object
+ *                 required: ["symbol"]
+ *                 properties:
+ *                   symbol:
+ *                     type: string
+ *                     description: Symbol of the currency
+ *                   nodeCount:
+ *                     type: number
+ *                     description: Number of nodes in the gossip of the currency
+ */
+
+/**
+ * @openapi
+ * /lightning/{currency}/gossip:
+ *   get:
+ *     tags: [Lightning]
+ *     description: Gets statistics about the last refresh of the lightning gossip of a currency
+ *     parameters:
+ *       - in: path
+ *         name: currency
+ *         required: true
+ *         schema:
+ *           type: string
+ *         description: Currency of the lightning network to use
+ *     responses:
+ *       '200':
+ *         description: Statistics about the lightning gossip
+ *         content:
+ *           application/json:
+ *             schema:
+ *               type: object
+ *               required: ["nodeCount", "channelCount", "lastRefreshDurationMs", "lastRefresh", "lastRefreshPhases"]
+ *               properties:
+ *                 nodeCount:
+ *                   type: number
+ *                   description: Number of nodes in the gossip
+ *                 channelCount:
+ *                   type: number
+ *                   description: Number of channels in the gossip
+ *                 lastRefreshDurationMs:
+ *                   type: number
+ *                   description: How long the last refresh took in milliseconds
+ *                 lastRefresh:
+ *                   type: number
+ *                   description: UNIX timestamp of the last refresh
+ *                 lastRefreshPhases:
+ *                   type: object
+ *                   description: How long the phases of the last refresh took in milliseconds
+ *                   properties:
+ *                     nodeFetchMs:
+ *                       type: number
+ *                     channelFetchMs:
+ *                       type: number
+ *                     cacheWriteMs:
+ *                       type: number
+ *       '404':
+ *         description: When there is no gossip for the currency
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
  */
 
 /**
@@ -236,13 +410,142 @@
  *               items:
  *                 $ref: '#/components/schemas/LightningNode'
  *       '400':
- *         description: Invalid request parameters
+ *         description: Invalid request parameters or a currency that is not supported
  *         content:
  *           application/json:
  *             schema:
  *               $ref: '#/components/schemas/ErrorResponse'
  *       '404':
- *         description: When the node cannot be found or the currency is not supported
+ *         description: When the node cannot be found
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
+ *       '503':
+ *         description: When the gossip of the currency is not loaded yet
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
+ */
+
+/**
+ * @openapi
+ * /lightning/{currency}/nodes/top:
+ *   get:
+ *     tags: [Lightning]
+ *     description: Gets the lightning nodes with the most public channel capacity
+ *     parameters:
+ *       - in: path
+ *         name: currency
+ *         required: true
+ *         schema:
+ *           type: string
+ *         description: Currency of the lightning network to use
+ *       - in: query
+ *         name: limit
+ *         required: false
+ *         schema:
+ *           type: number
+ *         description: Maximum number of nodes to return; defaults to 10 and is capped at 100
+ *     responses:
+ *       '200':
+ *         description: Lightning nodes sorted by their public channel capacity
+ *         content:
+ *           application/json:
+ *             schema:
+ *               type: array
+ *               items:
+ *                 allOf:
+ *                   - $ref: '#/components/schemas/LightningNode'
+ *                   - type: object
+ *                     required: ["capacity", "channelCount"]
+ *                     properties:
+ *                       capacity:
+ *                         type: number
+ *                         description: Summed capacity of the public channels of the node in satoshi
+ *                       channelCount:
+ *                         type: number
+ *                         description: Number of public channels of the node
+ *       '400':
+ *         description: When the currency is not supported
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
+ *       '503':
+ *         description: When the gossip of the currency is not loaded yet
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
+ */
+
+/**
+ * @openapi
+ * /lightning/{currency}/graph:
+ *   get:
+ *     tags: [Lightning]
+ *     description: Gets a page of the lightning graph. Nodes are sorted by public key and channels by ID; pass the returned cursors to get the next page
+ *     parameters:
+ *       - in: path
+ *         name: currency
+ *         required: true
+ *         schema:
+ *           type: string
+ *         description: Currency of the lightning network to use
+ *       - in: query
+ *         name: afterNode
+ *         required: false
+ *         schema:
+ *           type: string
+ *         description: Only return nodes with a public key after this one
+ *       - in: query
+ *         name: afterChannel
+ *         required: false
+ *         schema:
+ *           type: string
+ *         description: Only return channels with an ID after this one
+ *       - in: query
+ *         name: limit
+ *         required: false
+ *         schema:
+ *           type: number
+ *         description: Maximum number of nodes and of channels in the page; defaults to 1000 and is capped at 5000
+ *     responses:
+ *       '200':
+ *         description: A page of the lightning graph
+ *         content:
+ *           application/json:
+ *             schema:
+ *               type: object
+ *               required: ["nodes", "channels", "hasMore"]
+ *               properties:
+ *                 nodes:
+ *                   type: array
+ *                   items:
+ *                     $ref: '#/components/schemas/LightningNode'
+ *                 channels:
+ *                   type: array
+ *                   items:
+ *                     $ref: '#/components/schemas/LightningChannelInfo'
+ *                 nextNode:
+ *                   type: string
+ *                   description: Value for "afterNode" to get the next page
+ *                 nextChannel:
+ *                   type: string
+ *                   description: Value for "afterChannel" to get the next page
+ *                 hasMore:
+ *                   type: boolean
+ *                   description: Whether there might be more nodes or channels after this page
+ *       '400':
+ *         description: When the currency is not supported
+ *         content:
+ *           application/json:
+ *             schema:
+ *               $ref: '#/components/schemas/ErrorResponse'
+ *       '503':
+ *         description: When the gossip of the currency is not loaded yet
  *         content:
  *           application/json:
  *             schema: